@@ -1,3 +1,5 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use rayon::prelude::*;
 use regex::Regex;
 use rusqlite::{params, Connection, OpenFlags};
 use rustfft::{num_complex::Complex, FftPlanner};
@@ -7,7 +9,9 @@ use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 use symphonia::core::audio::SampleBuffer;
 use symphonia::core::codecs::DecoderOptions;
 use symphonia::core::formats::FormatOptions;
@@ -22,6 +26,43 @@ use zip::CompressionMethod;
 
 pub struct AppState {
     pub db: Mutex<Connection>,
+    pub busy: AtomicUsize, // 실행 중인 임포트/익스포트 수 (자동 동기화 일시 중지용)
+    pub watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    pub jobs: Mutex<std::collections::BTreeMap<u64, JobEntry>>,
+    pub next_job_id: AtomicU64,
+    pub classifier_retrain: AtomicU64, // 분류기 재학습 요청 세대 (디바운스용)
+}
+
+/// 임포트 시 오디오 파일을 라이브러리에 두는 방식 (팩 단위로 저장)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageMode {
+    #[default]
+    Copy,      // ~/Slice로 복사 (기본)
+    Hardlink,  // 같은 파일시스템이면 하드링크, 아니면 복사
+    Symlink,   // 원본을 가리키는 심볼릭 링크
+    Reference, // 복사 없이 원본 경로를 그대로 참조
+}
+
+impl StorageMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            StorageMode::Copy => "copy",
+            StorageMode::Hardlink => "hardlink",
+            StorageMode::Symlink => "symlink",
+            StorageMode::Reference => "reference",
+        }
+    }
+
+    /// DB 값 → StorageMode (NULL/알 수 없는 값은 copy)
+    fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("hardlink") => StorageMode::Hardlink,
+            Some("symlink") => StorageMode::Symlink,
+            Some("reference") => StorageMode::Reference,
+            _ => StorageMode::Copy,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,6 +73,7 @@ pub struct Pack {
     pub cover_url: Option<String>,
     pub sample_count: usize,
     pub created_at: Option<String>,
+    pub storage_mode: StorageMode,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -40,7 +82,7 @@ pub struct Sample {
     pub local_path: String,
     pub filename: String,
     pub audio_key: Option<String>,
-    pub bpm: Option<i32>,
+    pub bpm: Option<f64>,
     pub chord_type: Option<String>,
     pub duration: Option<i64>, // milliseconds
     pub genre: Option<String>,
@@ -50,6 +92,29 @@ pub struct Sample {
     pub pack_name: Option<String>,
     pub pack_genre: Option<String>,
     pub created_at: Option<String>,
+    pub provenance: SampleProvenance,
+    /// 루프 길이에 맞는 마디 수 (4/4)
+    #[serde(default)]
+    pub bars: Option<i32>,
+    /// 파일명 BPM이 루프 길이와 맞지 않음
+    #[serde(default)]
+    pub bpm_mismatch: bool,
+}
+
+/// 메타데이터 필드 값의 출처와 신뢰도 (0.0–1.0)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldProvenance {
+    pub source: String, // "splice" | "embedded" | "filename" | "path" | "pack" | "audio" | "user" | "unknown"
+    pub confidence: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SampleProvenance {
+    pub bpm: Option<FieldProvenance>,
+    pub audio_key: Option<FieldProvenance>,
+    pub sample_type: Option<FieldProvenance>,
+    pub genre: Option<FieldProvenance>,
+    pub tags: Option<FieldProvenance>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -59,6 +124,35 @@ pub struct Playlist {
     pub color: Option<String>,
     pub sample_count: usize,
     pub created_at: Option<String>,
+    /// 스마트 플레이리스트 규칙 (None이면 일반 플레이리스트)
+    pub rules: Option<SmartPlaylistRules>,
+}
+
+/// 스마트 플레이리스트 규칙 (playlists.rules에 JSON으로 저장, camelCase)
+/// 조건은 모두 AND, 목록 조건은 그 안에서 OR
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SmartPlaylistRules {
+    /// 규칙 스키마 버전 (SMART_RULES_VERSION)
+    pub version: u32,
+    pub q: Option<String>,
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    pub instruments: Vec<String>,
+    pub bpm_min: Option<f64>,
+    pub bpm_max: Option<f64>,
+    pub keys: Vec<String>,
+    pub pack_uuid: Option<String>,
+    pub genres: Vec<String>,
+    #[serde(rename = "type")]
+    pub sample_type: Option<String>,
+    /// created_at 기준 ("2024-05-01" 또는 "2024-05-01 12:00:00")
+    pub added_after: Option<String>,
+    pub added_before: Option<String>,
+    pub sort_by: Option<String>,
+    pub sort_dir: Option<String>,
+    /// 최대 샘플 수 (정렬 후 앞에서부터)
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -67,6 +161,16 @@ pub struct LibraryData {
     pub total_samples: usize,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SpliceDbInfo {
+    pub path: String,
+    pub account: String, // users/<계정>/<해시> 의 "<계정>/<해시>"
+    pub pack_count: usize,
+    pub sample_count: usize,
+    pub selected: bool,
+    pub is_override: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LibraryStatus {
     pub has_data: bool,
@@ -83,6 +187,7 @@ pub struct ImportProgress {
     pub current_pack: usize,
     pub total_packs: usize,
     pub current_pack_name: String,
+    pub job_id: Option<u64>, // 자동 동기화는 None
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -90,6 +195,18 @@ pub struct ImportResult {
     pub files_copied: usize,
     pub files_skipped: usize,
     pub total_packs: usize,
+    pub added: usize,   // 새로 추가된 샘플 수
+    pub updated: usize, // 메타데이터/경로가 갱신된 샘플 수
+    pub removed: usize, // 원본에서 사라져 제거된 샘플 수
+    pub failed: Vec<String>, // 배치하지 못해 건너뛴 파일의 오류 메시지
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LibraryChanged {
+    pub added_sample_ids: Vec<i64>,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -104,6 +221,119 @@ pub struct ExportProgress {
     pub current: usize,
     pub total: usize,
     pub current_file: String,
+    pub job_id: Option<u64>,
+    /// ZIP에 쓴 오디오 바이트 / 전체 오디오 바이트
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    SpliceImport,
+    ExternalImport,
+    Export,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobInfo {
+    pub id: u64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub current: usize,
+    pub total: usize,
+    pub current_file: String,
+    pub result: Option<serde_json::Value>, // ImportResult 또는 내보낸 파일 수
+    pub error: Option<String>,
+    pub started_at: u64, // unix seconds
+    pub finished_at: Option<u64>,
+}
+
+pub struct JobEntry {
+    info: JobInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelocateProgress {
+    pub current: usize,
+    pub total: usize,
+    pub current_file: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanProgress {
+    pub current: usize,
+    pub total: usize,
+    pub current_file: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthIssue {
+    pub sample_id: i64,
+    pub filename: String,
+    pub local_path: String,
+    pub pack_name: Option<String>,
+    pub issue: String, // "missing" | "empty" | "undecodable"
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthReport {
+    pub scanned: usize,
+    pub issues: Vec<HealthIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeyDetection {
+    pub sample_id: i64,
+    pub audio_key: Option<String>,
+    pub chord_type: Option<String>,
+    pub confidence: f32,
+    pub applied: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldChange {
+    pub field: String, // "bpm" | "key" | "sample_type" | "genre" | "tags"
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub source: String,
+    pub confidence: f32,
+    pub applied: bool, // false면 사용자 편집/Splice 값이라 보존됨
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReanalyzeDiff {
+    pub sample_id: i64,
+    pub filename: String,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelinkCandidate {
+    pub path: String,
+    pub score: f32, // 0.0–1.0
+    pub name_match: bool,
+    pub size_match: bool,
+    pub fingerprint_score: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelinkProposal {
+    pub sample_id: i64,
+    pub filename: String,
+    pub old_path: String,
+    pub candidates: Vec<RelinkCandidate>, // 점수 내림차순
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -128,15 +358,75 @@ fn get_home_dir() -> Result<PathBuf, String> {
     dirs::home_dir().ok_or_else(|| "홈 디렉토리를 찾을 수 없습니다".to_string())
 }
 
+/// 앱 데이터 폴더 (slice.db 위치, 라이브러리 루트 기본값)
 fn get_slice_path() -> Result<PathBuf, String> {
     Ok(get_home_dir()?.join("Slice"))
 }
 
-/// Splice가 sounds.db를 저장하는 가능한 경로들을 탐색
-fn find_splice_db() -> Result<PathBuf, String> {
+fn get_db_path() -> Result<PathBuf, String> {
+    Ok(get_slice_path()?.join("slice.db"))
+}
+
+/// 다른 연결이 쓰기 중일 때 대기하는 시간
+const DB_BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 백그라운드 작업용 별도 DB 연결 — 공유 Mutex<Connection>을 작업 내내 잡지 않도록
+fn open_job_db() -> Result<Connection, String> {
+    let conn = Connection::open(get_db_path()?).map_err(|e| format!("DB 열기 실패: {}", e))?;
+    conn.busy_timeout(DB_BUSY_TIMEOUT).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+/// 오디오 파일이 저장되는 라이브러리 루트 (설정이 없으면 ~/Slice)
+fn get_library_root(db: &Connection) -> PathBuf {
+    get_setting(db, SETTING_LIBRARY_ROOT)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| get_slice_path().unwrap_or_else(|_| PathBuf::from("Slice")))
+}
+
+/// 절대 경로 → DB 저장용 경로
+/// 라이브러리 루트 하위면 '/' 구분 상대 경로, 밖이면(reference 등) 절대 경로 그대로
+fn to_library_path(root: &Path, path: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(rel) => rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+/// DB 저장 경로 → 절대 경로
+fn resolve_library_path(root: &Path, stored: &str) -> String {
+    if Path::new(stored).is_absolute() {
+        return stored.to_string();
+    }
+    stored
+        .split('/')
+        .fold(root.to_path_buf(), |p, part| p.join(part))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// 하위 빈 디렉토리 재귀 삭제 (dir 자체는 유지)
+fn remove_empty_dirs(dir: &Path) {
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                remove_empty_dirs(&path);
+                let _ = std::fs::remove_dir(&path); // 비어있을 때만 성공
+            }
+        }
+    }
+}
+
+/// Splice가 계정 데이터를 저장하는 가능한 `users/` 디렉토리들
+fn splice_users_dirs() -> Vec<PathBuf> {
     let mut candidates: Vec<PathBuf> = Vec::new();
 
-    // macOS: ~/Library/Application Support/com.splice.Splice/users/default/
+    // macOS: ~/Library/Application Support/com.splice.Splice/users/
     #[cfg(target_os = "macos")]
     {
         if let Ok(home) = get_home_dir() {
@@ -144,71 +434,103 @@ fn find_splice_db() -> Result<PathBuf, String> {
                 home.join("Library")
                     .join("Application Support")
                     .join("com.splice.Splice")
-                    .join("users")
-                    .join("default"),
+                    .join("users"),
             );
         }
     }
 
-    // Windows: %LOCALAPPDATA%\SpliceSettings\users\default\
-    //          %APPDATA%\com.splice.Splice\users\default\
+    // Windows: %LOCALAPPDATA%\SpliceSettings\users\
+    //          %APPDATA%\com.splice.Splice\users\
     #[cfg(target_os = "windows")]
     {
         if let Some(local_app) = dirs::data_local_dir() {
-            candidates.push(
-                local_app
-                    .join("SpliceSettings")
-                    .join("users")
-                    .join("default"),
-            );
-            candidates.push(
-                local_app
-                    .join("Splice")
-                    .join("users")
-                    .join("default"),
-            );
+            candidates.push(local_app.join("SpliceSettings").join("users"));
+            candidates.push(local_app.join("Splice").join("users"));
         }
         if let Some(app_data) = dirs::data_dir() {
-            candidates.push(
-                app_data
-                    .join("com.splice.Splice")
-                    .join("users")
-                    .join("default"),
-            );
+            candidates.push(app_data.join("com.splice.Splice").join("users"));
         }
     }
 
-    // Linux: ~/.config/com.splice.Splice/users/default/ (만약 지원된다면)
+    // Linux: 공식 클라이언트가 없으므로 일반적인 설정 경로 + Wine 프리픽스 탐색
+    // (못 찾으면 설정에서 sounds.db 경로를 직접 지정)
     #[cfg(target_os = "linux")]
     {
         if let Some(config) = dirs::config_dir() {
-            candidates.push(
-                config
-                    .join("com.splice.Splice")
-                    .join("users")
-                    .join("default"),
-            );
+            candidates.push(config.join("com.splice.Splice").join("users"));
+            candidates.push(config.join("Splice").join("users"));
+        }
+        if let Some(data) = dirs::data_dir() {
+            candidates.push(data.join("com.splice.Splice").join("users"));
+        }
+        if let Ok(home) = get_home_dir() {
+            if let Ok(entries) = std::fs::read_dir(home.join(".wine").join("drive_c").join("users")) {
+                for entry in entries.flatten() {
+                    let app_data = entry.path().join("AppData");
+                    candidates.push(app_data.join("Local").join("SpliceSettings").join("users"));
+                    candidates.push(app_data.join("Roaming").join("com.splice.Splice").join("users"));
+                }
+            }
         }
     }
 
-    for base in &candidates {
-        if !base.exists() {
+    candidates
+}
+
+/// 자동 탐지된 모든 sounds.db 경로 (users/<계정>/<해시>/sounds.db)
+fn find_splice_dbs() -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for base in splice_users_dirs() {
+        let Ok(users) = std::fs::read_dir(&base) else {
             continue;
-        }
-        // users/default/ 하위에 해시 디렉토리가 있고, 그 안에 sounds.db가 있음
-        if let Ok(entries) = std::fs::read_dir(base) {
-            for entry in entries.flatten() {
-                if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
-                    let db_path = entry.path().join("sounds.db");
-                    if db_path.exists() {
-                        return Ok(db_path);
+        };
+        for user in users.flatten() {
+            if !user.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+                continue;
+            }
+            // users/<계정>/ 하위에 해시 디렉토리가 있고, 그 안에 sounds.db가 있음
+            if let Ok(entries) = std::fs::read_dir(user.path()) {
+                for entry in entries.flatten() {
+                    if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+                        let db_path = entry.path().join("sounds.db");
+                        if db_path.exists() && !found.contains(&db_path) {
+                            found.push(db_path);
+                        }
                     }
                 }
             }
         }
     }
+    found
+}
+
+/// 임포트/감시에 사용할 sounds.db 목록
+/// 사용자가 선택한 DB → 수동 지정 경로 → 자동 탐지된 첫 번째 DB 순으로 결정
+fn resolve_splice_dbs(db: &Connection) -> Result<Vec<PathBuf>, String> {
+    let selected: Vec<PathBuf> = get_setting(db, SETTING_SPLICE_DB_SELECTION)
+        .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .filter(|p| p.exists())
+        .collect();
+    if !selected.is_empty() {
+        return Ok(selected);
+    }
+
+    if let Some(path) = get_setting(db, SETTING_SPLICE_DB_OVERRIDE).map(PathBuf::from) {
+        if path.exists() {
+            return Ok(vec![path]);
+        }
+    }
 
-    Err("Splice sounds.db를 찾을 수 없습니다. Splice 앱이 설치되어 있는지 확인해주세요.".to_string())
+    find_splice_dbs()
+        .into_iter()
+        .next()
+        .map(|p| vec![p])
+        .ok_or_else(|| {
+            "Splice sounds.db를 찾을 수 없습니다. Splice 앱이 설치되어 있는지 확인하거나 설정에서 경로를 직접 지정해주세요.".to_string()
+        })
 }
 
 /// Splice 다운로드 폴더 경로 (오디오 파일이 저장되는 곳)
@@ -251,6 +573,10 @@ fn get_splice_sounds_dir() -> Result<PathBuf, String> {
 // ── DB helpers ──────────────────────────────────────────────────────
 
 fn init_db(db: &Connection) -> Result<(), String> {
+    // WAL: 백그라운드 작업이 쓰는 동안에도 UI 조회가 막히지 않도록
+    let _ = db.query_row("PRAGMA journal_mode=WAL", [], |row| row.get::<_, String>(0));
+    let _ = db.busy_timeout(DB_BUSY_TIMEOUT);
+
     db.execute_batch(
         "CREATE TABLE IF NOT EXISTS packs (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -266,7 +592,7 @@ fn init_db(db: &Connection) -> Result<(), String> {
             local_path TEXT NOT NULL,
             filename TEXT NOT NULL,
             audio_key TEXT,
-            bpm INTEGER,
+            bpm REAL,
             chord_type TEXT,
             duration INTEGER,
             file_hash TEXT UNIQUE,
@@ -311,12 +637,234 @@ fn init_db(db: &Connection) -> Result<(), String> {
 
     // Migration: playlists.color 컬럼 추가
     let _ = db.execute("ALTER TABLE playlists ADD COLUMN color TEXT", []);
+    // Migration: 스마트 플레이리스트 규칙 (버전 포함 JSON, NULL이면 일반 플레이리스트)
+    let _ = db.execute("ALTER TABLE playlists ADD COLUMN rules TEXT", []);
 
     // Migration: waveform_colors 컬럼 추가 (기존 DB 호환)
     let _ = db.execute("ALTER TABLE samples ADD COLUMN waveform_colors TEXT", []);
     // v2: 주파수 분석 알고리즘 변경 — 기존 캐시 무효화 (에너지 밀도 기반)
     let _ = db.execute("UPDATE samples SET waveform_colors = NULL WHERE waveform_colors IS NOT NULL", []);
 
+    // Migration: 증분 동기화용 컬럼 — source ("splice"/"external"), user_edited (사용자 수정 여부)
+    let _ = db.execute("ALTER TABLE samples ADD COLUMN source TEXT", []);
+    let _ = db.execute("ALTER TABLE samples ADD COLUMN user_edited INTEGER DEFAULT 0", []);
+    let _ = db.execute("ALTER TABLE packs ADD COLUMN source TEXT", []);
+    let _ = db.execute("ALTER TABLE packs ADD COLUMN user_edited INTEGER DEFAULT 0", []);
+    let _ = db.execute(
+        "UPDATE samples SET source = CASE WHEN file_hash LIKE 'ext-%' THEN 'external' ELSE 'splice' END
+         WHERE source IS NULL",
+        [],
+    );
+    let _ = db.execute(
+        "UPDATE packs SET source = CASE WHEN uuid LIKE 'ext-%' THEN 'external' ELSE 'splice' END
+         WHERE source IS NULL",
+        [],
+    );
+    // 이전 버전의 전체 삭제 임포트로 고아가 된 플레이리스트 항목 정리
+    let _ = db.execute(
+        "DELETE FROM playlist_samples WHERE sample_id NOT IN (SELECT id FROM samples)",
+        [],
+    );
+
+    // Migration: 팩별 파일 저장 방식 (copy/hardlink/symlink/reference)
+    let _ = db.execute("ALTER TABLE packs ADD COLUMN storage_mode TEXT DEFAULT 'copy'", []);
+
+    // Migration: 파일 크기 (누락 파일 재연결 시 후보 비교용)
+    let _ = db.execute("ALTER TABLE samples ADD COLUMN file_size INTEGER", []);
+
+    // Migration: 소수점 BPM — INTEGER 컬럼을 REAL로 교체 (87.5 같은 값이 반올림되지 않도록)
+    migrate_bpm_to_real(db)?;
+
+    // Migration: 오디오 분석으로 감지한 키의 신뢰도 (파일명/Splice 키는 NULL)
+    let _ = db.execute("ALTER TABLE samples ADD COLUMN key_confidence REAL", []);
+
+    // Migration: 필드별 출처/신뢰도 (bpm, key, sample_type, genre, tags)
+    // user_edited는 이후 파일명 편집 여부만 의미함
+    for field in META_FIELDS {
+        let (cols, source_col, conf_col) = field.columns();
+        let _ = db.execute(&format!("ALTER TABLE samples ADD COLUMN {} TEXT", source_col), []);
+        let _ = db.execute(&format!("ALTER TABLE samples ADD COLUMN {} REAL", conf_col), []);
+        // 출처 추적 이전 값: 편집된 샘플은 user, Splice 샘플은 splice, 나머지는 임포트 당시 휴리스틱
+        let (fallback_source, fallback_conf) = match field {
+            MetaField::Key => (
+                "CASE WHEN key_confidence IS NOT NULL THEN 'audio' ELSE 'filename' END",
+                "COALESCE(key_confidence, 0.9)",
+            ),
+            MetaField::Tags => ("'path'", "0.7"),
+            _ => ("'unknown'", "NULL"),
+        };
+        let _ = db.execute(
+            &format!(
+                "UPDATE samples SET
+                    {src} = CASE WHEN COALESCE(user_edited, 0) = 1 THEN 'user'
+                                 WHEN source = 'splice' AND {conf} IS NULL THEN 'splice'
+                                 ELSE {fallback_source} END,
+                    {conf} = CASE WHEN COALESCE(user_edited, 0) = 1 THEN 1.0
+                                  WHEN source = 'splice' AND {conf} IS NULL THEN 1.0
+                                  ELSE {fallback_conf} END
+                 WHERE {col} IS NOT NULL AND {src} IS NULL",
+                src = source_col,
+                conf = conf_col,
+                col = cols[0],
+                fallback_source = fallback_source,
+                fallback_conf = fallback_conf,
+            ),
+            [],
+        );
+    }
+
+    // Migration: 루프 마디 수와 파일명 BPM/길이 모순 표시 (추가될 때 한 번 기존 샘플에 채움)
+    if db.execute("ALTER TABLE samples ADD COLUMN bars INTEGER", []).is_ok() {
+        let _ = db.execute("ALTER TABLE samples ADD COLUMN bpm_mismatch INTEGER DEFAULT 0", []);
+        let _ = backfill_loop_bars(db);
+    }
+
+    // Migration: Splice 샘플을 가져온 sounds.db (계정별로 사라진 샘플 정리 범위를 나눔)
+    let _ = db.execute("ALTER TABLE samples ADD COLUMN splice_db TEXT", []);
+
+    // 앱 설정 (key-value)
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT
+        );",
+    )
+    .map_err(|e| format!("설정 테이블 초기화 실패: {}", e))?;
+
+    // 검색 인덱스 (FTS5, rowid = samples.id)
+    init_search_index(db)?;
+
+    // 정규화 태그 테이블 (samples.tags 문자열에서 트리거로 유지)
+    init_tag_tables(db)?;
+
+    // 태그/장르 자동 부여 규칙
+    init_metadata_rules(db)?;
+
+    // 파일 내장 루프 포인트/큐 마커
+    init_sample_markers(db)?;
+
+    // Migration: 라이브러리 루트 하위 절대 경로 → 상대 경로
+    let _ = relativize_library_paths(db, &get_library_root(db));
+
+    // Migration: 원샷/타악기 샘플에서 오디오로 감지한 키 제거 (음정이 없어 신뢰할 수 없음)
+    let _ = clear_unpitched_audio_keys(db);
+
+    Ok(())
+}
+
+/// samples.bpm 컬럼 타입이 INTEGER면 REAL 컬럼으로 옮김
+/// (SQLite는 컬럼 타입 변경이 없어 이름 변경 → 새 컬럼 복사 → 이전 컬럼 삭제)
+fn migrate_bpm_to_real(db: &Connection) -> Result<(), String> {
+    let bpm_type: Option<String> = db
+        .query_row(
+            "SELECT type FROM pragma_table_info('samples') WHERE name = 'bpm'",
+            [],
+            |row| row.get(0),
+        )
+        .ok();
+    if !bpm_type.is_some_and(|t| t.eq_ignore_ascii_case("INTEGER")) {
+        return Ok(());
+    }
+    db.execute_batch(
+        "BEGIN;
+         ALTER TABLE samples RENAME COLUMN bpm TO bpm_integer;
+         ALTER TABLE samples ADD COLUMN bpm REAL;
+         UPDATE samples SET bpm = bpm_integer;
+         ALTER TABLE samples DROP COLUMN bpm_integer;
+         COMMIT;",
+    )
+    .map_err(|e| {
+        let _ = db.execute_batch("ROLLBACK");
+        format!("BPM 컬럼 변환 실패: {}", e)
+    })
+}
+
+/// 기존 루프 샘플의 마디 수/BPM 모순 계산
+/// 출처 추적 이전 값(unknown)은 파일명에서 같은 BPM이 파싱되면 파일명 BPM으로 봄
+fn backfill_loop_bars(db: &Connection) -> Result<(), String> {
+    let rows: Vec<(i64, String, f64, Option<String>, i64)> = {
+        let mut stmt = db
+            .prepare(
+                "SELECT id, filename, bpm, bpm_source, duration FROM samples
+                 WHERE bpm IS NOT NULL AND duration IS NOT NULL AND sample_type = 'loop'",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+    for (id, filename, bpm, source, duration) in rows {
+        let source = match source.as_deref() {
+            Some("unknown") | None if parse_bpm_from_filename(&filename) == Some(bpm) => {
+                Some(SOURCE_FILENAME)
+            }
+            other => other,
+        };
+        let (bars, mismatch) = loop_length_check(Some(bpm), source, Some(duration), Some("loop"));
+        db.execute(
+            "UPDATE samples SET bars = ?2, bpm_mismatch = ?3 WHERE id = ?1",
+            params![id, bars, mismatch],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// root 하위를 가리키는 절대 경로 행을 root 기준 상대 경로로 재작성
+fn relativize_library_paths(db: &Connection, root: &Path) -> Result<usize, String> {
+    let mut prefix = root.to_string_lossy().to_string();
+    if !prefix.ends_with(std::path::MAIN_SEPARATOR) {
+        prefix.push(std::path::MAIN_SEPARATOR);
+    }
+    db.execute(
+        "UPDATE samples SET local_path = replace(substr(local_path, length(?1) + 1), '\\', '/')
+         WHERE substr(local_path, 1, length(?1)) = ?1",
+        params![prefix],
+    )
+    .map_err(|e| format!("경로 변환 실패: {}", e))
+}
+
+/// 선택된 Splice DB 경로 목록 (JSON 배열)
+const SETTING_SPLICE_DB_SELECTION: &str = "splice_db_selection";
+/// 수동 지정한 sounds.db 경로
+const SETTING_SPLICE_DB_OVERRIDE: &str = "splice_db_override";
+/// 라이브러리 루트 (오디오 파일 저장 위치)
+const SETTING_LIBRARY_ROOT: &str = "library_root";
+/// 기본 태그/장르 규칙을 채웠는지 여부
+const SETTING_METADATA_RULES_SEEDED: &str = "metadata_rules_seeded";
+/// 검색 인덱스 스키마 버전 (다르면 시작 시 재구축)
+const SETTING_SEARCH_INDEX_VERSION: &str = "search_index_version";
+/// 마지막 Splice 임포트의 저장 방식 (자동 동기화로 추가되는 새 팩에 사용)
+const SETTING_SPLICE_STORAGE_MODE: &str = "splice_storage_mode";
+/// 자동 동기화(파일 감시) 사용 여부 ("0"이면 꺼짐, 없으면 켜짐)
+const SETTING_LIBRARY_WATCHER: &str = "library_watcher";
+
+fn get_setting(db: &Connection, key: &str) -> Option<String> {
+    db.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        params![key],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .ok()
+    .flatten()
+}
+
+/// value가 None이면 설정 삭제
+fn set_setting(db: &Connection, key: &str, value: Option<&str>) -> Result<(), String> {
+    match value {
+        Some(v) => db.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, v],
+        ),
+        None => db.execute("DELETE FROM settings WHERE key = ?1", params![key]),
+    }
+    .map_err(|e| format!("설정 저장 실패: {}", e))?;
     Ok(())
 }
 
@@ -324,6 +872,70 @@ fn init_db(db: &Connection) -> Result<(), String> {
 
 const FFT_SIZE: usize = 2048;
 
+/// 5밴드 주파수 경계 (bin 인덱스) — 파형 색상과 타악기 분류가 공유
+/// Sub:     ~20–150Hz   (킥 바디, 서브베이스)
+/// LowMid:  ~150–600Hz  (킥 어택, 베이스 하모닉스)
+/// Mid:     ~600–2500Hz (보컬, 스네어, 클랩)
+/// HighMid: ~2500–6000Hz (프레즌스, 어택 트랜지언트)
+/// High:    6000Hz+     (하이햇, 심벌, 에어)
+struct FrequencyBands {
+    sub_end: usize,
+    lowmid_end: usize,
+    mid_end: usize,
+    himid_end: usize,
+    nyquist: usize,
+}
+
+impl FrequencyBands {
+    fn new(fft_size: usize, sample_rate: u32) -> Self {
+        let freq_to_bin =
+            |freq: f32| -> usize { (freq * fft_size as f32 / sample_rate as f32).round() as usize };
+        let sub_end = freq_to_bin(150.0).max(1).min(fft_size / 2);
+        let lowmid_end = freq_to_bin(600.0).max(sub_end + 1).min(fft_size / 2);
+        let mid_end = freq_to_bin(2500.0).max(lowmid_end + 1).min(fft_size / 2);
+        let himid_end = freq_to_bin(6000.0).max(mid_end + 1).min(fft_size / 2);
+        FrequencyBands {
+            sub_end,
+            lowmid_end,
+            mid_end,
+            himid_end,
+            nyquist: fft_size / 2,
+        }
+    }
+
+    /// 대역별 빈 수 (에너지 밀도 계산용)
+    fn bin_counts(&self) -> [f32; 5] {
+        [
+            (self.sub_end - 1).max(1) as f32,
+            (self.lowmid_end - self.sub_end).max(1) as f32,
+            (self.mid_end - self.lowmid_end).max(1) as f32,
+            (self.himid_end - self.mid_end).max(1) as f32,
+            (self.nyquist - self.himid_end).max(1) as f32,
+        ]
+    }
+
+    /// FFT 결과 → 대역별 에너지 합 (DC 제외)
+    fn energies(&self, spectrum: &[Complex<f32>]) -> [f32; 5] {
+        let mut e = [0.0f32; 5];
+        for (bin, c) in spectrum.iter().enumerate().take(self.nyquist).skip(1) {
+            let mag_sq = c.re * c.re + c.im * c.im;
+            let band = if bin < self.sub_end {
+                0
+            } else if bin < self.lowmid_end {
+                1
+            } else if bin < self.mid_end {
+                2
+            } else if bin < self.himid_end {
+                3
+            } else {
+                4
+            };
+            e[band] += mag_sq;
+        }
+        e
+    }
+}
+
 /// 각 파형 바에 대해 Low/Mid/High 주파수 대역 에너지를 분석하여 RGB 색상을 계산
 fn compute_frequency_colors(
     all_samples: &[f32],
@@ -347,26 +959,8 @@ fn compute_frequency_colors(
         })
         .collect();
 
-    // 5밴드 주파수 경계 (bin 인덱스)
-    // Sub:     ~20–150Hz   (킥 바디, 서브베이스)
-    // LowMid:  ~150–600Hz  (킥 어택, 베이스 하모닉스)
-    // Mid:     ~600–2500Hz (보컬, 스네어, 클랩)
-    // HighMid: ~2500–6000Hz (프레즌스, 어택 트랜지언트)
-    // High:    6000Hz+     (하이햇, 심벌, 에어)
-    let freq_to_bin =
-        |freq: f32| -> usize { (freq * fft_size as f32 / sample_rate as f32).round() as usize };
-    let sub_end = freq_to_bin(150.0).max(1).min(fft_size / 2);
-    let lowmid_end = freq_to_bin(600.0).max(sub_end + 1).min(fft_size / 2);
-    let mid_end = freq_to_bin(2500.0).max(lowmid_end + 1).min(fft_size / 2);
-    let himid_end = freq_to_bin(6000.0).max(mid_end + 1).min(fft_size / 2);
-    let nyquist = fft_size / 2;
-
-    // 대역별 빈 수 (에너지 밀도 계산용)
-    let sub_bins = (sub_end - 1).max(1) as f32;
-    let lowmid_bins = (lowmid_end - sub_end).max(1) as f32;
-    let mid_bins = (mid_end - lowmid_end).max(1) as f32;
-    let himid_bins = (himid_end - mid_end).max(1) as f32;
-    let high_bins = (nyquist - himid_end).max(1) as f32;
+    let bands = FrequencyBands::new(fft_size, sample_rate);
+    let [sub_bins, lowmid_bins, mid_bins, himid_bins, high_bins] = bands.bin_counts();
 
     let mut buffer = vec![Complex { re: 0.0f32, im: 0.0f32 }; fft_size];
 
@@ -409,26 +1003,7 @@ fn compute_frequency_colors(
             fft.process(&mut buffer);
 
             // 5밴드 에너지 합산
-            let mut sub_e = 0.0f32;
-            let mut lowmid_e = 0.0f32;
-            let mut mid_e = 0.0f32;
-            let mut himid_e = 0.0f32;
-            let mut high_e = 0.0f32;
-
-            for bin in 1..nyquist {
-                let mag_sq = buffer[bin].re * buffer[bin].re + buffer[bin].im * buffer[bin].im;
-                if bin < sub_end {
-                    sub_e += mag_sq;
-                } else if bin < lowmid_end {
-                    lowmid_e += mag_sq;
-                } else if bin < mid_end {
-                    mid_e += mag_sq;
-                } else if bin < himid_end {
-                    himid_e += mag_sq;
-                } else {
-                    high_e += mag_sq;
-                }
-            }
+            let [sub_e, lowmid_e, mid_e, himid_e, high_e] = bands.energies(&buffer);
 
             // ★ 에너지 밀도 (빈당 평균) — 대역 폭 차이 보정
             let sub_d = sub_e / sub_bins;
@@ -654,11 +1229,81 @@ fn decode_audio_mono(file_path: &str, max_seconds: Option<f64>) -> Result<(Vec<f
     Ok((all_samples, sample_rate))
 }
 
+// ── Shared analysis context ─────────────────────────────────────────
+
+/// 분석기가 공유하는 디코딩 구간 (BPM/키/무음 분석 모두 앞 30초만 사용)
+const ANALYSIS_MAX_SECONDS: f64 = 30.0;
+
+/// 파일 하나의 분석 컨텍스트
+/// PCM 디코딩은 처음 필요할 때 한 번만 수행하고 모든 분석기가 같은 버퍼를 읽음
+/// (파일명만으로 결정되면 디코딩하지 않음)
+struct AnalysisContext {
+    path: String,
+    pcm: std::cell::OnceCell<Option<(Vec<f32>, u32)>>,
+    embedded: std::cell::OnceCell<EmbeddedMetadata>,
+}
+
+impl AnalysisContext {
+    fn new(path: &str) -> Self {
+        AnalysisContext {
+            path: path.to_string(),
+            pcm: std::cell::OnceCell::new(),
+            embedded: std::cell::OnceCell::new(),
+        }
+    }
+
+    /// 파일에 내장된 메타데이터 (청크/태그는 한 번만 읽음)
+    fn embedded(&self) -> &EmbeddedMetadata {
+        self.embedded.get_or_init(|| read_embedded_metadata(&self.path))
+    }
+
+    /// 모노 PCM (앞 ANALYSIS_MAX_SECONDS초) + 샘플레이트 — 디코딩 실패 시 None
+    fn pcm(&self) -> Option<(&[f32], u32)> {
+        self.pcm
+            .get_or_init(|| decode_audio_mono(&self.path, Some(ANALYSIS_MAX_SECONDS)).ok())
+            .as_ref()
+            .map(|(samples, sample_rate)| (samples.as_slice(), *sample_rate))
+    }
+
+    /// 루프면 길이(loop_duration_ms)로 마디 수를 추론해 후보를 교차 검증
+    fn detect_bpm(&self, loop_duration_ms: Option<i64>) -> Option<f64> {
+        let (samples, sample_rate) = self.pcm()?;
+        let tempo = analyze_tempo(samples, sample_rate)?;
+        Some(match loop_duration_ms {
+            Some(duration) => infer_loop_tempo(duration, &tempo),
+            None => tempo.bpm,
+        })
+    }
+
+    fn detect_key(&self) -> Option<(String, String, f32)> {
+        let (samples, sample_rate) = self.pcm()?;
+        detect_key_from_samples(samples, sample_rate)
+    }
+
+    fn has_trailing_silence(&self) -> bool {
+        self.pcm()
+            .map(|(samples, sample_rate)| has_trailing_silence(samples, sample_rate))
+            .unwrap_or(false)
+    }
+
+    fn classify_drum_hit(&self) -> Option<(&'static str, f32)> {
+        let (samples, sample_rate) = self.pcm()?;
+        classify_drum_hit(samples, sample_rate)
+    }
+}
+
 // ── BPM detection from audio ────────────────────────────────────────
 
-fn detect_bpm_from_audio(file_path: &str) -> Option<i32> {
-    // 최대 30초까지 디코딩 (더 긴 분석 윈도우로 정확도 향상)
-    let (samples, sample_rate) = decode_audio_mono(file_path, Some(30.0)).ok()?;
+/// autocorrelation 템포 분석 결과
+struct TempoAnalysis {
+    /// 최종 추정 BPM
+    bpm: f64,
+    /// 상위 피크와 옥타브 변형 (BPM, 점수) — 루프 길이 교차 검증용
+    candidates: Vec<(f64, f64)>,
+}
+
+/// 앞 30초 PCM으로 BPM 추정 (더 긴 분석 윈도우로 정확도 향상)
+fn analyze_tempo(samples: &[f32], sample_rate: u32) -> Option<TempoAnalysis> {
     if samples.len() < sample_rate as usize * 2 {
         return None; // 2초 미만이면 BPM 감지 불가
     }
@@ -765,6 +1410,7 @@ fn detect_bpm_from_audio(file_path: &str) -> Option<i32> {
     // 5. 옥타브 보정: 각 피크의 BPM과 x2, /2 변형 중 최적 후보 선택
     let mut best_score = 0.0f64;
     let mut best_bpm = 0i32;
+    let mut candidates: Vec<(f64, f64)> = Vec::new();
 
     for &(lag, corr) in peaks.iter().take(5) {
         let secs_per_beat = (lag as f64 * hop_size as f64) / sr;
@@ -792,6 +1438,7 @@ fn detect_bpm_from_audio(file_path: &str) -> Option<i32> {
             };
 
             let score = corr * range_weight * octave_penalty;
+            candidates.push((candidate_f, score));
             if score > best_score {
                 best_score = score;
                 best_bpm = candidate;
@@ -815,1717 +1462,8204 @@ fn detect_bpm_from_audio(file_path: &str) -> Option<i32> {
     }
 
     if best_bpm >= 60 && best_bpm <= 190 && best_score > 0.0005 {
-        Some(best_bpm)
+        // 7. 정수 후보를 소수점 BPM으로 정밀화
+        Some(TempoAnalysis {
+            bpm: refine_bpm(&onset, frames_per_sec, best_bpm as f64),
+            candidates,
+        })
     } else {
         None
     }
 }
 
-// ── Filename / path parsing helpers ─────────────────────────────────
+/// 여러 박 떨어진 autocorrelation 피크로 BPM 정밀화
+/// lag 해상도(10ms)의 상대 오차는 멀리 볼수록 줄어듦 — 가장 먼 박 수의 피크를 포물선 보간
+fn refine_bpm(onset: &[f64], frames_per_sec: f64, bpm: f64) -> f64 {
+    let autocorr = |lag: usize| -> f64 {
+        let n = onset.len() - lag;
+        (0..n).map(|j| onset[j] * onset[j + lag]).sum::<f64>() / n as f64
+    };
+    let beat_lag = frames_per_sec * 60.0 / bpm;
+    let beats = ((onset.len() / 2) as f64 / beat_lag).floor().min(16.0);
+    if beats < 2.0 {
+        return bpm;
+    }
 
-fn parse_bpm_from_filename(filename: &str) -> Option<i32> {
-    // "120BPM", "120 BPM", "120bpm", "120_bpm" 등
-    let re = Regex::new(r"(?i)(\d{2,3})\s*[_\-]?\s*bpm").unwrap();
-    if let Some(caps) = re.captures(filename) {
-        if let Ok(bpm) = caps[1].parse::<i32>() {
-            if (60..=190).contains(&bpm) {
-                return Some(bpm);
-            }
-        }
+    // 목표 lag ±3% 안에서만 탐색 (이웃 박의 피크를 잡지 않도록)
+    let target = beat_lag * beats;
+    let radius = ((target * 0.03).ceil() as usize).max(2);
+    let lo = (target.round() as usize).saturating_sub(radius).max(1);
+    let hi = (target.round() as usize + radius).min(onset.len() - 2);
+    let Some((lag, peak)) = (lo..=hi)
+        .map(|lag| (lag, autocorr(lag)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+    else {
+        return bpm;
+    };
+    if lag == lo || lag == hi {
+        return bpm; // 구간 경계 → 로컬 피크 아님
     }
-    // "bpm120", "BPM_120", "BPM-120"
-    let re2 = Regex::new(r"(?i)bpm[\s_\-]*(\d{2,3})").unwrap();
-    if let Some(caps) = re2.captures(filename) {
-        if let Ok(bpm) = caps[1].parse::<i32>() {
-            if (60..=190).contains(&bpm) {
-                return Some(bpm);
-            }
-        }
+
+    let (prev, next) = (autocorr(lag - 1), autocorr(lag + 1));
+    let denom = prev - 2.0 * peak + next;
+    let offset = if denom.abs() > 1e-12 {
+        (0.5 * (prev - next) / denom).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    let refined = frames_per_sec * 60.0 * beats / (lag as f64 + offset);
+    // 정수 후보와 크게 다르면 다른 피크를 잡은 것 → 후보 유지
+    if (refined - bpm).abs() > 2.0 {
+        return bpm;
     }
-    // "tempo120", "Tempo 120", "Tempo_120", "Tempo-120"
-    let re3 = Regex::new(r"(?i)tempo[\s_\-]*(\d{2,3})").unwrap();
-    if let Some(caps) = re3.captures(filename) {
-        if let Ok(bpm) = caps[1].parse::<i32>() {
-            if (60..=190).contains(&bpm) {
-                return Some(bpm);
-            }
-        }
+    round_bpm(refined)
+}
+
+/// BPM 저장 정밀도: 소수 첫째 자리, 정수에 아주 가까우면 정수로
+fn round_bpm(bpm: f64) -> f64 {
+    if (bpm - bpm.round()).abs() < 0.1 {
+        bpm.round()
+    } else {
+        (bpm * 10.0).round() / 10.0
     }
-    // "120 Tempo", "120_Tempo"
-    let re4 = Regex::new(r"(?i)(\d{2,3})\s*[_\-]?\s*tempo").unwrap();
-    if let Some(caps) = re4.captures(filename) {
-        if let Ok(bpm) = caps[1].parse::<i32>() {
-            if (60..=190).contains(&bpm) {
-                return Some(bpm);
-            }
-        }
-    }
-    // 독립 숫자 패턴 (폴백): 구분자 사이 2~3자리 숫자를 BPM으로 추정
-    // bit, bar, k, hz, db, ch, st 등 비-BPM 접미사가 붙은 숫자는 제외
-    let re5 = Regex::new(r"(?:^|[^0-9a-zA-Z])(\d{2,3})(?:[^0-9a-zA-Z]|$)").unwrap();
-    for caps in re5.captures_iter(filename) {
-        if let Ok(num) = caps[1].parse::<i32>() {
-            if !(60..=190).contains(&num) {
-                continue;
-            }
-            // 숫자 뒤 텍스트 확인: 비-BPM 접미사 제외
-            let end_pos = caps.get(1).unwrap().end();
-            if end_pos < filename.len() {
-                let after = filename[end_pos..].to_lowercase();
-                if after.starts_with("bit")
-                    || after.starts_with("bar")
-                    || after.starts_with("hz")
-                    || after.starts_with("khz")
-                    || after.starts_with("db")
-                    || after.starts_with("ch")
-                    || after.starts_with("st")
-                    || after.starts_with("kbps")
-                {
-                    continue;
-                }
-            }
-            return Some(num);
-        }
-    }
-    None
 }
 
-fn parse_key_from_filename(filename: &str) -> Option<String> {
-    // "Cmaj", "C#min", "Bbmajor", "A minor" 등
-    let re = Regex::new(r"(?i)\b([A-G][#b]?)\s*(maj(?:or)?|min(?:or)?)\b").unwrap();
-    if let Some(caps) = re.captures(filename) {
-        let note = caps[1].chars().next()?.to_uppercase().to_string()
-            + &caps[1][1..];
-        let quality = caps[2].to_lowercase();
-        let q = if quality.starts_with("min") {
-            "min"
-        } else {
-            "maj"
-        };
-        return Some(format!("{}{}", note, q));
+/// BPM 표시 문자열 (정수면 소수점 없이)
+fn format_bpm(bpm: f64) -> String {
+    if bpm.fract() == 0.0 {
+        format!("{}", bpm as i64)
+    } else {
+        format!("{}", bpm)
     }
+}
 
-    // "Am", "F#m", "Bbm" (단독 m = minor)
-    let re2 = Regex::new(r"\b([A-G][#b]?)m\b").unwrap();
-    if let Some(caps) = re2.captures(filename) {
-        let note = caps[1].chars().next()?.to_uppercase().to_string()
-            + &caps[1][1..];
-        return Some(format!("{}min", note));
-    }
+// ── Loop length (bar count) ─────────────────────────────────────────
 
-    None
+/// 루프 길이로 가정하는 마디 수 (4/4)
+const LOOP_BAR_COUNTS: [i32; 4] = [1, 2, 4, 8];
+/// 마디 수 × 템포로 계산한 길이와 실제 길이의 허용 오차 (인코더 패딩 등)
+const LOOP_LENGTH_TOLERANCE: f64 = 0.03;
+
+/// 길이가 정확히 bars 마디(4/4)일 때의 템포
+fn tempo_for_bars(duration_ms: i64, bars: i32) -> f64 {
+    240_000.0 * bars as f64 / duration_ms as f64
 }
 
-/// 오디오 파일의 뒤쪽 무음 여부를 검사 (one-shot 판별용)
-/// 뒤쪽 30%가 대부분 무음이면 one-shot (긴 디케이/리버브 꼬리)
-fn has_trailing_silence(file_path: &str) -> bool {
-    let (samples, sample_rate) = match decode_audio_mono(file_path, Some(30.0)) {
-        Ok(r) => r,
-        Err(_) => return false,
+/// BPM과 길이가 맞는 마디 수
+fn loop_bar_count(bpm: f64, duration_ms: i64) -> Option<i32> {
+    LOOP_BAR_COUNTS.into_iter().find(|&bars| {
+        let expected = 240_000.0 * bars as f64 / bpm;
+        (duration_ms as f64 - expected).abs() / expected <= LOOP_LENGTH_TOLERANCE
+    })
+}
+
+/// 루프 BPM과 길이의 관계 → (마디 수, 파일명 BPM이 길이와 모순되는지)
+fn loop_length_check(
+    bpm: Option<f64>,
+    bpm_source: Option<&str>,
+    duration_ms: Option<i64>,
+    sample_type: Option<&str>,
+) -> (Option<i32>, bool) {
+    let (Some(bpm), Some(duration), Some("loop")) = (bpm, duration_ms, sample_type) else {
+        return (None, false);
     };
+    let bars = loop_bar_count(bpm, duration);
+    (bars, bars.is_none() && bpm_source == Some(SOURCE_FILENAME))
+}
 
-    let chunk_size = (sample_rate as usize) / 10; // 100ms 단위
-    if chunk_size == 0 {
-        return false;
-    }
-    let total_chunks = samples.len() / chunk_size;
-    if total_chunks < 5 {
-        return false;
+/// 루프 길이로 템포 추정 교차 검증
+/// 최종 추정치가 어떤 마디 수와도 맞지 않으면, 길이와 맞는 마디 수 템포 중
+/// autocorrelation 후보가 가장 강하게 지지하는 것을 선택 (없으면 추정치 유지)
+fn infer_loop_tempo(duration_ms: i64, tempo: &TempoAnalysis) -> f64 {
+    if duration_ms <= 0 || loop_bar_count(tempo.bpm, duration_ms).is_some() {
+        return tempo.bpm;
     }
-
-    // 각 청크의 RMS 에너지 계산
-    let energies: Vec<f64> = (0..total_chunks)
-        .map(|i| {
-            let start = i * chunk_size;
-            let end = (start + chunk_size).min(samples.len());
-            let rms: f64 = samples[start..end]
+    LOOP_BAR_COUNTS
+        .into_iter()
+        .map(|bars| tempo_for_bars(duration_ms, bars))
+        .filter(|t| (60.0..=190.0).contains(t))
+        .filter_map(|t| {
+            let support = tempo
+                .candidates
                 .iter()
-                .map(|s| (*s as f64).powi(2))
-                .sum::<f64>()
-                / (end - start) as f64;
-            rms.sqrt()
+                .filter(|(c, _)| (c - t).abs() / t <= LOOP_LENGTH_TOLERANCE)
+                .map(|(_, score)| *score)
+                .fold(0.0f64, f64::max);
+            (support > 0.0).then_some((t, support))
         })
-        .collect();
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(t, _)| round_bpm(t))
+        .unwrap_or(tempo.bpm)
+}
 
-    let peak_energy = energies.iter().cloned().fold(0.0f64, f64::max);
-    if peak_energy <= 0.0 {
-        return false;
-    }
+// ── Metadata provenance ──────────────────────────────────────────────
+
+const SOURCE_SPLICE: &str = "splice";
+const SOURCE_FILENAME: &str = "filename";
+const SOURCE_PATH: &str = "path";
+const SOURCE_PACK: &str = "pack";
+const SOURCE_AUDIO: &str = "audio";
+const SOURCE_USER: &str = "user";
+const SOURCE_CLASSIFIER: &str = "classifier";
+const SOURCE_EMBEDDED: &str = "embedded";
+const SOURCE_SIDECAR: &str = "sidecar";
+
+// 휴리스틱별 기본 신뢰도 (오디오 분석은 감지기가 직접 계산)
+const CONFIDENCE_EXACT: f32 = 1.0; // Splice DB, 사용자 편집, 내보내기 사이드카
+const CONFIDENCE_EMBEDDED: f32 = 0.95; // 파일에 내장된 ACID 청크/태그
+const CONFIDENCE_FILENAME: f32 = 0.9; // 파일명에 명시된 BPM/키/타입 키워드
+const CONFIDENCE_PATH: f32 = 0.7; // 폴더/파일명 키워드 기반 장르·태그
+const CONFIDENCE_PACK: f32 = 0.5; // 팩 장르 상속
+const CONFIDENCE_AUDIO_BPM: f32 = 0.6; // 자기상관 BPM 추정
+
+/// 출처/신뢰도를 추적하는 샘플 메타데이터 필드
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MetaField {
+    Bpm,
+    Key,
+    SampleType,
+    Genre,
+    Tags,
+}
 
-    // 앞쪽 50%에서 피크 에너지 위치 확인
-    let half = total_chunks / 2;
-    let front_peak = energies[..half].iter().cloned().fold(0.0f64, f64::max);
-    let back_peak = energies[half..].iter().cloned().fold(0.0f64, f64::max);
+const META_FIELDS: [MetaField; 5] = [
+    MetaField::Bpm,
+    MetaField::Key,
+    MetaField::SampleType,
+    MetaField::Genre,
+    MetaField::Tags,
+];
+
+impl MetaField {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetaField::Bpm => "bpm",
+            MetaField::Key => "key",
+            MetaField::SampleType => "sample_type",
+            MetaField::Genre => "genre",
+            MetaField::Tags => "tags",
+        }
+    }
 
-    // 앞쪽 피크가 뒤쪽보다 확실히 크면 one-shot 가능성
-    if front_peak <= 0.0 {
-        return false;
+    fn parse(s: &str) -> Option<Self> {
+        META_FIELDS.into_iter().find(|f| f.as_str() == s)
     }
 
-    // 뒤쪽 30% 무음 체크
-    let tail_start = (total_chunks as f64 * 0.7) as usize;
-    let silence_threshold = peak_energy * 0.03; // 피크의 3% 이하 = 무음
+    /// (값 컬럼, 출처 컬럼, 신뢰도 컬럼) — 키는 audio_key + chord_type 한 묶음
+    fn columns(self) -> (&'static [&'static str], &'static str, &'static str) {
+        match self {
+            MetaField::Bpm => (&["bpm", "bars", "bpm_mismatch"], "bpm_source", "bpm_confidence"),
+            MetaField::Key => (&["audio_key", "chord_type"], "key_source", "key_confidence"),
+            MetaField::SampleType => (&["sample_type"], "sample_type_source", "sample_type_confidence"),
+            MetaField::Genre => (&["genre"], "genre_source", "genre_confidence"),
+            MetaField::Tags => (&["tags"], "tags_source", "tags_confidence"),
+        }
+    }
+}
 
-    let silent_tail_chunks = energies[tail_start..]
+/// 샘플 필드 값과 출처/신뢰도 기록 — 출처가 user인 필드는 덮어쓰지 않음
+/// values는 MetaField::columns() 순서. 실제로 바뀌었으면 1 반환
+fn write_sample_field(
+    conn: &Connection,
+    sample_id: i64,
+    field: MetaField,
+    values: &[&dyn rusqlite::ToSql],
+    source: Option<&str>,
+    confidence: Option<f32>,
+) -> rusqlite::Result<usize> {
+    let (cols, source_col, conf_col) = field.columns();
+    debug_assert_eq!(cols.len(), values.len());
+    let n = cols.len();
+    let sets: Vec<String> = cols
         .iter()
-        .filter(|&&e| e < silence_threshold)
-        .count();
-    let tail_chunk_count = total_chunks - tail_start;
+        .enumerate()
+        .map(|(i, c)| format!("{} = ?{}", c, i + 1))
+        .collect();
+    let diffs: Vec<String> = cols
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("{} IS NOT ?{}", c, i + 1))
+        .collect();
+    let sql = format!(
+        "UPDATE samples SET {sets}, {src} = ?{s}, {conf} = ?{c}
+         WHERE id = ?{id} AND {src} IS NOT '{user}'
+           AND ({diffs} OR {src} IS NOT ?{s} OR {conf} IS NOT ?{c})",
+        sets = sets.join(", "),
+        diffs = diffs.join(" OR "),
+        src = source_col,
+        conf = conf_col,
+        user = SOURCE_USER,
+        s = n + 1,
+        c = n + 2,
+        id = n + 3,
+    );
+    let mut args: Vec<&dyn rusqlite::ToSql> = values.to_vec();
+    args.push(&source);
+    args.push(&confidence);
+    args.push(&sample_id);
+    conn.execute(&sql, args.as_slice())
+}
 
-    // 뒤쪽 30%의 60% 이상이 무음이면 → one-shot
-    let silence_ratio = silent_tail_chunks as f64 / tail_chunk_count as f64;
-    if silence_ratio > 0.6 {
-        return true;
-    }
+/// 현재 BPM/출처/길이/타입으로 루프 마디 수와 모순 표시 다시 계산. 바뀌었으면 1 반환
+fn refresh_loop_bars(conn: &Connection, sample_id: i64) -> rusqlite::Result<usize> {
+    let (bpm, source, duration, sample_type) = conn.query_row(
+        "SELECT bpm, bpm_source, duration, sample_type FROM samples WHERE id = ?1",
+        params![sample_id],
+        |row| {
+            Ok((
+                row.get::<_, Option<f64>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        },
+    )?;
+    let (bars, mismatch) = loop_length_check(bpm, source.as_deref(), duration, sample_type.as_deref());
+    conn.execute(
+        "UPDATE samples SET bars = ?2, bpm_mismatch = ?3
+         WHERE id = ?1 AND (bars IS NOT ?2 OR bpm_mismatch IS NOT ?3)",
+        params![sample_id, bars, mismatch],
+    )
+}
 
-    // 또는: 뒤쪽 피크가 앞쪽 피크의 10% 미만이면 → one-shot (에너지 감쇠)
-    if back_peak < front_peak * 0.1 {
-        return true;
-    }
+fn provenance_from_row(
+    row: &rusqlite::Row,
+    source_idx: usize,
+) -> rusqlite::Result<Option<FieldProvenance>> {
+    Ok(row
+        .get::<_, Option<String>>(source_idx)?
+        .map(|source| FieldProvenance {
+            source,
+            confidence: row.get::<_, Option<f64>>(source_idx + 1).ok().flatten().map(|c| c as f32),
+        }))
+}
 
-    false
+/// Sample 조회용 공통 SELECT 컬럼 (samples s LEFT JOIN packs p 기준)
+/// 샘플 장르가 비어(또는 출처 없이 같은 값이라) 팩 장르를 보여줄 때는 출처를 pack으로 표시
+const SAMPLE_COLUMNS: &str = "s.id, s.local_path, s.filename, s.audio_key, s.bpm, s.chord_type,
+                    s.duration, COALESCE(s.genre, p.genre) as genre,
+                    s.sample_type, s.tags,
+                    s.pack_uuid, p.name as pack_name, p.genre as pack_genre,
+                    s.created_at,
+                    s.bpm_source, s.bpm_confidence, s.key_source, s.key_confidence,
+                    s.sample_type_source, s.sample_type_confidence,
+                    CASE WHEN s.genre IS NULL AND p.genre IS NOT NULL THEN 'pack'
+                         WHEN s.genre_source IS NULL AND s.genre = p.genre THEN 'pack'
+                         ELSE s.genre_source END,
+                    CASE WHEN s.genre IS NULL AND p.genre IS NOT NULL THEN 0.5
+                         WHEN s.genre_source IS NULL AND s.genre = p.genre THEN 0.5
+                         ELSE s.genre_confidence END,
+                    s.tags_source, s.tags_confidence,
+                    s.bars, COALESCE(s.bpm_mismatch, 0)";
+
+/// SAMPLE_COLUMNS 순서의 행 → Sample
+fn sample_from_row(row: &rusqlite::Row, root: &Path) -> rusqlite::Result<Sample> {
+    Ok(Sample {
+        id: row.get(0)?,
+        local_path: resolve_library_path(root, &row.get::<_, String>(1)?),
+        filename: row.get(2)?,
+        audio_key: row.get(3)?,
+        bpm: row.get(4)?,
+        chord_type: row.get(5)?,
+        duration: row.get(6)?,
+        genre: row.get(7)?,
+        sample_type: row.get(8)?,
+        tags: row.get(9)?,
+        pack_uuid: row.get(10)?,
+        pack_name: row.get(11)?,
+        pack_genre: row.get(12)?,
+        created_at: row.get(13)?,
+        provenance: SampleProvenance {
+            bpm: provenance_from_row(row, 14)?,
+            audio_key: provenance_from_row(row, 16)?,
+            sample_type: provenance_from_row(row, 18)?,
+            genre: provenance_from_row(row, 20)?,
+            tags: provenance_from_row(row, 22)?,
+        },
+        bars: row.get(24)?,
+        bpm_mismatch: row.get::<_, i64>(25)? != 0,
+    })
 }
 
-fn parse_sample_type(filename: &str, duration_ms: Option<i64>, file_path: Option<&str>) -> String {
-    let lower = filename.to_lowercase();
+// ── Musical key detection (chromagram) ──────────────────────────────
 
-    // 1. 파일명 키워드 우선
-    if lower.contains("loop") || lower.contains("_lp") {
-        return "loop".to_string();
+const PITCH_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+// Krumhansl-Schmuckler 키 프로파일 (C 기준)
+const MAJOR_PROFILE: [f32; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const MINOR_PROFILE: [f32; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+/// 이 신뢰도 미만이면 임포트 시 키를 자동으로 채우지 않음
+const KEY_MIN_CONFIDENCE: f32 = 0.3;
+
+/// 피어슨 상관계수 (-1.0–1.0), 길이가 다르면 짧은 쪽 기준
+fn pearson_correlation(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len().min(b.len());
+    if n == 0 {
+        return 0.0;
     }
-    if lower.contains("oneshot")
-        || lower.contains("one-shot")
-        || lower.contains("one shot")
-        || lower.contains("_hit")
-        || lower.contains(" hit")
-        || lower.contains("stab")
-        || lower.contains("impact")
-        || lower.contains("riser")
-        || lower.contains("downlifter")
-        || lower.contains("fx")
-        || lower.contains("sfx")
-        || lower.contains("transition")
-        || lower.contains("fill")
-    {
-        return "oneshot".to_string();
+    let mean_a = a[..n].iter().sum::<f32>() / n as f32;
+    let mean_b = b[..n].iter().sum::<f32>() / n as f32;
+    let mut cov = 0.0f32;
+    let mut var_a = 0.0f32;
+    let mut var_b = 0.0f32;
+    for (x, y) in a[..n].iter().zip(&b[..n]) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
     }
+    if var_a <= 0.0 || var_b <= 0.0 {
+        return 0.0;
+    }
+    cov / (var_a.sqrt() * var_b.sqrt())
+}
 
-    // 2. 짧은 샘플은 one-shot
-    if let Some(d) = duration_ms {
-        if d < 1500 {
-            return "oneshot".to_string();
-        }
+/// 12 피치 클래스 크로마 프로파일 (C=0 … B=11, 최대값 1.0으로 정규화)
+fn compute_chroma(samples: &[f32], sample_rate: u32) -> Option<[f32; 12]> {
+    let fft_size = 4096usize;
+    let hop = fft_size / 2;
+    if samples.len() < fft_size || sample_rate == 0 {
+        return None;
     }
 
-    // 3. 1.5초~20초 범위: 오디오 분석으로 뒤쪽 무음 체크
-    if let (Some(d), Some(path)) = (duration_ms, file_path) {
-        if d >= 1500 && d <= 20000 {
-            if has_trailing_silence(path) {
-                return "oneshot".to_string();
+    let mut planner = FftPlanner::<f32>::new();
+    let fft = planner.plan_fft_forward(fft_size);
+
+    let hann: Vec<f32> = (0..fft_size)
+        .map(|i| {
+            0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / fft_size as f32).cos())
+        })
+        .collect();
+
+    // 빈 → 피치 클래스 매핑 (A1 55Hz ~ 5kHz, 그 밖은 무시)
+    let bin_pitch: Vec<Option<usize>> = (0..fft_size / 2)
+        .map(|bin| {
+            let freq = bin as f32 * sample_rate as f32 / fft_size as f32;
+            if !(55.0..=5000.0).contains(&freq) {
+                return None;
+            }
+            let midi = 69.0 + 12.0 * (freq / 440.0).log2();
+            Some((midi.round() as i32).rem_euclid(12) as usize)
+        })
+        .collect();
+
+    let mut chroma = [0.0f32; 12];
+    let mut buffer = vec![Complex { re: 0.0f32, im: 0.0f32 }; fft_size];
+    let mut start = 0;
+    while start + fft_size <= samples.len() {
+        for (j, c) in buffer.iter_mut().enumerate() {
+            c.re = samples[start + j] * hann[j];
+            c.im = 0.0;
+        }
+        fft.process(&mut buffer);
+        for (bin, pitch) in bin_pitch.iter().enumerate() {
+            if let Some(pc) = pitch {
+                let mag = (buffer[bin].re * buffer[bin].re + buffer[bin].im * buffer[bin].im).sqrt();
+                // 로그 압축 — 큰 저음 성분이 전체를 지배하지 않도록
+                chroma[*pc] += (1.0 + mag).ln();
             }
         }
+        start += hop;
     }
 
-    // 4. 20초 초과면 loop, 그 외 duration 기반
-    match duration_ms {
-        Some(d) if d > 20000 => "loop".to_string(),
-        Some(_) => "loop".to_string(),
-        None => "oneshot".to_string(),
+    let max = chroma.iter().cloned().fold(0.0f32, f32::max);
+    if max <= 0.0 {
+        return None;
+    }
+    for v in chroma.iter_mut() {
+        *v /= max;
     }
+    Some(chroma)
 }
 
-fn parse_tags_from_path(full_path: &str, filename: &str) -> Vec<String> {
-    let combined = format!("{} {}", full_path, filename).to_lowercase();
-    let mut tags: Vec<String> = Vec::new();
-
-    let instrument_keywords: &[(&str, &str)] = &[
-        ("kick", "kick"),
-        ("snare", "snare"),
-        ("hihat", "hihat"),
-        ("hi-hat", "hihat"),
-        ("hi hat", "hihat"),
-        ("hh_", "hihat"),
-        ("_hh", "hihat"),
-        ("clap", "clap"),
-        ("cymbal", "cymbal"),
-        ("crash", "crash"),
-        ("ride", "ride"),
-        ("open hat", "open hat"),
-        ("closed hat", "closed hat"),
-        ("bass", "bass"),
-        ("sub", "sub bass"),
-        ("lead", "lead"),
-        ("pad", "pad"),
-        ("pluck", "pluck"),
-        ("chord", "chord"),
-        ("arp", "arp"),
-        ("vocal", "vocal"),
-        ("vox", "vocal"),
-        ("voice", "vocal"),
-        ("fx", "fx"),
-        ("sfx", "fx"),
-        ("riser", "riser"),
-        ("impact", "impact"),
-        ("transition", "transition"),
-        ("sweep", "sweep"),
-        ("piano", "piano"),
-        ("keys", "keys"),
-        ("guitar", "guitar"),
-        ("strings", "strings"),
-        ("brass", "brass"),
-        ("synth", "synth"),
-        ("organ", "organ"),
-        ("flute", "flute"),
-        ("bell", "bell"),
-        ("perc", "percussion"),
-        ("percussion", "percussion"),
-        ("tom", "tom"),
-        ("shaker", "shaker"),
-        ("tambourine", "tambourine"),
-        ("rim", "rimshot"),
-        ("808", "808"),
-        ("top", "top loop"),
-        ("fill", "fill"),
-        ("break", "break"),
-        ("groove", "groove"),
-        ("melody", "melody"),
-        ("melodic", "melodic"),
-        ("drum", "drums"),
-        ("drums", "drums"),
-    ];
-
-    for (keyword, tag) in instrument_keywords {
-        if combined.contains(keyword) && !tags.contains(&tag.to_string()) {
-            tags.push(tag.to_string());
+/// 크로마를 24개 장/단조 템플릿과 비교 → (으뜸음 피치 클래스, 단조 여부, 신뢰도)
+/// 신뢰도: 최고 상관계수 × 2위와의 격차 (격차 0.1 이상이면 감점 없음)
+fn match_key_profile(chroma: &[f32; 12]) -> Option<(usize, bool, f32)> {
+    let mut scores: Vec<(usize, bool, f32)> = Vec::with_capacity(24);
+    for tonic in 0..12 {
+        for (minor, profile) in [(false, &MAJOR_PROFILE), (true, &MINOR_PROFILE)] {
+            let rotated: Vec<f32> = (0..12).map(|i| profile[(i + 12 - tonic) % 12]).collect();
+            scores.push((tonic, minor, pearson_correlation(chroma, &rotated)));
         }
     }
+    scores.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
 
-    // ── 상위 카테고리 태그 자동 부여 ──
-
-    // drums: 타악기 계열
-    const DRUM_TAGS: &[&str] = &[
-        "kick", "snare", "hihat", "clap", "cymbal", "crash", "ride",
-        "open hat", "closed hat", "percussion", "tom", "shaker",
-        "tambourine", "rimshot", "808", "top loop", "fill",
-    ];
-    if !tags.contains(&"drums".to_string())
-        && tags.iter().any(|t| DRUM_TAGS.contains(&t.as_str()))
-    {
-        tags.push("drums".to_string());
+    let (tonic, minor, best) = scores[0];
+    let second = scores[1].2;
+    if best <= 0.0 {
+        return None;
     }
+    let margin = ((best - second) / 0.1).clamp(0.0, 1.0);
+    Some((tonic, minor, (best * (0.5 + 0.5 * margin)).clamp(0.0, 1.0)))
+}
 
-    // melodic: 음정이 있는 악기 계열
-    const MELODIC_TAGS: &[&str] = &[
-        "piano", "keys", "guitar", "strings", "brass", "synth", "organ",
-        "flute", "bell", "lead", "pad", "pluck", "chord", "arp", "melody",
-    ];
-    if !tags.contains(&"melodic".to_string())
-        && tags.iter().any(|t| MELODIC_TAGS.contains(&t.as_str()))
-    {
-        tags.push("melodic".to_string());
+/// 음정이 없는 타악기 태그 — 이 태그만 있는 샘플은 키를 감지하지 않음
+const PERCUSSIVE_TAGS: &[&str] = &[
+    "drums", "kick", "snare", "hihat", "clap", "cymbal", "crash", "ride", "open hat",
+    "closed hat", "percussion", "tom", "shaker", "tambourine", "rimshot", "top loop",
+    "fill", "break",
+];
+/// 타악기 태그와 함께 있어도 음정이 있는 샘플로 보는 태그
+const PITCHED_TAGS: &[&str] = &["melodic", "bass", "808"];
+
+/// 오디오 키 감지 대상인지 — 원샷과 타악기 샘플은 크로마그램이 음정 없는 소리에서
+/// 임의의 키를 높은 신뢰도로 내므로 제외
+fn should_detect_key(sample_type: Option<&str>, tags: Option<&str>) -> bool {
+    if sample_type == Some("oneshot") {
+        return false;
     }
+    let tags: Vec<String> = tags
+        .unwrap_or("")
+        .split(',')
+        .map(|t| t.trim().to_lowercase())
+        .collect();
+    let has = |list: &[&str]| tags.iter().any(|t| list.contains(&t.as_str()));
+    !has(PERCUSSIVE_TAGS) || has(PITCHED_TAGS)
+}
 
-    // fx: 이펙트/트랜지션 계열
-    const FX_TAGS: &[&str] = &[
-        "fx", "riser", "impact", "transition", "sweep",
-    ];
-    if !tags.contains(&"fx".to_string())
-        && tags.iter().any(|t| FX_TAGS.contains(&t.as_str()))
-    {
-        tags.push("fx".to_string());
+/// 이전 버전이 원샷/타악기 샘플에 오디오 분석으로 채운 키 제거
+fn clear_unpitched_audio_keys(db: &Connection) -> Result<(), String> {
+    let ids: Vec<i64> = {
+        let mut stmt = db
+            .prepare("SELECT id, sample_type, tags FROM samples WHERE key_source = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![SOURCE_AUDIO], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok())
+            .filter(|(_, sample_type, tags)| !should_detect_key(sample_type.as_deref(), tags.as_deref()))
+            .map(|(id, _, _)| id)
+            .collect()
+    };
+    for id in ids {
+        db.execute(
+            "UPDATE samples SET audio_key = NULL, chord_type = NULL, key_source = NULL, key_confidence = NULL
+             WHERE id = ?1",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
     }
+    Ok(())
+}
 
-    // bass: 저음부 계열
-    const BASS_TAGS: &[&str] = &[
-        "bass", "sub bass", "808",
-    ];
-    if !tags.contains(&"bass".to_string())
-        && tags.iter().any(|t| BASS_TAGS.contains(&t.as_str()))
-    {
-        tags.push("bass".to_string());
-    }
+/// 오디오 파일에서 키 감지 (단독 실행용 — 임포트/재분석은 AnalysisContext 사용)
+fn detect_key_from_audio(file_path: &str) -> Option<(String, String, f32)> {
+    AnalysisContext::new(file_path).detect_key()
+}
 
-    tags
+/// PCM에서 키 감지 → (audio_key, chord_type, 신뢰도)
+/// Splice DB와 같은 형식: audio_key는 음 이름("C#"), chord_type은 "major"/"minor"
+fn detect_key_from_samples(samples: &[f32], sample_rate: u32) -> Option<(String, String, f32)> {
+    let chroma = compute_chroma(samples, sample_rate)?;
+    let (tonic, minor, confidence) = match_key_profile(&chroma)?;
+    let chord_type = if minor { "minor" } else { "major" };
+    Some((PITCH_NAMES[tonic].to_string(), chord_type.to_string(), confidence))
 }
 
-fn parse_genre_from_path(full_path: &str) -> Option<String> {
-    let lower = full_path.to_lowercase();
+// ── Drum hit classification ─────────────────────────────────────────
+
+/// 이보다 긴 샘플은 타악기 한 타로 보지 않음
+const DRUM_HIT_MAX_MS: i64 = 3000;
+/// 이 신뢰도 미만이면 태그를 붙이지 않음
+const DRUM_HIT_MIN_CONFIDENCE: f32 = 0.4;
+const DRUM_HIT_FFT_SIZE: usize = 4096;
+/// 분류 결과 → 태그 (기본 태그 규칙과 같은 이름)
+const DRUM_HIT_TAGS: &[(&str, &str)] = &[
+    ("kick", "kick"),
+    ("snare", "snare"),
+    ("clap", "clap"),
+    ("hat", "hihat"),
+    ("cymbal", "cymbal"),
+    ("tom", "tom"),
+    ("perc", "percussion"),
+];
+
+struct DrumHitFeatures {
+    /// 피크의 10% → 피크까지
+    attack_ms: f32,
+    /// 피크 → -20dB까지
+    decay_ms: f32,
+    centroid_hz: f32,
+    /// 어택 구간 5밴드 에너지 비율 (합 1)
+    bands: [f32; 5],
+    /// 어택 후 40ms 안의 재어택 수 (클랩의 여러 겹 타격)
+    flams: usize,
+}
 
-    let genre_keywords: &[(&[&str], &str)] = &[
-        (&["hip hop", "hiphop", "hip-hop", "boom bap", "boom-bap"], "Hip Hop"),
-        (&["trap"], "Trap"),
-        (&["drill"], "Drill"),
-        (&["house", "deep house", "tech house"], "House"),
-        (&["techno"], "Techno"),
-        (&["edm", "electro"], "Electronic"),
-        (&["dubstep", "dub step"], "Dubstep"),
-        (&["dnb", "drum and bass", "drum & bass", "drum n bass"], "DnB"),
-        (&["pop"], "Pop"),
-        (&["rnb", "r&b", "r'n'b"], "R&B"),
-        (&["lo-fi", "lofi", "lo fi"], "Lo-Fi"),
-        (&["ambient"], "Ambient"),
-        (&["jazz"], "Jazz"),
-        (&["soul"], "Soul"),
-        (&["funk"], "Funk"),
-        (&["reggae", "dancehall", "reggaeton"], "Reggae"),
-        (&["rock", "indie"], "Rock"),
-        (&["latin", "salsa", "bossa"], "Latin"),
-        (&["afro", "afrobeat"], "Afrobeat"),
-        (&["cinematic", "film", "orchestral"], "Cinematic"),
-        (&["future bass", "future-bass"], "Future Bass"),
-        (&["trance"], "Trance"),
-        (&["garage", "uk garage"], "Garage"),
-    ];
+fn drum_hit_features(samples: &[f32], sample_rate: u32) -> Option<DrumHitFeatures> {
+    let sr = sample_rate as f32;
+
+    // 5ms RMS envelope
+    let win = ((sr * 0.005) as usize).max(1);
+    let env: Vec<f32> = samples
+        .chunks(win)
+        .map(|c| (c.iter().map(|s| s * s).sum::<f32>() / c.len() as f32).sqrt())
+        .collect();
+    let (peak_idx, &peak) = env.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+    if peak < 1e-4 {
+        return None; // 무음
+    }
+    let frame_ms = win as f32 * 1000.0 / sr;
 
-    for (keywords, genre) in genre_keywords {
-        for kw in *keywords {
-            if lower.contains(kw) {
-                return Some(genre.to_string());
+    let onset = env.iter().position(|&e| e >= peak * 0.1).unwrap_or(peak_idx);
+    let attack_ms = (peak_idx - onset) as f32 * frame_ms;
+    let decay_frames = env[peak_idx..]
+        .iter()
+        .position(|&e| e < peak * 0.1)
+        .unwrap_or(env.len() - peak_idx);
+    let decay_ms = decay_frames as f32 * frame_ms;
+
+    // 재어택: 50% 아래로 떨어졌다가 다시 80% 이상으로 오른 횟수
+    let flam_end = (onset + (40.0 / frame_ms) as usize).min(env.len());
+    let mut flams = 0;
+    let mut hit = false;
+    let mut dipped = false;
+    for &e in &env[onset..flam_end] {
+        if e >= peak * 0.8 {
+            if hit && dipped {
+                flams += 1;
             }
+            hit = true;
+            dipped = false;
+        } else if hit && e < peak * 0.5 {
+            dipped = true;
         }
     }
 
-    None
-}
+    // 어택부터 DRUM_HIT_FFT_SIZE 구간 스펙트럼 (앞쪽은 평탄, 뒤쪽 절반만 Hann으로 감쇠 — 트랜지언트 보존)
+    let n = DRUM_HIT_FFT_SIZE;
+    let start = onset * win;
+    let mut buffer = vec![Complex { re: 0.0f32, im: 0.0f32 }; n];
+    for (j, s) in samples[start.min(samples.len())..].iter().take(n).enumerate() {
+        let w = if j < n / 2 {
+            1.0
+        } else {
+            0.5 * (1.0 + (std::f32::consts::PI * (j - n / 2) as f32 / (n / 2) as f32).cos())
+        };
+        buffer[j].re = s * w;
+    }
+    FftPlanner::<f32>::new().plan_fft_forward(n).process(&mut buffer);
 
-fn is_audio_file(path: &Path) -> bool {
-    match path.extension().and_then(|e| e.to_str()) {
-        Some(ext) => matches!(
-            ext.to_lowercase().as_str(),
-            "wav" | "mp3" | "flac" | "ogg" | "aiff" | "aif"
-        ),
-        None => false,
+    let energies = FrequencyBands::new(n, sample_rate).energies(&buffer);
+    let total: f32 = energies.iter().sum();
+    if total <= 0.0 {
+        return None;
     }
-}
+    let bands = energies.map(|e| e / total);
 
-fn collect_audio_files(dir: &Path) -> Vec<PathBuf> {
-    let mut files = Vec::new();
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                files.extend(collect_audio_files(&path));
-            } else if is_audio_file(&path) {
-                files.push(path);
-            }
-        }
+    let (mut weighted, mut mag_sum) = (0.0f32, 0.0f32);
+    for (bin, c) in buffer.iter().enumerate().take(n / 2).skip(1) {
+        let mag = (c.re * c.re + c.im * c.im).sqrt();
+        weighted += bin as f32 * sr / n as f32 * mag;
+        mag_sum += mag;
     }
-    files
+    let centroid_hz = if mag_sum > 0.0 { weighted / mag_sum } else { 0.0 };
+
+    Some(DrumHitFeatures {
+        attack_ms,
+        decay_ms,
+        centroid_hz,
+        bands,
+        flams,
+    })
 }
 
-fn generate_pack_uuid(folder_name: &str) -> String {
-    let mut hasher = DefaultHasher::new();
-    folder_name.hash(&mut hasher);
-    format!("ext-{:016x}", hasher.finish())
+/// lo..hi 안이면 1, 밖이면 soft만큼 멀어질 때까지 0으로 선형 감소
+fn fuzzy_range(x: f32, lo: f32, hi: f32, soft: f32) -> f32 {
+    let d = if x < lo {
+        lo - x
+    } else if x > hi {
+        x - hi
+    } else {
+        0.0
+    };
+    (1.0 - d / soft).max(0.0)
 }
 
-fn compute_duration_ms(file_path: &str) -> Option<i64> {
-    let file = std::fs::File::open(file_path).ok()?;
-    let mss = MediaSourceStream::new(Box::new(file), Default::default());
-
-    let mut hint = Hint::new();
-    if let Some(ext) = Path::new(file_path).extension().and_then(|e| e.to_str()) {
-        hint.with_extension(ext);
-    }
+/// 원샷 오디오 → (kick/snare/clap/hat/cymbal/tom/perc, 신뢰도)
+/// 클래스별 특징 범위 적합도의 기하 평균으로 점수를 매기고, 2위와의 차이를 신뢰도에 반영
+fn classify_drum_hit(samples: &[f32], sample_rate: u32) -> Option<(&'static str, f32)> {
+    let f = drum_hit_features(samples, sample_rate)?;
+    let [sub, lowmid, mid, himid, high] = f.bands;
+    let flam = f.flams.min(2) as f32 / 2.0;
+    let nyquist = sample_rate as f32 / 2.0;
+    let score = |parts: &[f32]| parts.iter().product::<f32>().powf(1.0 / parts.len() as f32);
+
+    let scores: [(&'static str, f32); 7] = [
+        ("kick", score(&[
+            fuzzy_range(sub, 0.45, 1.0, 0.25),
+            fuzzy_range(f.centroid_hz, 0.0, 400.0, 600.0),
+            fuzzy_range(f.decay_ms, 60.0, 1000.0, 60.0),
+        ])),
+        ("tom", score(&[
+            fuzzy_range(sub + lowmid, 0.6, 1.0, 0.2),
+            fuzzy_range(lowmid, 0.3, 1.0, 0.2),
+            fuzzy_range(f.centroid_hz, 150.0, 900.0, 500.0),
+            fuzzy_range(f.decay_ms, 120.0, 1200.0, 100.0),
+        ])),
+        ("snare", score(&[
+            fuzzy_range(himid + high, 0.2, 0.65, 0.2),
+            fuzzy_range(sub + lowmid, 0.1, 0.55, 0.2),
+            fuzzy_range(f.centroid_hz, 1200.0, 6000.0, 1500.0),
+            fuzzy_range(f.decay_ms, 60.0, 500.0, 200.0),
+            1.0 - 0.7 * flam,
+        ])),
+        ("clap", score(&[
+            fuzzy_range(mid + himid, 0.45, 1.0, 0.2),
+            fuzzy_range(sub, 0.0, 0.1, 0.15),
+            fuzzy_range(f.centroid_hz, 1000.0, 5000.0, 2000.0),
+            0.4 + 0.6 * flam,
+        ])),
+        ("hat", score(&[
+            fuzzy_range(high, 0.45, 1.0, 0.25),
+            fuzzy_range(f.centroid_hz, 6000.0, nyquist, 2500.0),
+            fuzzy_range(f.decay_ms, 0.0, 350.0, 300.0),
+        ])),
+        ("cymbal", score(&[
+            fuzzy_range(himid + high, 0.6, 1.0, 0.2),
+            fuzzy_range(f.centroid_hz, 4000.0, nyquist, 2000.0),
+            fuzzy_range(f.decay_ms, 500.0, f32::MAX, 300.0),
+        ])),
+        // 기타 타악기: 다른 클래스에 맞지 않는 짧은 중고역 타격 (약간 불리하게)
+        ("perc", 0.8 * score(&[
+            fuzzy_range(sub, 0.0, 0.2, 0.2),
+            fuzzy_range(f.centroid_hz, 500.0, 6000.0, 1000.0),
+            fuzzy_range(f.decay_ms, 0.0, 300.0, 200.0),
+        ])),
+    ];
 
-    let probed = symphonia::default::get_probe()
-        .format(
-            &hint,
-            mss,
-            &FormatOptions::default(),
-            &MetadataOptions::default(),
-        )
-        .ok()?;
+    // 어택이 느리면 타악기가 아님
+    let percussive = fuzzy_range(f.attack_ms, 0.0, 30.0, 50.0);
+    let mut ranked = scores;
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let (class, best) = ranked[0];
+    let confidence = (best - 0.5 * ranked[1].1) * percussive;
+    (confidence >= DRUM_HIT_MIN_CONFIDENCE).then_some((class, confidence))
+}
 
-    let track = probed.format.default_track()?;
-    let tb = track.codec_params.time_base?;
-    let nf = track.codec_params.n_frames?;
-    let time = tb.calc_time(nf);
-    let ms = (time.seconds as f64 + time.frac) * 1000.0;
-    Some(ms as i64)
+/// 파일명 태그에 타악기 종류가 없으면 오디오 분류 결과를 태그로 추가
+/// (상위 카테고리 규칙도 다시 적용) → 추가한 (태그, 신뢰도)
+fn merge_drum_hit_tag(
+    rules: &MetadataRuleSet,
+    tags: &mut Vec<String>,
+    audio: &AnalysisContext,
+) -> Option<(String, f32)> {
+    if tags
+        .iter()
+        .any(|t| DRUM_HIT_TAGS.iter().any(|(_, tag)| t == tag))
+    {
+        return None;
+    }
+    let (class, confidence) = audio.classify_drum_hit()?;
+    let tag = DRUM_HIT_TAGS.iter().find(|(c, _)| *c == class)?.1.to_string();
+    tags.push(tag.clone());
+    apply_category_rules(rules, tags);
+    Some((tag, confidence))
 }
 
-// ── Commands ────────────────────────────────────────────────────────
+// ── Embedded metadata (WAV/AIFF chunks, ID3, Vorbis comments) ───────
+
+/// 이보다 큰 청크는 메타데이터로 보지 않고 건너뜀 (data/SSND 등)
+const EMBEDDED_CHUNK_MAX_BYTES: u32 = 1 << 20;
+/// MP3 앞쪽 ID3v2 태그를 읽을 최대 크기 (앨범 아트 등은 잘려도 무방)
+const ID3_MAX_BYTES: usize = 1 << 20;
+
+/// 샘플 루프 구간 (WAV smpl 청크 / AIFF INST 청크)
+#[derive(Debug, Serialize, Clone)]
+pub struct LoopPoint {
+    pub start_ms: f64,
+    pub end_ms: f64,
+    /// "forward" | "pingpong" | "backward"
+    pub loop_type: String,
+    /// 0 = 무한 반복
+    pub play_count: u32,
+}
 
-#[tauri::command]
-fn check_library_status(state: State<AppState>) -> Result<LibraryStatus, String> {
-    let db = state.db.lock().unwrap();
-    let sample_count: i64 = db
-        .query_row("SELECT COUNT(*) FROM samples", [], |row| row.get(0))
-        .unwrap_or(0);
-    let pack_count: i64 = db
-        .query_row(
-            "SELECT COUNT(DISTINCT pack_uuid) FROM samples WHERE pack_uuid IS NOT NULL",
-            [],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
-    let splice_available = find_splice_db().is_ok();
+/// 큐 마커 (WAV cue 청크 + adtl 라벨 / AIFF MARK 청크)
+#[derive(Debug, Serialize, Clone)]
+pub struct CueMarker {
+    pub position_ms: f64,
+    pub label: Option<String>,
+}
 
-    Ok(LibraryStatus {
-        has_data: sample_count > 0,
-        pack_count: pack_count as usize,
-        sample_count: sample_count as usize,
-        splice_available,
-    })
+/// 파일에 내장된 루프 포인트/큐 마커
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SampleMarkers {
+    /// MIDI 루트 노트 (ACID 루트 노트, 없으면 smpl unity note / AIFF base note)
+    pub root_note: Option<i32>,
+    pub loops: Vec<LoopPoint>,
+    pub cues: Vec<CueMarker>,
 }
 
-#[tauri::command]
-fn import_from_splice(app: tauri::AppHandle, state: State<AppState>) -> Result<ImportResult, String> {
-    // 1. Open Splice DB (read-only)
-    let splice_db_path = find_splice_db()?;
-    let splice_db =
-        Connection::open_with_flags(&splice_db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
-            .map_err(|e| format!("Splice DB 열기 실패: {}", e))?;
-
-    // 2. Read all packs from Splice
-    struct SplicePack {
-        uuid: String,
-        name: Option<String>,
-        description: Option<String>,
-        cover_url: Option<String>,
-        genre: Option<String>,
-        permalink: Option<String>,
+impl SampleMarkers {
+    fn is_empty(&self) -> bool {
+        self.root_note.is_none() && self.loops.is_empty() && self.cues.is_empty()
     }
+}
 
-    let splice_packs: Vec<SplicePack> = {
-        let mut stmt = splice_db
-            .prepare("SELECT uuid, name, description, cover_url, genre, permalink FROM packs")
-            .map_err(|e| e.to_string())?;
-        let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-        let mut out = Vec::new();
-        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-            out.push(SplicePack {
-                uuid: row.get::<_, String>(0).unwrap_or_default(),
-                name: row.get::<_, Option<String>>(1).unwrap_or(None),
-                description: row.get::<_, Option<String>>(2).unwrap_or(None),
-                cover_url: row.get::<_, Option<String>>(3).unwrap_or(None),
-                genre: row.get::<_, Option<String>>(4).unwrap_or(None),
-                permalink: row.get::<_, Option<String>>(5).unwrap_or(None),
-            });
+/// 오디오 파일에 내장된 메타데이터 — 파일명 휴리스틱보다 우선
+#[derive(Debug, Default)]
+struct EmbeddedMetadata {
+    bpm: Option<f64>,
+    /// (키, 코드 타입)
+    key: Option<(String, Option<String>)>,
+    /// ACID one-shot 플래그 → "oneshot" | "loop"
+    sample_type: Option<&'static str>,
+    genre: Option<String>,
+    markers: SampleMarkers,
+}
+
+/// 확장자별로 ACID/smpl/cue/INFO 청크, ID3, Vorbis 코멘트를 읽음 (실패해도 빈 값)
+fn read_embedded_metadata(path: &str) -> EmbeddedMetadata {
+    let mut meta = EmbeddedMetadata::default();
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let _ = match ext.as_str() {
+        "wav" | "wave" | "aif" | "aiff" | "aifc" => {
+            read_chunked_metadata(path, &mut meta).map_err(|e| e.to_string())
         }
-        out
+        "mp3" => read_id3_file(path, &mut meta).map_err(|e| e.to_string()),
+        "flac" | "ogg" => read_vorbis_comments(path, &mut meta),
+        _ => Ok(()),
     };
+    meta
+}
 
-    // 3. Read all samples from Splice
-    struct SpliceRow {
-        local_path: String,
-        filename: String,
-        audio_key: Option<String>,
-        bpm: Option<i32>,
-        chord_type: Option<String>,
-        duration: Option<i64>,
-        file_hash: String,
-        genre: Option<String>,
-        sample_type: Option<String>,
-        tags: Option<String>,
-        pack_uuid: Option<String>,
-    }
+fn le_u16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
 
-    let splice_samples: Vec<SpliceRow> = {
-        let mut stmt = splice_db
-            .prepare(
-                "SELECT local_path, filename, audio_key, bpm, chord_type, duration,
-                        file_hash, genre, sample_type, tags, pack_uuid
-                 FROM samples",
-            )
-            .map_err(|e| e.to_string())?;
-        let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
-        let mut out = Vec::new();
-        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
-            out.push(SpliceRow {
-                local_path: row.get::<_, String>(0).unwrap_or_default(),
-                filename: row.get::<_, String>(1).unwrap_or_default(),
-                audio_key: row.get::<_, Option<String>>(2).unwrap_or(None),
-                bpm: row.get::<_, Option<i32>>(3).unwrap_or(None),
-                chord_type: row.get::<_, Option<String>>(4).unwrap_or(None),
-                duration: row.get::<_, Option<i64>>(5).unwrap_or(None),
-                file_hash: row.get::<_, String>(6).unwrap_or_default(),
-                genre: row.get::<_, Option<String>>(7).unwrap_or(None),
-                sample_type: row.get::<_, Option<String>>(8).unwrap_or(None),
-                tags: row.get::<_, Option<String>>(9).unwrap_or(None),
-                pack_uuid: row.get::<_, Option<String>>(10).unwrap_or(None),
-            });
-        }
-        out
-    };
+fn le_u32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
 
-    // Done with Splice DB
-    drop(splice_db);
+fn be_u16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
 
-    // 4. Write to Slice DB
-    let mut db = state.db.lock().unwrap();
-    db.execute_batch("DELETE FROM samples; DELETE FROM packs;")
-        .map_err(|e| format!("기존 데이터 삭제 실패: {}", e))?;
+fn be_u32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
 
-    {
-        let tx = db.transaction().map_err(|e| e.to_string())?;
+/// ID3v2 syncsafe 정수 (바이트당 7비트)
+fn syncsafe_u32(b: &[u8], at: usize) -> Option<u32> {
+    Some(b.get(at..at + 4)?.iter().fold(0u32, |acc, &x| (acc << 7) | (x & 0x7f) as u32))
+}
 
-        // Insert packs
-        for p in &splice_packs {
-            tx.execute(
-                "INSERT OR IGNORE INTO packs (uuid, name, description, cover_url, genre, permalink, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
-                params![p.uuid, p.name, p.description, p.cover_url, p.genre, p.permalink],
-            )
-            .map_err(|e| e.to_string())?;
+/// NUL 종료 문자열 (latin1/UTF-8 혼용 — 깨진 바이트는 대체 문자)
+fn zstr(b: &[u8]) -> Option<String> {
+    let end = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+    let s = String::from_utf8_lossy(&b[..end]).trim().to_string();
+    (!s.is_empty()).then_some(s)
+}
+
+/// RIFF WAVE / FORM AIFF 청크 목록을 훑어 메타데이터 청크만 읽음 (오디오 데이터는 seek로 건너뜀)
+fn read_chunked_metadata(path: &str, meta: &mut EmbeddedMetadata) -> std::io::Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header)?;
+    let big_endian = match (&header[0..4], &header[8..12]) {
+        (b"RIFF", b"WAVE") => false,
+        (b"FORM", b"AIFF") | (b"FORM", b"AIFC") => true,
+        _ => return Ok(()),
+    };
+    let wanted: &[&[u8; 4]] = if big_endian {
+        &[b"COMM", b"MARK", b"INST", b"ID3 "]
+    } else {
+        &[b"fmt ", b"acid", b"smpl", b"cue ", b"LIST", b"id3 ", b"ID3 "]
+    };
+
+    let mut chunks: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+    let mut pos = 12u64;
+    while pos + 8 <= file_len {
+        let mut chunk_header = [0u8; 8];
+        if file.read_exact(&mut chunk_header).is_err() {
+            break;
+        }
+        let id: [u8; 4] = [chunk_header[0], chunk_header[1], chunk_header[2], chunk_header[3]];
+        let size = if big_endian {
+            be_u32(&chunk_header, 4)
+        } else {
+            le_u32(&chunk_header, 4)
+        }
+        .unwrap_or(0);
+        if size <= EMBEDDED_CHUNK_MAX_BYTES && wanted.contains(&&id) {
+            let mut body = vec![0u8; size as usize];
+            if file.read_exact(&mut body).is_err() {
+                break; // 잘린 파일
+            }
+            chunks.push((id, body));
         }
+        // 청크는 짝수 바이트 정렬
+        pos += 8 + size as u64 + (size as u64 & 1);
+        file.seek(SeekFrom::Start(pos))?;
+    }
 
-        // Insert samples + copy audio files
-        let total = splice_samples.len();
-        let splice_sounds_dir = get_splice_sounds_dir().unwrap_or_else(|_| {
-            get_home_dir().unwrap_or_default().join("Splice")
-        });
-        let slice_dir = get_slice_path().unwrap_or_else(|_| {
-            get_home_dir().unwrap_or_default().join("Slice")
-        });
-        let splice_prefix = splice_sounds_dir.to_string_lossy().to_string();
-        let slice_prefix = slice_dir.to_string_lossy().to_string();
-
-        let mut copied = 0usize;
-        let mut skipped = 0usize;
-
-        for (i, s) in splice_samples.iter().enumerate() {
-            // Rewrite path: Splice dir → Slice dir (크로스 플랫폼)
-            let old_path_buf = PathBuf::from(&s.local_path);
-            let new_path = if let Ok(rel) = old_path_buf.strip_prefix(&splice_sounds_dir) {
-                // PathBuf 기반 상대 경로 추출 → Slice 디렉토리에 합치기
-                slice_dir.join(rel).to_string_lossy().to_string()
-            } else {
-                // strip_prefix 실패 시 문자열 치환 폴백
-                // (Windows \ / macOS / 모두 처리)
-                let normalized_local = s.local_path.replace('\\', "/");
-                let normalized_splice = splice_prefix.replace('\\', "/");
-                let normalized_slice = slice_prefix.replace('\\', "/");
-                normalized_local.replace(&normalized_splice, &normalized_slice)
-            };
-            let new_path_buf = PathBuf::from(&new_path);
+    if big_endian {
+        parse_aiff_chunks(&chunks, meta);
+    } else {
+        parse_wav_chunks(&chunks, meta);
+    }
+    Ok(())
+}
 
-            // Copy audio file
-            if !new_path_buf.exists() {
-                if old_path_buf.exists() {
-                    if let Some(parent) = new_path_buf.parent() {
-                        let _ = std::fs::create_dir_all(parent);
+fn parse_wav_chunks(chunks: &[([u8; 4], Vec<u8>)], meta: &mut EmbeddedMetadata) {
+    let sample_rate = chunks
+        .iter()
+        .find(|(id, _)| id == b"fmt ")
+        .and_then(|(_, body)| le_u32(body, 4))
+        .filter(|&sr| sr > 0);
+    let Some(sample_rate) = sample_rate else {
+        return;
+    };
+    let frames_to_ms = |frames: u32| frames as f64 * 1000.0 / sample_rate as f64;
+
+    let mut acid_root: Option<i32> = None;
+    let mut unity_note: Option<i32> = None;
+    let mut cue_points: Vec<(u32, u32)> = Vec::new(); // (cue id, 프레임 위치)
+    let mut labels: std::collections::HashMap<u32, String> = std::collections::HashMap::new();
+
+    for (id, body) in chunks {
+        match id {
+            b"acid" => {
+                // flags, 루트 노트, (예약), 비트 수, 박자, 템포
+                let flags = le_u32(body, 0).unwrap_or(0);
+                let one_shot = flags & 0x01 != 0;
+                meta.sample_type = Some(if one_shot { "oneshot" } else { "loop" });
+                if flags & 0x02 != 0 {
+                    acid_root = le_u16(body, 4).map(|n| n as i32).filter(|n| (0..128).contains(n));
+                }
+                let tempo = body
+                    .get(20..24)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64);
+                if let Some(tempo) = tempo.filter(|t| !one_shot && (40.0..=300.0).contains(t)) {
+                    meta.bpm = Some(round_bpm(tempo));
+                }
+            }
+            b"smpl" => {
+                unity_note = le_u32(body, 12).map(|n| n as i32).filter(|n| (0..128).contains(n));
+                let count = le_u32(body, 28).unwrap_or(0) as usize;
+                for i in 0..count.min(64) {
+                    let at = 36 + i * 24;
+                    let (Some(kind), Some(start), Some(end)) =
+                        (le_u32(body, at + 4), le_u32(body, at + 8), le_u32(body, at + 12))
+                    else {
+                        break;
+                    };
+                    meta.markers.loops.push(LoopPoint {
+                        start_ms: frames_to_ms(start),
+                        // 끝 프레임 포함
+                        end_ms: frames_to_ms(end.saturating_add(1)),
+                        loop_type: match kind {
+                            1 => "pingpong",
+                            2 => "backward",
+                            _ => "forward",
+                        }
+                        .to_string(),
+                        play_count: le_u32(body, at + 20).unwrap_or(0),
+                    });
+                }
+            }
+            b"cue " => {
+                let count = le_u32(body, 0).unwrap_or(0) as usize;
+                for i in 0..count.min(1024) {
+                    let at = 4 + i * 24;
+                    let (Some(cue_id), Some(offset)) = (le_u32(body, at), le_u32(body, at + 20)) else {
+                        break;
+                    };
+                    cue_points.push((cue_id, offset));
+                }
+            }
+            b"LIST" => {
+                let form = body.get(0..4).unwrap_or_default();
+                let mut at = 4;
+                while let Some(size) = le_u32(body, at + 4) {
+                    let sub_id = &body[at..at + 4];
+                    let data = body.get(at + 8..at + 8 + size as usize).unwrap_or_default();
+                    match (form, sub_id) {
+                        (b"INFO", b"IGNR") if meta.genre.is_none() => meta.genre = zstr(data),
+                        (b"adtl", b"labl") => {
+                            if let (Some(cue_id), Some(text)) = (le_u32(data, 0), zstr(data.get(4..).unwrap_or_default())) {
+                                labels.insert(cue_id, text);
+                            }
+                        }
+                        _ => {}
                     }
-                    if std::fs::copy(&old_path_buf, &new_path_buf).is_ok() {
-                        copied += 1;
+                    at += 8 + size as usize + (size as usize & 1);
+                }
+            }
+            b"id3 " | b"ID3 " => parse_id3v2(body, meta),
+            _ => {}
+        }
+    }
+
+    cue_points.sort_by_key(|(_, offset)| *offset);
+    meta.markers.cues = cue_points
+        .into_iter()
+        .map(|(cue_id, offset)| CueMarker {
+            position_ms: frames_to_ms(offset),
+            label: labels.remove(&cue_id),
+        })
+        .collect();
+    meta.markers.root_note = acid_root.or(unity_note);
+    // 태그에 키가 없으면 ACID 루트 노트 (장/단조 정보 없음)
+    if meta.key.is_none() {
+        meta.key = acid_root.map(|n| (PITCH_NAMES[n as usize % 12].to_string(), None));
+    }
+}
+
+/// 80비트 IEEE 확장 정밀도 (AIFF COMM 샘플레이트)
+fn ieee_extended_to_f64(b: &[u8]) -> Option<f64> {
+    let exponent = (be_u16(b, 0)? & 0x7fff) as i32;
+    let mantissa = u64::from_be_bytes(b.get(2..10)?.try_into().ok()?);
+    if exponent == 0 && mantissa == 0 {
+        return Some(0.0);
+    }
+    Some(mantissa as f64 * 2f64.powi(exponent - 16383 - 63))
+}
+
+fn parse_aiff_chunks(chunks: &[([u8; 4], Vec<u8>)], meta: &mut EmbeddedMetadata) {
+    let sample_rate = chunks
+        .iter()
+        .find(|(id, _)| id == b"COMM")
+        .and_then(|(_, body)| ieee_extended_to_f64(body.get(8..18)?))
+        .filter(|&sr| sr > 0.0);
+    let Some(sample_rate) = sample_rate else {
+        return;
+    };
+    let frames_to_ms = |frames: u32| frames as f64 * 1000.0 / sample_rate;
+
+    // 마커: (id, 프레임 위치, 이름) — pstring은 길이 바이트 포함 짝수 정렬
+    let mut markers: Vec<(u16, u32, Option<String>)> = Vec::new();
+    if let Some((_, body)) = chunks.iter().find(|(id, _)| id == b"MARK") {
+        let count = be_u16(body, 0).unwrap_or(0) as usize;
+        let mut at = 2;
+        for _ in 0..count {
+            let (Some(marker_id), Some(position), Some(&len)) =
+                (be_u16(body, at), be_u32(body, at + 2), body.get(at + 6))
+            else {
+                break;
+            };
+            let name = body.get(at + 7..at + 7 + len as usize).and_then(zstr);
+            markers.push((marker_id, position, name));
+            at += 7 + len as usize + ((len as usize + 1) & 1);
+        }
+    }
+
+    for (id, body) in chunks {
+        match id {
+            b"INST" => {
+                meta.markers.root_note = body.first().map(|&n| n as i32).filter(|n| (0..128).contains(n));
+                // sustain loop, release loop: (재생 모드, 시작 마커, 끝 마커)
+                for at in [8, 14] {
+                    let (Some(mode), Some(begin), Some(end)) =
+                        (be_u16(body, at), be_u16(body, at + 2), be_u16(body, at + 4))
+                    else {
+                        continue;
+                    };
+                    let position = |marker_id: u16| markers.iter().find(|m| m.0 == marker_id).map(|m| m.1);
+                    let (Some(start), Some(end)) = (position(begin), position(end)) else {
+                        continue;
+                    };
+                    if mode == 0 || end <= start {
+                        continue;
                     }
+                    meta.markers.loops.push(LoopPoint {
+                        start_ms: frames_to_ms(start),
+                        end_ms: frames_to_ms(end),
+                        loop_type: if mode == 2 { "pingpong" } else { "forward" }.to_string(),
+                        play_count: 0,
+                    });
                 }
-            } else {
-                skipped += 1;
             }
+            b"ID3 " => parse_id3v2(body, meta),
+            _ => {}
+        }
+    }
+
+    markers.sort_by_key(|m| m.1);
+    meta.markers.cues = markers
+        .into_iter()
+        .map(|(_, position, label)| CueMarker {
+            position_ms: frames_to_ms(position),
+            label,
+        })
+        .collect();
+}
+
+/// MP3 앞쪽 ID3v2 태그
+fn read_id3_file(path: &str, meta: &mut EmbeddedMetadata) -> std::io::Result<()> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut header = [0u8; 10];
+    file.read_exact(&mut header)?;
+    if &header[0..3] != b"ID3" {
+        return Ok(());
+    }
+    let size = (syncsafe_u32(&header, 6).unwrap_or(0) as usize).min(ID3_MAX_BYTES);
+    let mut tag = header.to_vec();
+    file.take(size as u64).read_to_end(&mut tag)?;
+    parse_id3v2(&tag, meta);
+    Ok(())
+}
+
+/// ID3v2.2–2.4 텍스트 프레임 (BPM, 키, 장르)
+fn parse_id3v2(data: &[u8], meta: &mut EmbeddedMetadata) {
+    if data.len() < 10 || &data[0..3] != b"ID3" {
+        return;
+    }
+    let major = data[3];
+    let flags = data[5];
+    let end = (10 + syncsafe_u32(data, 6).unwrap_or(0) as usize).min(data.len());
+    let mut body = data[10..end].to_vec();
+    // 태그 전체 unsynchronisation (v2.3 이하): FF 00 → FF
+    if flags & 0x80 != 0 && major < 4 {
+        let mut out = Vec::with_capacity(body.len());
+        for (i, &b) in body.iter().enumerate() {
+            if !(b == 0 && i > 0 && body[i - 1] == 0xff) {
+                out.push(b);
+            }
+        }
+        body = out;
+    }
+    let mut at = 0usize;
+    if flags & 0x40 != 0 && major >= 3 {
+        // 확장 헤더
+        at = match major {
+            3 => 4 + be_u32(&body, 0).unwrap_or(0) as usize,
+            _ => syncsafe_u32(&body, 0).unwrap_or(0) as usize,
+        };
+    }
 
-            // Use file_hash or generate fallback
-            let hash = if s.file_hash.is_empty() {
-                format!("path_{}", i)
-            } else {
-                s.file_hash.clone()
+    let (id_len, header_len) = if major == 2 { (3, 6) } else { (4, 10) };
+    while at + header_len <= body.len() {
+        let id = &body[at..at + id_len];
+        if id[0] == 0 {
+            break; // 패딩
+        }
+        let size = match major {
+            2 => (body[at + 3] as usize) << 16 | (body[at + 4] as usize) << 8 | body[at + 5] as usize,
+            3 => be_u32(&body, at + 4).unwrap_or(0) as usize,
+            _ => syncsafe_u32(&body, at + 4).unwrap_or(0) as usize,
+        };
+        let start = at + header_len;
+        let Some(frame) = body.get(start..start + size) else {
+            break;
+        };
+        let name = match id {
+            b"TBPM" | b"TBP" => Some("BPM"),
+            b"TKEY" | b"TKE" => Some("INITIALKEY"),
+            b"TCON" | b"TCO" => Some("GENRE"),
+            _ => None,
+        };
+        if let (Some(name), Some(text)) = (name, id3_text(frame)) {
+            apply_embedded_tag(name, &text, meta);
+        }
+        at = start + size;
+    }
+}
+
+/// ID3 텍스트 프레임 값 (인코딩 바이트 + 텍스트, 여러 값이면 첫 번째)
+fn id3_text(frame: &[u8]) -> Option<String> {
+    let (&encoding, text) = frame.split_first()?;
+    let decoded = match encoding {
+        0 => text.iter().map(|&c| c as char).collect::<String>(),
+        1 | 2 => {
+            let big_endian = match text.get(0..2) {
+                Some([0xff, 0xfe]) => false,
+                Some([0xfe, 0xff]) => true,
+                _ => encoding == 2,
             };
+            let skip = if encoding == 1 && text.len() >= 2 { 2 } else { 0 };
+            let units: Vec<u16> = text[skip..]
+                .chunks_exact(2)
+                .map(|c| if big_endian { u16::from_be_bytes([c[0], c[1]]) } else { u16::from_le_bytes([c[0], c[1]]) })
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(text).to_string(),
+    };
+    let first = decoded.split('\0').next()?.trim().to_string();
+    (!first.is_empty()).then_some(first)
+}
 
-            // BPM: Splice DB 값은 그대로 사용, 없으면 파일명에서 파싱 시도
-            let bpm = s.bpm.or_else(|| parse_bpm_from_filename(&s.filename));
+/// FLAC/OGG Vorbis 코멘트 (symphonia 메타데이터)
+fn read_vorbis_comments(path: &str, meta: &mut EmbeddedMetadata) -> Result<(), String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = Path::new(path).extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+    let mut probed = symphonia::default::get_probe()
+        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| e.to_string())?;
 
-            tx.execute(
-                "INSERT OR IGNORE INTO samples
-                 (local_path, filename, audio_key, bpm, chord_type, duration,
-                  file_hash, genre, sample_type, tags, pack_uuid, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, datetime('now'))",
-                params![
-                    new_path,
-                    s.filename,
-                    s.audio_key,
-                    bpm,
-                    s.chord_type,
-                    s.duration,
-                    hash,
-                    s.genre,
-                    s.sample_type,
-                    s.tags,
-                    s.pack_uuid
-                ],
-            )
-            .map_err(|e| e.to_string())?;
+    let mut tags: Vec<(String, String)> = Vec::new();
+    if let Some(rev) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+        tags.extend(rev.tags().iter().map(|t| (t.key.clone(), t.value.to_string())));
+    }
+    if let Some(rev) = probed.format.metadata().current() {
+        tags.extend(rev.tags().iter().map(|t| (t.key.clone(), t.value.to_string())));
+    }
+    for (key, value) in tags {
+        apply_embedded_tag(&key, &value, meta);
+    }
+    Ok(())
+}
 
-            // Emit progress every 10 items
-            if i % 10 == 0 || i + 1 == total {
-                let _ = app.emit(
-                    "import-progress",
-                    ImportProgress {
-                        current: i + 1,
-                        total,
-                        current_file: s.filename.clone(),
-                        current_pack: 1,
-                        total_packs: 1,
-                        current_pack_name: String::new(),
-                    },
-                );
+/// 태그 이름(Vorbis/ID3 공통) → 필드. 이미 값이 있으면 유지 (ACID 청크 우선)
+fn apply_embedded_tag(name: &str, value: &str, meta: &mut EmbeddedMetadata) {
+    match name.to_ascii_uppercase().as_str() {
+        "BPM" | "TEMPO" if meta.bpm.is_none() => {
+            meta.bpm = value
+                .trim()
+                .replace(',', ".")
+                .parse::<f64>()
+                .ok()
+                .filter(|b| (40.0..=300.0).contains(b))
+                .map(round_bpm);
+        }
+        "INITIALKEY" | "KEY" if meta.key.is_none() => meta.key = parse_key_tag(value),
+        "GENRE" if meta.genre.is_none() => {
+            // ID3v1 장르 번호 "(17)" 접두사는 버리고, 번호만 있으면 무시
+            let genre = Regex::new(r"^\(\d+\)").unwrap().replace(value.trim(), "").trim().to_string();
+            if !genre.is_empty() && !genre.chars().all(|c| c.is_ascii_digit()) {
+                meta.genre = Some(genre);
             }
         }
+        _ => {}
+    }
+}
 
-        tx.commit().map_err(|e| e.to_string())?;
+/// 키 태그 값 ("Am", "C#", "F# minor", Camelot "8A") → (키, 코드 타입)
+fn parse_key_tag(value: &str) -> Option<(String, Option<String>)> {
+    // Camelot 휠: 1A = G#m … 12A = C#m, 1B = B … 12B = E
+    const CAMELOT_MINOR: [&str; 12] = ["G#", "D#", "A#", "F", "C", "G", "D", "A", "E", "B", "F#", "C#"];
+    const CAMELOT_MAJOR: [&str; 12] = ["B", "F#", "C#", "G#", "D#", "A#", "F", "C", "G", "D", "A", "E"];
+
+    let value = value.trim().replace('♯', "#").replace('♭', "b");
+    let camelot = Regex::new(r"(?i)^(1[0-2]|[1-9])([ab])$").unwrap();
+    if let Some(caps) = camelot.captures(&value) {
+        let idx = caps[1].parse::<usize>().ok()? - 1;
+        let minor = caps[2].eq_ignore_ascii_case("a");
+        let note = if minor { CAMELOT_MINOR[idx] } else { CAMELOT_MAJOR[idx] };
+        let chord = if minor { "minor" } else { "major" };
+        return Some((note.to_string(), Some(chord.to_string())));
+    }
+    let re = Regex::new(r"^([A-Ga-g])([#b]?)\s*(?i:(m|min|minor|maj|major))?$").unwrap();
+    let caps = re.captures(&value)?;
+    let note = caps[1].to_uppercase() + &caps[2];
+    let minor = caps.get(3).is_some_and(|q| {
+        let q = q.as_str().to_lowercase();
+        q == "m" || q.starts_with("min")
+    });
+    let chord = if minor { "minor" } else { "major" };
+    Some((note, Some(chord.to_string())))
+}
 
-        // Return result
-        let total_packs: i64 = db
-            .query_row(
-                "SELECT COUNT(DISTINCT p.uuid) FROM packs p JOIN samples s ON s.pack_uuid = p.uuid",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(0);
+/// 내장 루프/큐 테이블 (samples 삭제 시 트리거로 정리)
+fn init_sample_markers(db: &Connection) -> Result<(), String> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sample_loops (
+            sample_id INTEGER NOT NULL,
+            idx INTEGER NOT NULL,
+            start_ms REAL NOT NULL,
+            end_ms REAL NOT NULL,
+            loop_type TEXT NOT NULL,
+            play_count INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (sample_id, idx)
+        );
+        CREATE TABLE IF NOT EXISTS sample_cues (
+            sample_id INTEGER NOT NULL,
+            idx INTEGER NOT NULL,
+            position_ms REAL NOT NULL,
+            label TEXT,
+            PRIMARY KEY (sample_id, idx)
+        );
+        CREATE TRIGGER IF NOT EXISTS sample_markers_delete AFTER DELETE ON samples BEGIN
+            DELETE FROM sample_loops WHERE sample_id = old.id;
+            DELETE FROM sample_cues WHERE sample_id = old.id;
+        END;",
+    )
+    .map_err(|e| format!("마커 테이블 초기화 실패: {}", e))?;
+    let _ = db.execute("ALTER TABLE samples ADD COLUMN root_note INTEGER", []);
+    Ok(())
+}
 
-        Ok(ImportResult {
-            files_copied: copied,
-            files_skipped: skipped,
-            total_packs: total_packs as usize,
-        })
+/// 샘플의 루프/큐/루트 노트를 교체 저장
+fn store_sample_markers(
+    conn: &Connection,
+    sample_id: i64,
+    markers: &SampleMarkers,
+) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM sample_loops WHERE sample_id = ?1", params![sample_id])?;
+    conn.execute("DELETE FROM sample_cues WHERE sample_id = ?1", params![sample_id])?;
+    for (i, l) in markers.loops.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO sample_loops (sample_id, idx, start_ms, end_ms, loop_type, play_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![sample_id, i as i64, l.start_ms, l.end_ms, l.loop_type, l.play_count],
+        )?;
+    }
+    for (i, c) in markers.cues.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO sample_cues (sample_id, idx, position_ms, label) VALUES (?1, ?2, ?3, ?4)",
+            params![sample_id, i as i64, c.position_ms, c.label],
+        )?;
     }
+    conn.execute(
+        "UPDATE samples SET root_note = ?2 WHERE id = ?1",
+        params![sample_id, markers.root_note],
+    )?;
+    Ok(())
 }
 
+/// 샘플 파일에 내장돼 있던 루프 포인트/큐 마커/루트 노트
 #[tauri::command]
-fn scan_library(state: State<AppState>) -> Result<LibraryData, String> {
+fn get_sample_markers(sample_id: i64, state: State<AppState>) -> Result<SampleMarkers, String> {
     let db = state.db.lock().unwrap();
+    let root_note: Option<i32> = db
+        .query_row("SELECT root_note FROM samples WHERE id = ?1", params![sample_id], |row| {
+            row.get(0)
+        })
+        .map_err(|e| format!("샘플을 찾을 수 없습니다: {}", e))?;
     let mut stmt = db
         .prepare(
-            "SELECT p.uuid, p.name, p.genre, p.cover_url, COUNT(s.id) as sample_count, p.created_at
-             FROM packs p
-             JOIN samples s ON s.pack_uuid = p.uuid
-             GROUP BY p.uuid
-             ORDER BY p.name COLLATE NOCASE",
+            "SELECT start_ms, end_ms, loop_type, play_count FROM sample_loops
+             WHERE sample_id = ?1 ORDER BY idx",
         )
         .map_err(|e| e.to_string())?;
-
-    let packs: Vec<Pack> = stmt
-        .query_map([], |row| {
-            Ok(Pack {
-                uuid: row.get(0)?,
-                name: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
-                genre: row.get(2)?,
-                cover_url: row.get(3)?,
-                sample_count: row.get::<_, i64>(4)? as usize,
-                created_at: row.get(5)?,
+    let loops = stmt
+        .query_map(params![sample_id], |row| {
+            Ok(LoopPoint {
+                start_ms: row.get(0)?,
+                end_ms: row.get(1)?,
+                loop_type: row.get(2)?,
+                play_count: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    let mut stmt = db
+        .prepare("SELECT position_ms, label FROM sample_cues WHERE sample_id = ?1 ORDER BY idx")
+        .map_err(|e| e.to_string())?;
+    let cues = stmt
+        .query_map(params![sample_id], |row| {
+            Ok(CueMarker {
+                position_ms: row.get(0)?,
+                label: row.get(1)?,
             })
         })
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
+    Ok(SampleMarkers {
+        root_note,
+        loops,
+        cues,
+    })
+}
 
-    let total_samples = packs.iter().map(|p| p.sample_count).sum();
+// ── Metadata write-back (RIFF INFO/ACID, ID3, Vorbis comments) ──────
 
-    Ok(LibraryData {
-        packs,
-        total_samples,
-    })
+/// 같은 디렉토리에 이 접미사로 임시 파일을 쓴 뒤 rename으로 원본 교체
+const TAG_WRITE_TMP_SUFFIX: &str = ".slice-tmp";
+/// 태그 목록을 기록할 Vorbis 코멘트/ID3 TXXX 이름 (RIFF INFO는 IKEY)
+const KEYWORDS_TAG: &str = "KEYWORDS";
+
+/// 파일 태그 필드 하나의 변경 (before: 지금 파일에 있는 값)
+#[derive(Debug, Serialize, Clone)]
+pub struct TagWriteChange {
+    /// "bpm" | "key" | "genre" | "tags"
+    pub field: String,
+    pub before: Option<String>,
+    pub after: String,
 }
 
-#[tauri::command]
-fn get_all_samples(state: State<AppState>) -> Result<Vec<Sample>, String> {
-    let db = state.db.lock().unwrap();
-    let mut stmt = db
-        .prepare(
-            "SELECT s.id, s.local_path, s.filename, s.audio_key, s.bpm, s.chord_type,
-                    s.duration, COALESCE(s.genre, p.genre) as genre,
-                    s.sample_type, s.tags,
-                    s.pack_uuid, p.name as pack_name, p.genre as pack_genre,
-                    s.created_at
-             FROM samples s
-             LEFT JOIN packs p ON s.pack_uuid = p.uuid
-             ORDER BY s.filename COLLATE NOCASE",
-        )
-        .map_err(|e| e.to_string())?;
+/// 샘플 하나의 파일 태그 쓰기 결과
+#[derive(Debug, Serialize, Clone)]
+pub struct TagWriteReport {
+    pub sample_id: i64,
+    pub filename: String,
+    pub local_path: String,
+    /// "unchanged" | "pending"(dry run) | "written" | "unsupported" | "failed"
+    pub status: String,
+    pub changes: Vec<TagWriteChange>,
+    pub error: Option<String>,
+}
 
-    let samples: Vec<Sample> = stmt
-        .query_map([], |row| {
-            Ok(Sample {
-                id: row.get(0)?,
-                local_path: row.get(1)?,
-                filename: row.get(2)?,
-                audio_key: row.get(3)?,
-                bpm: row.get(4)?,
-                chord_type: row.get(5)?,
-                duration: row.get(6)?,
-                genre: row.get(7)?,
-                sample_type: row.get(8)?,
-                tags: row.get(9)?,
-                pack_uuid: row.get(10)?,
-                pack_name: row.get(11)?,
-                pack_genre: row.get(12)?,
-                created_at: row.get(13)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
+/// 파일에 들어 있는/들어갈 태그 값 (키는 "Am" 표기, 태그는 "; " 구분)
+#[derive(Debug, Default, Clone)]
+struct FileTags {
+    bpm: Option<f64>,
+    key: Option<String>,
+    genre: Option<String>,
+    keywords: Option<String>,
+}
 
-    Ok(samples)
+struct TagWriteTarget {
+    id: i64,
+    filename: String,
+    path: String,
+    tags: FileTags,
+    one_shot: bool,
+    bars: Option<i32>,
 }
 
-#[tauri::command]
-fn get_pack_samples(pack_uuid: String, state: State<AppState>) -> Result<Vec<Sample>, String> {
-    let db = state.db.lock().unwrap();
+fn load_tag_write_targets(db: &Connection, sample_ids: &[i64]) -> Result<Vec<TagWriteTarget>, String> {
+    let root = get_library_root(db);
     let mut stmt = db
-        .prepare(
-            "SELECT s.id, s.local_path, s.filename, s.audio_key, s.bpm, s.chord_type,
-                    s.duration, COALESCE(s.genre, p.genre) as genre,
-                    s.sample_type, s.tags,
-                    s.pack_uuid, p.name as pack_name, p.genre as pack_genre,
-                    s.created_at
-             FROM samples s
-             LEFT JOIN packs p ON s.pack_uuid = p.uuid
-             WHERE s.pack_uuid = ?1
-             ORDER BY s.filename COLLATE NOCASE",
-        )
+        .prepare(&format!(
+            "SELECT s.id, s.filename, s.local_path, s.bpm, {}, COALESCE(s.genre, p.genre), s.tags,
+                    s.sample_type, s.bars
+             FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE s.id IN ({})
+             ORDER BY s.id",
+            KEY_LABEL_SQL,
+            sql_placeholders(sample_ids.len())
+        ))
         .map_err(|e| e.to_string())?;
-
-    let samples: Vec<Sample> = stmt
-        .query_map(params![pack_uuid], |row| {
-            Ok(Sample {
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(sample_ids.iter()), |row| {
+            let keywords = row.get::<_, Option<String>>(6)?.map(|tags| {
+                tags.split(',')
+                    .map(|t| t.trim())
+                    .filter(|t| !t.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            });
+            Ok(TagWriteTarget {
                 id: row.get(0)?,
-                local_path: row.get(1)?,
-                filename: row.get(2)?,
-                audio_key: row.get(3)?,
-                bpm: row.get(4)?,
-                chord_type: row.get(5)?,
-                duration: row.get(6)?,
-                genre: row.get(7)?,
-                sample_type: row.get(8)?,
-                tags: row.get(9)?,
-                pack_uuid: row.get(10)?,
-                pack_name: row.get(11)?,
-                pack_genre: row.get(12)?,
-                created_at: row.get(13)?,
+                filename: row.get(1)?,
+                path: resolve_library_path(&root, &row.get::<_, String>(2)?),
+                tags: FileTags {
+                    bpm: row.get(3)?,
+                    key: row.get(4)?,
+                    genre: row.get::<_, Option<String>>(5)?.filter(|g| !g.trim().is_empty()),
+                    keywords: keywords.filter(|k| !k.is_empty()),
+                },
+                one_shot: row.get::<_, Option<String>>(7)?.as_deref() == Some("oneshot"),
+                bars: row.get(8)?,
             })
         })
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
-
-    Ok(samples)
+    Ok(rows)
 }
 
-/// 폴더 트리 구조를 재귀적으로 스캔
-fn build_folder_tree(dir: &Path) -> FolderNode {
-    let name = dir
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("Unknown")
-        .to_string();
-
-    let mut audio_count = 0usize;
-    let mut children = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        let mut entries: Vec<_> = entries.flatten().collect();
-        entries.sort_by_key(|e| e.file_name());
-
-        for entry in entries {
-            let path = entry.path();
-            if path.is_dir() {
-                // .으로 시작하는 숨김 폴더 제외
-                let fname = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if !fname.starts_with('.') {
-                    let child = build_folder_tree(&path);
-                    if child.total_audio_count > 0 {
-                        children.push(child);
-                    }
-                }
-            } else if is_audio_file(&path) {
-                audio_count += 1;
-            }
+/// 파일에 있는 값과 쓸 값 비교 — DB에 값이 없는 필드는 파일 값을 그대로 둠
+fn file_tag_changes(current: &FileTags, desired: &FileTags) -> Vec<TagWriteChange> {
+    let mut changes = Vec::new();
+    let mut push = |field: &str, before: Option<String>, after: String| {
+        changes.push(TagWriteChange {
+            field: field.to_string(),
+            before,
+            after,
+        })
+    };
+    if let Some(bpm) = desired.bpm {
+        if !current.bpm.is_some_and(|b| (b - bpm).abs() < 0.05) {
+            push("bpm", current.bpm.map(format_bpm), format_bpm(bpm));
         }
     }
-
-    let total_audio_count = audio_count + children.iter().map(|c| c.total_audio_count).sum::<usize>();
-
-    FolderNode {
-        name,
-        path: dir.to_string_lossy().to_string(),
-        audio_count,
-        total_audio_count,
-        children,
+    if let Some(key) = &desired.key {
+        let same = current
+            .key
+            .as_deref()
+            .is_some_and(|k| k == key || parse_key_tag(k).is_some_and(|p| Some(p) == parse_key_tag(key)));
+        if !same {
+            push("key", current.key.clone(), key.clone());
+        }
     }
+    for (field, before, after) in [
+        ("genre", &current.genre, &desired.genre),
+        ("tags", &current.keywords, &desired.keywords),
+    ] {
+        if let Some(after) = after {
+            if before.as_deref().map(str::trim) != Some(after.trim()) {
+                push(field, before.clone(), after.clone());
+            }
+        }
+    }
+    changes
 }
 
-/// 외부 폴더의 트리 구조 스캔 (팩 선택 UI용)
-#[tauri::command]
-fn scan_external_folder(folder_path: String) -> Result<FolderNode, String> {
-    let folder = PathBuf::from(&folder_path);
-    if !folder.is_dir() {
-        return Err("유효한 폴더가 아닙니다".to_string());
-    }
-    let tree = build_folder_tree(&folder);
-    if tree.total_audio_count == 0 {
-        return Err("오디오 파일을 찾을 수 없습니다".to_string());
+/// 임시 파일에 쓴 뒤 rename으로 교체 — 도중에 실패하면 원본은 그대로
+/// (hardlink 저장이면 교체 후 원본 폴더의 파일과 연결이 끊김)
+fn replace_file_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut std::fs::File) -> Result<(), String>,
+) -> Result<(), String> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("sample");
+    let tmp = path.with_file_name(format!(".{}{}", name, TAG_WRITE_TMP_SUFFIX));
+    let result = (|| {
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp)
+            .map_err(|e| format!("임시 파일 생성 실패: {}", e))?;
+        write(&mut file)?;
+        file.sync_all().map_err(|e| e.to_string())?;
+        if let Ok(meta) = std::fs::metadata(path) {
+            let _ = std::fs::set_permissions(&tmp, meta.permissions());
+        }
+        std::fs::rename(&tmp, path).map_err(|e| format!("파일 교체 실패: {}", e))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
     }
-    Ok(tree)
+    result
 }
 
-/// 팩 이름 충돌 확인
-#[tauri::command]
-fn check_pack_name_conflicts(
-    pack_names: Vec<String>,
-    state: State<AppState>,
-) -> Result<Vec<PackConflict>, String> {
-    let db = state.db.lock().unwrap();
-    let mut conflicts = Vec::new();
+/// 형식별로 파일 태그를 비교하고, dry_run이 아니면 바뀐 필드만 다시 씀
+/// 지원하지 않는 형식이면 None
+fn write_file_tags(t: &TagWriteTarget, dry_run: bool) -> Result<Option<Vec<TagWriteChange>>, String> {
+    // 심볼릭 링크는 링크가 아니라 실제 파일을 교체
+    let path = std::fs::canonicalize(&t.path).map_err(|e| format!("파일을 찾을 수 없습니다: {}", e))?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let changes = match ext.as_str() {
+        "wav" | "wave" => write_wav_tags(&path, t, dry_run)?,
+        "mp3" => write_id3_tags(&path, &t.tags, dry_run)?,
+        "flac" => write_flac_tags(&path, &t.tags, dry_run)?,
+        "ogg" => write_ogg_tags(&path, &t.tags, dry_run)?,
+        _ => return Ok(None),
+    };
+    Ok(Some(changes))
+}
 
-    for name in &pack_names {
-        let result: Result<(String, usize), _> = db.query_row(
-            "SELECT p.uuid, COUNT(s.id) FROM packs p
-             LEFT JOIN samples s ON s.pack_uuid = p.uuid
-             WHERE p.name = ?1
-             GROUP BY p.uuid",
-            params![name],
-            |row| Ok((row.get(0)?, row.get(1)?)),
-        );
-        if let Ok((uuid, count)) = result {
-            conflicts.push(PackConflict {
-                name: name.clone(),
-                existing_uuid: uuid,
-                existing_sample_count: count,
-            });
-        }
+/// RIFF 청크 하나 (짝수 바이트 정렬)
+fn write_riff_chunk(out: &mut impl Write, id: &[u8], body: &[u8]) -> std::io::Result<()> {
+    out.write_all(id)?;
+    out.write_all(&(body.len() as u32).to_le_bytes())?;
+    out.write_all(body)?;
+    if body.len() % 2 == 1 {
+        out.write_all(&[0])?;
     }
+    Ok(())
+}
 
-    Ok(conflicts)
+/// 노트 이름 → 피치 클래스 ("Db" 같은 플랫 표기 포함)
+fn pitch_class(note: &str) -> Option<usize> {
+    let mut chars = note.chars();
+    let letter = chars.next()?.to_ascii_uppercase().to_string();
+    let base = PITCH_NAMES.iter().position(|n| *n == letter)?;
+    Some(match chars.next() {
+        Some('#') => (base + 1) % 12,
+        Some('b') => (base + 11) % 12,
+        _ => base,
+    })
 }
 
-/// 단일 팩을 임포트하는 내부 헬퍼
-/// replace_uuid: Some이면 기존 팩을 교체 (기존 샘플 삭제 후 해당 UUID 재사용)
-fn import_single_pack(
-    pack_name: &str,
-    pack_folder: &Path,
-    audio_files: &[PathBuf],
-    dest_base: &Path,
-    tx: &rusqlite::Transaction,
-    app: &tauri::AppHandle,
-    global_offset: usize,
-    global_total: usize,
-    replace_uuid: Option<&str>,
-    pack_index: usize,
-    total_packs: usize,
-) -> Result<(usize, usize), String> {
-    let pack_path_str = pack_folder.to_string_lossy().to_string();
+/// WAV: LIST INFO(IGNR 장르, IKEY 태그) + ACID 청크(템포, 루트 노트)
+/// ACID에는 장/단조가 없으므로 키는 루트 노트만 기록
+fn write_wav_tags(path: &Path, t: &TagWriteTarget, dry_run: bool) -> Result<Vec<TagWriteChange>, String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut src = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let file_len = src.metadata().map_err(|e| e.to_string())?.len();
+    let mut header = [0u8; 12];
+    src.read_exact(&mut header).map_err(|e| e.to_string())?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err("RIFF WAVE 파일이 아닙니다".to_string());
+    }
 
-    // 교체 모드: 기존 팩의 UUID 사용 + 기존 샘플 삭제
-    // 새로 추가 모드: 폴더 경로 기반 UUID 생성
-    let pack_uuid = if let Some(uuid) = replace_uuid {
-        // 기존 샘플 삭제
-        tx.execute(
-            "DELETE FROM samples WHERE pack_uuid = ?1",
-            params![uuid],
-        )
-        .map_err(|e| format!("기존 샘플 삭제 실패: {}", e))?;
-        uuid.to_string()
+    // (청크 id, 본문 위치, 크기)
+    let mut chunks: Vec<([u8; 4], u64, u32)> = Vec::new();
+    let mut acid: Option<Vec<u8>> = None;
+    let mut info: Option<Vec<u8>> = None;
+    // 너무 커서 읽지 않은 acid/INFO 청크 — 교체하지 못하므로 해당 필드는 쓰지 않음 (중복 청크 방지)
+    let mut acid_oversized = false;
+    let mut info_oversized = false;
+    let mut pos = 12u64;
+    while pos + 8 <= file_len {
+        let mut chunk_header = [0u8; 8];
+        src.read_exact(&mut chunk_header).map_err(|e| e.to_string())?;
+        let id: [u8; 4] = [chunk_header[0], chunk_header[1], chunk_header[2], chunk_header[3]];
+        let size = le_u32(&chunk_header, 4).unwrap_or(0);
+        if pos + 8 + size as u64 > file_len {
+            return Err("청크 크기가 파일 길이를 넘습니다 (잘린 WAV 파일)".to_string());
+        }
+        if size <= EMBEDDED_CHUNK_MAX_BYTES && (&id == b"acid" || &id == b"LIST") {
+            let mut body = vec![0u8; size as usize];
+            src.read_exact(&mut body).map_err(|e| e.to_string())?;
+            match &id {
+                b"acid" if acid.is_none() => acid = Some(body),
+                b"LIST" if info.is_none() && body.starts_with(b"INFO") => info = Some(body),
+                _ => {}
+            }
+        } else if &id == b"acid" {
+            acid_oversized = true;
+        } else if &id == b"LIST" && size >= 4 {
+            let mut form = [0u8; 4];
+            src.read_exact(&mut form).map_err(|e| e.to_string())?;
+            info_oversized |= &form == b"INFO";
+        }
+        chunks.push((id, pos + 8, size));
+        pos += 8 + size as u64 + (size as u64 & 1);
+        src.seek(SeekFrom::Start(pos)).map_err(|e| e.to_string())?;
+    }
+
+    // INFO 하위 청크 목록
+    let mut info_entries: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+    if let Some(body) = &info {
+        let mut at = 4;
+        while let Some(size) = le_u32(body, at + 4) {
+            let Some(data) = body.get(at + 8..at + 8 + size as usize) else {
+                break;
+            };
+            info_entries.push(([body[at], body[at + 1], body[at + 2], body[at + 3]], data.to_vec()));
+            at += 8 + size as usize + (size as usize & 1);
+        }
+    }
+    let info_text = |id: &[u8; 4]| info_entries.iter().find(|(i, _)| i == id).and_then(|(_, d)| zstr(d));
+    let acid_flags = acid.as_deref().and_then(|b| le_u32(b, 0)).unwrap_or(0);
+    let current = FileTags {
+        bpm: acid
+            .as_deref()
+            .and_then(|b| b.get(20..24))
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+            .filter(|bpm| (40.0..=300.0).contains(bpm))
+            .map(round_bpm),
+        key: acid
+            .as_deref()
+            .filter(|_| acid_flags & 0x02 != 0)
+            .and_then(|b| le_u16(b, 4))
+            .map(|n| PITCH_NAMES[n as usize % 12].to_string()),
+        genre: info_text(b"IGNR"),
+        keywords: info_text(b"IKEY"),
+    };
+    let desired = FileTags {
+        key: t.tags.key.as_deref().and_then(parse_key_tag).map(|(note, _)| note),
+        ..t.tags.clone()
+    };
+    let changes: Vec<TagWriteChange> = file_tag_changes(&current, &desired)
+        .into_iter()
+        .filter(|c| match c.field.as_str() {
+            "bpm" | "key" => !acid_oversized,
+            _ => !info_oversized,
+        })
+        .collect();
+    if dry_run || changes.is_empty() {
+        return Ok(changes);
+    }
+    // 바뀌는 필드가 있는 청크만 다시 만들고, 나머지 청크는 원본 그대로 복사
+    let acid_changed = changes.iter().any(|c| c.field == "bpm" || c.field == "key");
+    let info_changed = changes.iter().any(|c| c.field == "genre" || c.field == "tags");
+
+    // 새 ACID 청크: 기존 플래그/비트 수 유지, 없으면 4/4 박자로 생성
+    let new_acid = acid_changed.then(|| build_wav_acid(acid.as_deref(), &desired, t));
+    let new_info = if info_changed {
+        Some(build_wav_info(&mut info_entries, &desired).map_err(|e| e.to_string())?)
     } else {
-        generate_pack_uuid(&pack_path_str)
+        None
     };
 
-    // 팩 등록
-    let genre = parse_genre_from_path(&pack_path_str);
-    tx.execute(
-        "INSERT OR REPLACE INTO packs (uuid, name, description, cover_url, genre, permalink, created_at)
-         VALUES (?1, ?2, ?3, NULL, ?4, NULL, datetime('now'))",
-        params![pack_uuid, pack_name, format!("외부 임포트: {}", pack_name), genre],
-    )
-    .map_err(|e| format!("팩 등록 실패: {}", e))?;
-
-    let mut copied = 0usize;
-    let mut skipped = 0usize;
-
-    for (i, src_path) in audio_files.iter().enumerate() {
-        let src_str = src_path.to_string_lossy().to_string();
-        let filename = src_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        // 상대 경로 유지
-        let rel_path = src_path
-            .strip_prefix(pack_folder)
-            .unwrap_or(src_path)
-            .to_string_lossy()
-            .to_string();
-        let dest_path = dest_base.join(&rel_path);
-        let dest_str = dest_path.to_string_lossy().to_string();
-
-        // 파일 복사
-        if !dest_path.exists() {
-            if let Some(parent) = dest_path.parent() {
-                let _ = std::fs::create_dir_all(parent);
+    replace_file_atomically(path, |out| {
+        let mut out = std::io::BufWriter::new(out);
+        let io = |e: std::io::Error| e.to_string();
+        out.write_all(b"RIFF\0\0\0\0WAVE").map_err(io)?;
+        let mut pending_acid = new_acid.as_deref();
+        let mut pending_info = new_info.as_deref();
+        for (id, body_pos, size) in &chunks {
+            // 기존 청크는 제자리에서 교체, 없던 청크는 data 청크 앞에 추가
+            if id == b"data" {
+                if acid.is_none() {
+                    if let Some(body) = pending_acid.take() {
+                        write_riff_chunk(&mut out, b"acid", body).map_err(io)?;
+                    }
+                }
+                if info.is_none() {
+                    if let Some(body) = pending_info.take() {
+                        write_riff_chunk(&mut out, b"LIST", body).map_err(io)?;
+                    }
+                }
             }
-            if std::fs::copy(src_path, &dest_path).is_ok() {
-                copied += 1;
+            match id {
+                b"acid" if pending_acid.is_some() => {
+                    if let Some(body) = pending_acid.take() {
+                        write_riff_chunk(&mut out, b"acid", body).map_err(io)?;
+                    }
+                    continue;
+                }
+                b"LIST" if pending_info.is_some() && *size <= EMBEDDED_CHUNK_MAX_BYTES => {
+                    src.seek(SeekFrom::Start(*body_pos)).map_err(io)?;
+                    let mut form = [0u8; 4];
+                    src.read_exact(&mut form).map_err(io)?;
+                    if &form == b"INFO" {
+                        if let Some(body) = pending_info.take() {
+                            write_riff_chunk(&mut out, b"LIST", body).map_err(io)?;
+                        }
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            out.write_all(id).map_err(io)?;
+            out.write_all(&size.to_le_bytes()).map_err(io)?;
+            src.seek(SeekFrom::Start(*body_pos)).map_err(io)?;
+            let copied = std::io::copy(&mut (&mut src).take(*size as u64), &mut out).map_err(io)?;
+            if copied != *size as u64 {
+                return Err("원본 WAV 파일을 끝까지 읽지 못했습니다".to_string());
+            }
+            if size % 2 == 1 {
+                out.write_all(&[0]).map_err(io)?;
             }
-        } else {
-            skipped += 1;
         }
+        if let Some(body) = pending_acid {
+            write_riff_chunk(&mut out, b"acid", body).map_err(io)?;
+        }
+        if let Some(body) = pending_info {
+            write_riff_chunk(&mut out, b"LIST", body).map_err(io)?;
+        }
+        let file = out.into_inner().map_err(|e| e.to_string())?;
+        let riff_size = u32::try_from(file.stream_position().map_err(io)? - 8)
+            .map_err(|_| "WAV 파일이 4GB를 넘습니다".to_string())?;
+        file.seek(SeekFrom::Start(4)).map_err(io)?;
+        file.write_all(&riff_size.to_le_bytes()).map_err(io)
+    })?;
+    Ok(changes)
+}
 
-        // 메타데이터 파싱
-        let full_path_for_parse = format!("{}/{}", pack_name, rel_path);
-        let duration_ms = compute_duration_ms(&dest_str)
-            .or_else(|| compute_duration_ms(&src_str));
-
-        // BPM: 파일명 → 오디오 분석 순으로 시도
-        // (2초 이상 샘플이면 루프 여부와 관계없이 오디오 분석 시도)
-        let bpm = parse_bpm_from_filename(&full_path_for_parse).or_else(|| {
-            let long_enough = duration_ms.map(|d| d >= 2000).unwrap_or(false);
-            if long_enough {
-                let target = if dest_path.exists() { &dest_str } else { &src_str };
-                detect_bpm_from_audio(target)
-            } else {
-                None
+/// WAV acid 청크 본문: 기존 청크의 플래그/비트 수를 유지하고 BPM/루트 음만 교체
+/// (없으면 4/4 박자로 생성, one-shot이면 원샷 플래그)
+fn build_wav_acid(acid: Option<&[u8]>, desired: &FileTags, t: &TagWriteTarget) -> Vec<u8> {
+    let mut new_acid = acid.map(<[u8]>::to_vec).unwrap_or_else(|| {
+        let mut body = vec![0u8; 24];
+        body[0..4].copy_from_slice(&(if t.one_shot { 0x01u32 } else { 0 }).to_le_bytes());
+        body[6..8].copy_from_slice(&0x8000u16.to_le_bytes());
+        body[16..18].copy_from_slice(&4u16.to_le_bytes());
+        body[18..20].copy_from_slice(&4u16.to_le_bytes());
+        body
+    });
+    new_acid.resize(new_acid.len().max(24), 0);
+    if let Some(bpm) = desired.bpm {
+        new_acid[20..24].copy_from_slice(&(bpm as f32).to_le_bytes());
+        if le_u32(&new_acid, 12) == Some(0) {
+            if let Some(bars) = t.bars {
+                new_acid[12..16].copy_from_slice(&(bars as u32 * 4).to_le_bytes());
             }
-        });
+        }
+    }
+    if let Some(pc) = desired.key.as_deref().and_then(pitch_class) {
+        let flags = le_u32(&new_acid, 0).unwrap_or(0) | 0x02;
+        new_acid[0..4].copy_from_slice(&flags.to_le_bytes());
+        new_acid[4..6].copy_from_slice(&(48 + pc as u16).to_le_bytes());
+    }
+    new_acid
+}
 
-        let audio_key = parse_key_from_filename(&full_path_for_parse);
-        let audio_path = if dest_path.exists() { &dest_str } else { &src_str };
-        let sample_type = parse_sample_type(&full_path_for_parse, duration_ms, Some(audio_path));
-        let tags_vec = parse_tags_from_path(&full_path_for_parse, &filename);
-        let tags = if tags_vec.is_empty() {
-            None
-        } else {
-            Some(tags_vec.join(","))
+/// WAV LIST/INFO 청크 본문: 기존 항목은 유지하고 IGNR/IKEY만 교체
+fn build_wav_info(info_entries: &mut Vec<([u8; 4], Vec<u8>)>, desired: &FileTags) -> std::io::Result<Vec<u8>> {
+    for (id, value) in [(b"IGNR", &desired.genre), (b"IKEY", &desired.keywords)] {
+        let Some(value) = value else {
+            continue;
         };
-        let sample_genre = parse_genre_from_path(&full_path_for_parse).or_else(|| genre.clone());
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        match info_entries.iter_mut().find(|(i, _)| i == id) {
+            Some(entry) => entry.1 = data,
+            None => info_entries.push((*id, data)),
+        }
+    }
+    let mut new_info = b"INFO".to_vec();
+    for (id, data) in info_entries.iter() {
+        write_riff_chunk(&mut new_info, id, data)?;
+    }
+    Ok(new_info)
+}
 
-        // file_hash: dest_path 기반으로 생성
-        let mut hasher = DefaultHasher::new();
-        dest_str.hash(&mut hasher);
-        let file_hash = format!("ext-{:016x}", hasher.finish());
+/// MP3: ID3v2 TBPM/TKEY/TCON + TXXX:KEYWORDS (다른 프레임은 유지)
+fn write_id3_tags(path: &Path, desired: &FileTags, dry_run: bool) -> Result<Vec<TagWriteChange>, String> {
+    use id3::TagLike;
 
-        tx.execute(
-            "INSERT OR IGNORE INTO samples
-             (local_path, filename, audio_key, bpm, chord_type, duration,
-              file_hash, genre, sample_type, tags, pack_uuid, created_at)
-             VALUES (?1, ?2, ?3, ?4, NULL, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))",
-            params![
-                dest_str,
-                filename,
-                audio_key,
-                bpm,
-                duration_ms,
-                file_hash,
-                sample_genre,
-                sample_type,
-                tags,
-                pack_uuid
-            ],
-        )
-        .map_err(|e| e.to_string())?;
+    let existing = id3::no_tag_ok(id3::Tag::read_from_path(path)).map_err(|e| format!("ID3 태그 읽기 실패: {}", e))?;
+    let version = match existing.as_ref().map(|t| t.version()) {
+        Some(id3::Version::Id3v24) => id3::Version::Id3v24,
+        _ => id3::Version::Id3v23,
+    };
+    let mut tag = existing.unwrap_or_default();
+    let text = |id: &str| tag.get(id).and_then(|f| f.content().text()).map(str::to_string);
+    let current = FileTags {
+        bpm: text("TBPM").and_then(|b| b.trim().replace(',', ".").parse::<f64>().ok()),
+        key: text("TKEY"),
+        genre: tag.genre().map(str::to_string),
+        keywords: tag
+            .extended_texts()
+            .find(|t| t.description.eq_ignore_ascii_case(KEYWORDS_TAG))
+            .map(|t| t.value.clone()),
+    };
+    let changes = file_tag_changes(&current, desired);
+    if dry_run || changes.is_empty() {
+        return Ok(changes);
+    }
 
-        // 진행 상황 전송 (전역 인덱스 기준)
-        let global_i = global_offset + i;
-        if global_i % 5 == 0 || global_i + 1 == global_total {
-            let _ = app.emit(
-                "import-progress",
-                ImportProgress {
-                    current: global_i + 1,
-                    total: global_total,
-                    current_file: filename.clone(),
-                    current_pack: pack_index + 1,
-                    total_packs,
-                    current_pack_name: pack_name.to_string(),
-                },
-            );
+    for change in &changes {
+        match change.field.as_str() {
+            "bpm" => tag.set_text("TBPM", change.after.clone()),
+            "key" => tag.set_text("TKEY", change.after.clone()),
+            "genre" => tag.set_genre(change.after.clone()),
+            _ => {
+                tag.remove_extended_text(Some(KEYWORDS_TAG), None);
+                tag.add_frame(id3::frame::ExtendedText {
+                    description: KEYWORDS_TAG.to_string(),
+                    value: change.after.clone(),
+                });
+            }
         }
     }
-
-    Ok((copied, skipped))
+    replace_file_atomically(path, |out| {
+        let mut src = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        std::io::copy(&mut src, out).map_err(|e| e.to_string())?;
+        tag.write_to_file(out, version).map_err(|e| format!("ID3 태그 쓰기 실패: {}", e))
+    })?;
+    Ok(changes)
 }
 
-/// 외부 폴더에서 샘플팩 임포트
-/// selected_paths: 팩으로 임포트할 폴더 경로 목록
-/// replace_map: 폴더명 → 기존 팩 UUID (교체할 팩 매핑, 없으면 새로 추가)
-#[tauri::command]
-fn import_external_folder(
-    selected_paths: Vec<String>,
-    replace_map: std::collections::HashMap<String, String>,
-    app: tauri::AppHandle,
-    state: State<AppState>,
-) -> Result<ImportResult, String> {
-    if selected_paths.is_empty() {
-        return Err("임포트할 폴더를 선택해주세요".to_string());
+/// Vorbis 코멘트 본문 → (vendor, "NAME=value" 목록)
+/// OGG 패킷이면 앞의 "\x03vorbis"를 떼고 넘김 (끝의 framing 비트는 무시)
+fn parse_vorbis_comment(body: &[u8]) -> Option<(Vec<u8>, Vec<String>)> {
+    let vendor_len = le_u32(body, 0)? as usize;
+    let vendor = body.get(4..4 + vendor_len)?.to_vec();
+    let mut at = 4 + vendor_len;
+    let count = le_u32(body, at)?;
+    at += 4;
+    let mut comments = Vec::new();
+    for _ in 0..count {
+        let len = le_u32(body, at)? as usize;
+        comments.push(String::from_utf8_lossy(body.get(at + 4..at + 4 + len)?).to_string());
+        at += 4 + len;
     }
+    Some((vendor, comments))
+}
 
-    // 각 선택된 폴더의 오디오 파일 수집
-    let mut packs: Vec<(String, PathBuf, Vec<PathBuf>)> = Vec::new();
-    for path_str in &selected_paths {
-        let folder = PathBuf::from(path_str);
-        if !folder.is_dir() {
-            continue;
-        }
-        let name = folder
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("Unknown Pack")
-            .to_string();
-        let audio_files = collect_audio_files(&folder);
-        if !audio_files.is_empty() {
-            packs.push((name, folder, audio_files));
-        }
+fn build_vorbis_comment(vendor: &[u8], comments: &[String]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    body.extend_from_slice(vendor);
+    body.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for c in comments {
+        body.extend_from_slice(&(c.len() as u32).to_le_bytes());
+        body.extend_from_slice(c.as_bytes());
     }
+    body
+}
 
-    if packs.is_empty() {
-        return Err("선택된 폴더에서 오디오 파일을 찾을 수 없습니다".to_string());
+/// 필드별 Vorbis 코멘트 이름 (첫 번째로 기록, 나머지는 읽기/교체 시에만)
+fn vorbis_field_names(field: &str) -> &'static [&'static str] {
+    match field {
+        "bpm" => &["BPM", "TEMPO"],
+        "key" => &["INITIALKEY", "KEY"],
+        "genre" => &["GENRE"],
+        _ => &[KEYWORDS_TAG],
     }
+}
 
-    let total_packs = packs.len();
-    let global_total: usize = packs.iter().map(|(_, _, files)| files.len()).sum();
-    let slice_dir = get_slice_path()?;
-
-    let mut total_copied = 0usize;
+fn vorbis_current_tags(comments: &[String]) -> FileTags {
+    let get = |field: &str| {
+        comments.iter().find_map(|c| {
+            let (name, value) = c.split_once('=')?;
+            vorbis_field_names(field)
+                .iter()
+                .any(|n| n.eq_ignore_ascii_case(name))
+                .then(|| value.trim().to_string())
+        })
+    };
+    FileTags {
+        bpm: get("bpm").and_then(|b| b.replace(',', ".").parse::<f64>().ok()),
+        key: get("key"),
+        genre: get("genre"),
+        keywords: get("tags"),
+    }
+}
+
+/// 바뀐 필드의 기존 코멘트를 모두 지우고 새 값 하나로 교체
+fn apply_vorbis_changes(comments: &mut Vec<String>, changes: &[TagWriteChange]) {
+    for change in changes {
+        let names = vorbis_field_names(&change.field);
+        comments.retain(|c| {
+            let name = c.split_once('=').map_or(c.as_str(), |(n, _)| n);
+            !names.iter().any(|n| n.eq_ignore_ascii_case(name))
+        });
+        comments.push(format!("{}={}", names[0], change.after));
+    }
+}
+
+/// FLAC: VORBIS_COMMENT 메타데이터 블록 교체 (없으면 STREAMINFO 뒤에 추가)
+fn write_flac_tags(path: &Path, desired: &FileTags, dry_run: bool) -> Result<Vec<TagWriteChange>, String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut src = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let io = |e: std::io::Error| e.to_string();
+    // 일부 태거가 붙이는 앞쪽 ID3v2 태그는 그대로 보존
+    let mut prefix = vec![0u8; 10];
+    src.read_exact(&mut prefix).map_err(io)?;
+    if &prefix[0..3] == b"ID3" {
+        let size = syncsafe_u32(&prefix, 6).unwrap_or(0) as u64;
+        (&mut src).take(size).read_to_end(&mut prefix).map_err(io)?;
+    } else {
+        prefix.clear();
+        src.seek(SeekFrom::Start(0)).map_err(io)?;
+    }
+    let mut magic = [0u8; 4];
+    src.read_exact(&mut magic).map_err(io)?;
+    if &magic != b"fLaC" {
+        return Err("FLAC 파일이 아닙니다".to_string());
+    }
+
+    // (블록 타입, 본문)
+    let mut blocks: Vec<(u8, Vec<u8>)> = Vec::new();
+    loop {
+        let mut block_header = [0u8; 4];
+        src.read_exact(&mut block_header).map_err(io)?;
+        let len = (block_header[1] as usize) << 16 | (block_header[2] as usize) << 8 | block_header[3] as usize;
+        let mut body = vec![0u8; len];
+        src.read_exact(&mut body).map_err(io)?;
+        blocks.push((block_header[0] & 0x7f, body));
+        if block_header[0] & 0x80 != 0 {
+            break;
+        }
+    }
+    let audio_start = src.stream_position().map_err(io)?;
+
+    let comment_idx = blocks.iter().position(|(kind, _)| *kind == 4);
+    let (vendor, mut comments) = comment_idx
+        .and_then(|i| parse_vorbis_comment(&blocks[i].1))
+        .unwrap_or_else(|| (b"Slice".to_vec(), Vec::new()));
+    let changes = file_tag_changes(&vorbis_current_tags(&comments), desired);
+    if dry_run || changes.is_empty() {
+        return Ok(changes);
+    }
+
+    apply_vorbis_changes(&mut comments, &changes);
+    let body = build_vorbis_comment(&vendor, &comments);
+    if body.len() >= 1 << 24 {
+        return Err("Vorbis 코멘트가 너무 큽니다".to_string());
+    }
+    match comment_idx {
+        Some(i) => blocks[i].1 = body,
+        None => blocks.insert(1.min(blocks.len()), (4, body)),
+    }
+
+    replace_file_atomically(path, |out| {
+        let mut out = std::io::BufWriter::new(out);
+        out.write_all(&prefix).map_err(io)?;
+        out.write_all(b"fLaC").map_err(io)?;
+        for (i, (kind, body)) in blocks.iter().enumerate() {
+            let last = if i + 1 == blocks.len() { 0x80 } else { 0 };
+            let len = body.len() as u32;
+            out.write_all(&[kind | last, (len >> 16) as u8, (len >> 8) as u8, len as u8])
+                .map_err(io)?;
+            out.write_all(body).map_err(io)?;
+        }
+        src.seek(SeekFrom::Start(audio_start)).map_err(io)?;
+        std::io::copy(&mut src, &mut out).map_err(io)?;
+        out.flush().map_err(io)
+    })?;
+    Ok(changes)
+}
+
+/// OGG Vorbis: 첫 스트림의 코멘트 헤더 패킷 교체 (페이지는 ogg 크레이트가 다시 구성)
+fn write_ogg_tags(path: &Path, desired: &FileTags, dry_run: bool) -> Result<Vec<TagWriteChange>, String> {
+    const COMMENT_HEADER: &[u8] = b"\x03vorbis";
+
+    let open = || -> Result<ogg::PacketReader<std::io::BufReader<std::fs::File>>, String> {
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        Ok(ogg::PacketReader::new(std::io::BufReader::new(file)))
+    };
+    let mut reader = open()?;
+    let ident = reader
+        .read_packet()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "빈 OGG 파일입니다".to_string())?;
+    if !ident.data.starts_with(b"\x01vorbis") {
+        return Err("Vorbis 스트림이 아닙니다 (Opus/FLAC-in-OGG 미지원)".to_string());
+    }
+    let serial = ident.stream_serial();
+    let comment = reader
+        .read_packet()
+        .map_err(|e| e.to_string())?
+        .filter(|p| p.stream_serial() == serial && p.data.starts_with(COMMENT_HEADER))
+        .ok_or_else(|| "Vorbis 코멘트 헤더를 찾을 수 없습니다".to_string())?;
+    let (vendor, mut comments) = parse_vorbis_comment(&comment.data[COMMENT_HEADER.len()..])
+        .ok_or_else(|| "Vorbis 코멘트 헤더가 손상되었습니다".to_string())?;
+    let changes = file_tag_changes(&vorbis_current_tags(&comments), desired);
+    if dry_run || changes.is_empty() {
+        return Ok(changes);
+    }
+
+    apply_vorbis_changes(&mut comments, &changes);
+    let mut new_comment = COMMENT_HEADER.to_vec();
+    new_comment.extend(build_vorbis_comment(&vendor, &comments));
+    new_comment.push(1); // framing 비트
+
+    replace_file_atomically(path, |out| {
+        let mut reader = open()?;
+        let mut writer = ogg::PacketWriter::new(std::io::BufWriter::new(out));
+        let mut index = 0usize;
+        while let Some(packet) = reader.read_packet().map_err(|e| e.to_string())? {
+            let end = if packet.last_in_stream() {
+                ogg::PacketWriteEndInfo::EndStream
+            } else if packet.last_in_page() {
+                ogg::PacketWriteEndInfo::EndPage
+            } else {
+                ogg::PacketWriteEndInfo::NormalPacket
+            };
+            let (packet_serial, absgp) = (packet.stream_serial(), packet.absgp_page());
+            let data = if packet_serial == serial {
+                index += 1;
+                if index == 2 {
+                    new_comment.clone()
+                } else {
+                    packet.data
+                }
+            } else {
+                packet.data
+            };
+            writer
+                .write_packet(data.into_boxed_slice(), packet_serial, end, absgp)
+                .map_err(|e| e.to_string())?;
+        }
+        writer.into_inner().flush().map_err(|e| e.to_string())
+    })?;
+    Ok(changes)
+}
+
+/// 샘플의 BPM/키/장르/태그를 오디오 파일 자체에 기록 (사용자가 명시적으로 실행할 때만)
+/// WAV: RIFF INFO + ACID, MP3: ID3v2, FLAC/OGG: Vorbis 코멘트
+/// dry_run이면 파일은 그대로 두고 파일별로 바뀔 내용만 보고
+#[tauri::command]
+async fn write_tags_to_files(
+    sample_ids: Vec<i64>,
+    dry_run: bool,
+    app: tauri::AppHandle,
+) -> Result<Vec<TagWriteReport>, String> {
+    if sample_ids.is_empty() {
+        return Err("태그를 기록할 샘플이 없습니다".to_string());
+    }
+    tauri::async_runtime::spawn_blocking(move || write_tags_to_files_blocking(&sample_ids, dry_run, &app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn write_tags_to_files_blocking(
+    sample_ids: &[i64],
+    dry_run: bool,
+    app: &tauri::AppHandle,
+) -> Result<Vec<TagWriteReport>, String> {
+    let state = app.state::<AppState>();
+    // 파일 교체가 감시 이벤트로 자동 동기화를 부르지 않도록
+    let _busy = BusyGuard::new(&state.busy);
+
+    let targets = {
+        let db = state.db.lock().unwrap();
+        load_tag_write_targets(&db, sample_ids)?
+    }; // 파일 쓰는 동안 DB lock 해제
+
+    let total = targets.len();
+    let mut reports = Vec::with_capacity(total);
+    for (i, t) in targets.iter().enumerate() {
+        let _ = app.emit(
+            "tag-write-progress",
+            ScanProgress {
+                current: i + 1,
+                total,
+                current_file: t.filename.clone(),
+            },
+        );
+        let (status, changes, error) = match write_file_tags(t, dry_run) {
+            Ok(None) => ("unsupported", Vec::new(), None),
+            Ok(Some(changes)) if changes.is_empty() => ("unchanged", changes, None),
+            Ok(Some(changes)) if dry_run => ("pending", changes, None),
+            Ok(Some(changes)) => ("written", changes, None),
+            Err(e) => ("failed", Vec::new(), Some(e)),
+        };
+        reports.push(TagWriteReport {
+            sample_id: t.id,
+            filename: t.filename.clone(),
+            local_path: t.path.clone(),
+            status: status.to_string(),
+            changes,
+            error,
+        });
+    }
+
+    // 다시 쓴 파일 크기 갱신 (상태 점검/재연결이 크기로 파일을 찾음)
+    if !dry_run {
+        let db = state.db.lock().unwrap();
+        for r in reports.iter().filter(|r| r.status == "written") {
+            let _ = db.execute(
+                "UPDATE samples SET file_size = ?1 WHERE id = ?2",
+                params![file_size_of(&r.local_path), r.sample_id],
+            );
+        }
+    }
+    Ok(reports)
+}
+
+// ── Filename / path parsing helpers ─────────────────────────────────
+
+/// "87.5" / "87_5" 형태의 BPM 숫자 → 범위(60~190) 안이면 값
+fn parse_bpm_number(caps: &regex::Captures) -> Option<f64> {
+    let whole = caps.name("whole")?.as_str();
+    let bpm: f64 = match caps.name("frac") {
+        Some(frac) => format!("{}.{}", whole, frac.as_str()).parse().ok()?,
+        None => whole.parse().ok()?,
+    };
+    (60.0..=190.0).contains(&bpm).then(|| round_bpm(bpm))
+}
+
+fn parse_bpm_from_filename(filename: &str) -> Option<f64> {
+    // "120BPM", "120 BPM", "120bpm", "120_bpm", 소수점 "87.5bpm", "87_5 BPM" 등
+    let re = Regex::new(r"(?i)(?P<whole>\d{2,3})(?:[._](?P<frac>\d{1,2}))?\s*[_\-]?\s*bpm").unwrap();
+    if let Some(bpm) = re.captures(filename).and_then(|caps| parse_bpm_number(&caps)) {
+        return Some(bpm);
+    }
+    // "bpm120", "BPM_120", "BPM-120", "bpm87.5"
+    let re2 = Regex::new(r"(?i)bpm[\s_\-]*(?P<whole>\d{2,3})(?:\.(?P<frac>\d{1,2}))?").unwrap();
+    if let Some(bpm) = re2.captures(filename).and_then(|caps| parse_bpm_number(&caps)) {
+        return Some(bpm);
+    }
+    // "tempo120", "Tempo 120", "Tempo_120", "Tempo-120"
+    let re3 = Regex::new(r"(?i)tempo[\s_\-]*(?P<whole>\d{2,3})(?:\.(?P<frac>\d{1,2}))?").unwrap();
+    if let Some(bpm) = re3.captures(filename).and_then(|caps| parse_bpm_number(&caps)) {
+        return Some(bpm);
+    }
+    // "120 Tempo", "120_Tempo", "87_5 Tempo"
+    let re4 = Regex::new(r"(?i)(?P<whole>\d{2,3})(?:[._](?P<frac>\d{1,2}))?\s*[_\-]?\s*tempo").unwrap();
+    if let Some(bpm) = re4.captures(filename).and_then(|caps| parse_bpm_number(&caps)) {
+        return Some(bpm);
+    }
+    // 독립 숫자 패턴 (폴백): 구분자 사이 2~3자리 숫자를 BPM으로 추정
+    // bit, bar, k, hz, db, ch, st 등 비-BPM 접미사가 붙은 숫자는 제외
+    let re5 = Regex::new(r"(?:^|[^0-9a-zA-Z])(\d{2,3})(?:[^0-9a-zA-Z]|$)").unwrap();
+    for caps in re5.captures_iter(filename) {
+        if let Ok(num) = caps[1].parse::<i32>() {
+            if !(60..=190).contains(&num) {
+                continue;
+            }
+            // 숫자 뒤 텍스트 확인: 비-BPM 접미사 제외
+            let end_pos = caps.get(1).unwrap().end();
+            if end_pos < filename.len() {
+                let after = filename[end_pos..].to_lowercase();
+                if after.starts_with("bit")
+                    || after.starts_with("bar")
+                    || after.starts_with("hz")
+                    || after.starts_with("khz")
+                    || after.starts_with("db")
+                    || after.starts_with("ch")
+                    || after.starts_with("st")
+                    || after.starts_with("kbps")
+                {
+                    continue;
+                }
+            }
+            return Some(num as f64);
+        }
+    }
+    None
+}
+
+fn parse_key_from_filename(filename: &str) -> Option<String> {
+    // "Cmaj", "C#min", "Bbmajor", "A minor" 등
+    let re = Regex::new(r"(?i)\b([A-G][#b]?)\s*(maj(?:or)?|min(?:or)?)\b").unwrap();
+    if let Some(caps) = re.captures(filename) {
+        let note = caps[1].chars().next()?.to_uppercase().to_string()
+            + &caps[1][1..];
+        let quality = caps[2].to_lowercase();
+        let q = if quality.starts_with("min") {
+            "min"
+        } else {
+            "maj"
+        };
+        return Some(format!("{}{}", note, q));
+    }
+
+    // "Am", "F#m", "Bbm" (단독 m = minor)
+    let re2 = Regex::new(r"\b([A-G][#b]?)m\b").unwrap();
+    if let Some(caps) = re2.captures(filename) {
+        let note = caps[1].chars().next()?.to_uppercase().to_string()
+            + &caps[1][1..];
+        return Some(format!("{}min", note));
+    }
+
+    None
+}
+
+/// 뒤쪽 무음 여부를 검사 (one-shot 판별용)
+/// 뒤쪽 30%가 대부분 무음이면 one-shot (긴 디케이/리버브 꼬리)
+fn has_trailing_silence(samples: &[f32], sample_rate: u32) -> bool {
+    let chunk_size = (sample_rate as usize) / 10; // 100ms 단위
+    if chunk_size == 0 {
+        return false;
+    }
+    let total_chunks = samples.len() / chunk_size;
+    if total_chunks < 5 {
+        return false;
+    }
+
+    // 각 청크의 RMS 에너지 계산
+    let energies: Vec<f64> = (0..total_chunks)
+        .map(|i| {
+            let start = i * chunk_size;
+            let end = (start + chunk_size).min(samples.len());
+            let rms: f64 = samples[start..end]
+                .iter()
+                .map(|s| (*s as f64).powi(2))
+                .sum::<f64>()
+                / (end - start) as f64;
+            rms.sqrt()
+        })
+        .collect();
+
+    let peak_energy = energies.iter().cloned().fold(0.0f64, f64::max);
+    if peak_energy <= 0.0 {
+        return false;
+    }
+
+    // 앞쪽 50%에서 피크 에너지 위치 확인
+    let half = total_chunks / 2;
+    let front_peak = energies[..half].iter().cloned().fold(0.0f64, f64::max);
+    let back_peak = energies[half..].iter().cloned().fold(0.0f64, f64::max);
+
+    // 앞쪽 피크가 뒤쪽보다 확실히 크면 one-shot 가능성
+    if front_peak <= 0.0 {
+        return false;
+    }
+
+    // 뒤쪽 30% 무음 체크
+    let tail_start = (total_chunks as f64 * 0.7) as usize;
+    let silence_threshold = peak_energy * 0.03; // 피크의 3% 이하 = 무음
+
+    let silent_tail_chunks = energies[tail_start..]
+        .iter()
+        .filter(|&&e| e < silence_threshold)
+        .count();
+    let tail_chunk_count = total_chunks - tail_start;
+
+    // 뒤쪽 30%의 60% 이상이 무음이면 → one-shot
+    let silence_ratio = silent_tail_chunks as f64 / tail_chunk_count as f64;
+    if silence_ratio > 0.6 {
+        return true;
+    }
+
+    // 또는: 뒤쪽 피크가 앞쪽 피크의 10% 미만이면 → one-shot (에너지 감쇠)
+    if back_peak < front_peak * 0.1 {
+        return true;
+    }
+
+    false
+}
+
+/// 샘플 타입 판별 → (타입, 출처, 신뢰도)
+fn parse_sample_type(
+    filename: &str,
+    duration_ms: Option<i64>,
+    audio: Option<&AnalysisContext>,
+) -> (String, &'static str, f32) {
+    let lower = filename.to_lowercase();
+
+    // 1. 파일명 키워드 우선
+    if lower.contains("loop") || lower.contains("_lp") {
+        return ("loop".to_string(), SOURCE_FILENAME, CONFIDENCE_FILENAME);
+    }
+    if lower.contains("oneshot")
+        || lower.contains("one-shot")
+        || lower.contains("one shot")
+        || lower.contains("_hit")
+        || lower.contains(" hit")
+        || lower.contains("stab")
+        || lower.contains("impact")
+        || lower.contains("riser")
+        || lower.contains("downlifter")
+        || lower.contains("fx")
+        || lower.contains("sfx")
+        || lower.contains("transition")
+        || lower.contains("fill")
+    {
+        return ("oneshot".to_string(), SOURCE_FILENAME, CONFIDENCE_FILENAME);
+    }
+
+    // 2. 짧은 샘플은 one-shot
+    if let Some(d) = duration_ms {
+        if d < 1500 {
+            return ("oneshot".to_string(), SOURCE_AUDIO, 0.8);
+        }
+    }
+
+    // 3. 1.5초~20초 범위: 오디오 분석으로 뒤쪽 무음 체크
+    if let (Some(d), Some(audio)) = (duration_ms, audio) {
+        if d >= 1500 && d <= 20000 {
+            if audio.has_trailing_silence() {
+                return ("oneshot".to_string(), SOURCE_AUDIO, 0.7);
+            }
+        }
+    }
+
+    // 4. 20초 초과면 loop, 그 외 duration 기반
+    match duration_ms {
+        Some(d) if d > 20000 => ("loop".to_string(), SOURCE_AUDIO, 0.8),
+        Some(_) => ("loop".to_string(), SOURCE_AUDIO, 0.5),
+        None => ("oneshot".to_string(), SOURCE_FILENAME, 0.2),
+    }
+}
+
+/// 경로 + 파일명에서 태그 추출 (규칙 저장소 기준)
+/// 1) keyword/regex/segment 규칙을 순서대로 적용 → 2) tag 규칙으로 상위 카테고리 부여
+fn parse_tags_from_path(rules: &MetadataRuleSet, full_path: &str, filename: &str) -> Vec<String> {
+    let combined = format!("{} {}", full_path, filename).to_lowercase();
+    let segments = path_segments(full_path);
+    let mut tags: Vec<String> = Vec::new();
+
+    for rule in rules.for_target(RuleTarget::Tag) {
+        if !matches!(rule.matcher, CompiledMatcher::Tag(_))
+            && rule.matches(&combined, &segments)
+            && !tags.contains(&rule.value)
+        {
+            tags.push(rule.value.clone());
+        }
+    }
+
+    apply_category_rules(rules, &mut tags);
+    tags
+}
+
+/// 상위 카테고리 태그 (이미 붙은 태그 기준 tag 규칙)
+fn apply_category_rules(rules: &MetadataRuleSet, tags: &mut Vec<String>) {
+    for rule in rules.for_target(RuleTarget::Tag) {
+        if let CompiledMatcher::Tag(source) = &rule.matcher {
+            if tags.contains(source) && !tags.contains(&rule.value) {
+                tags.push(rule.value.clone());
+            }
+        }
+    }
+}
+
+/// 경로에서 장르 추출 — 처음 일치하는 규칙의 장르
+fn parse_genre_from_path(rules: &MetadataRuleSet, full_path: &str) -> Option<String> {
+    let lower = full_path.to_lowercase();
+    let segments = path_segments(full_path);
+    rules
+        .for_target(RuleTarget::Genre)
+        .find(|rule| rule.matches(&lower, &segments))
+        .map(|rule| rule.value.clone())
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => matches!(
+            ext.to_lowercase().as_str(),
+            "wav" | "mp3" | "flac" | "ogg" | "aiff" | "aif"
+        ),
+        None => false,
+    }
+}
+
+fn collect_audio_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                files.extend(collect_audio_files(&path));
+            } else if is_audio_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+fn generate_pack_uuid(folder_name: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    folder_name.hash(&mut hasher);
+    format!("ext-{:016x}", hasher.finish())
+}
+
+#[cfg(unix)]
+fn create_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(src, dest)
+}
+
+#[cfg(windows)]
+fn create_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(src, dest)
+}
+
+/// 저장 방식에 따라 원본 파일을 라이브러리에 배치하고 DB에 저장할 경로를 반환
+/// bool: 새로 배치했는지 여부 (이미 있거나 reference면 false)
+fn place_library_file(src: &Path, dest: &Path, mode: StorageMode) -> Result<(PathBuf, bool), String> {
+    if mode == StorageMode::Reference {
+        return Ok((src.to_path_buf(), false));
+    }
+    if dest.exists() {
+        return Ok((dest.to_path_buf(), false));
+    }
+    if let Some(parent) = dest.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    // 원본이 사라진 심볼릭 링크가 남아 있으면 제거 후 다시 생성
+    if dest.symlink_metadata().is_ok() {
+        let _ = std::fs::remove_file(dest);
+    }
+
+    let placed = match mode {
+        // 다른 파일시스템이면 하드링크가 실패하므로 복사로 폴백
+        StorageMode::Hardlink => std::fs::hard_link(src, dest)
+            .or_else(|_| std::fs::copy(src, dest).map(|_| ())),
+        // Windows는 개발자 모드/관리자 권한 없이 심볼릭 링크를 만들 수 없으므로 복사로 폴백
+        StorageMode::Symlink => create_symlink(src, dest).or_else(|e| {
+            if cfg!(windows) {
+                std::fs::copy(src, dest).map(|_| ())
+            } else {
+                Err(e)
+            }
+        }),
+        _ => std::fs::copy(src, dest).map(|_| ()),
+    };
+    placed.map_err(|e| format!("파일 배치 실패 ({}): {}", dest.display(), e))?;
+    Ok((dest.to_path_buf(), true))
+}
+
+/// 라이브러리 파일 삭제 — reference 팩의 파일이나 라이브러리 루트 밖 파일(루트 밖으로 재연결 등)은
+/// 사용자 원본이므로 지우지 않음 (하드링크/심볼릭 링크는 링크만 지워지고 원본은 유지됨)
+fn remove_library_file(root: &Path, path: &str, mode: StorageMode) {
+    if mode == StorageMode::Reference || !Path::new(path).starts_with(root) {
+        return;
+    }
+    remove_placed_file(Path::new(path));
+}
+
+/// 이번 실행에서 직접 만든 파일/링크 삭제
+fn remove_placed_file(p: &Path) {
+    if p.symlink_metadata().is_ok() {
+        let _ = std::fs::remove_file(p);
+    }
+}
+
+/// 이번 실행에서 새로 배치한 파일 — keep() 없이 drop되면(취소/오류) 삭제
+struct PlacedFiles {
+    files: Vec<(PathBuf, StorageMode)>,
+    kept: bool,
+}
+
+impl PlacedFiles {
+    fn new() -> Self {
+        PlacedFiles {
+            files: Vec::new(),
+            kept: false,
+        }
+    }
+
+    fn push(&mut self, path: PathBuf, mode: StorageMode) {
+        self.files.push((path, mode));
+    }
+
+    /// DB 커밋 후 호출 — 배치한 파일 유지
+    fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for PlacedFiles {
+    fn drop(&mut self) {
+        if self.kept {
+            return;
+        }
+        for (path, _) in &self.files {
+            remove_placed_file(path);
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::remove_dir(parent); // 비어있을 때만 성공
+            }
+        }
+    }
+}
+
+fn file_size_of(file_path: &str) -> Option<i64> {
+    std::fs::metadata(file_path).ok().map(|m| m.len() as i64)
+}
+
+fn compute_duration_ms(file_path: &str) -> Option<i64> {
+    let file = std::fs::File::open(file_path).ok()?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = Path::new(file_path).extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?;
+
+    let track = probed.format.default_track()?;
+    let tb = track.codec_params.time_base?;
+    let nf = track.codec_params.n_frames?;
+    let time = tb.calc_time(nf);
+    let ms = (time.seconds as f64 + time.frac) * 1000.0;
+    Some(ms as i64)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SampleTagInfo {
+    pub name: String,
+    pub source: Option<String>,
+    pub confidence: Option<f32>,
+}
+
+/// 샘플의 태그별 출처/신뢰도 (태그별 값이 없으면 샘플의 tags 출처)
+#[tauri::command]
+fn get_sample_tags(sample_id: i64, state: State<AppState>) -> Result<Vec<SampleTagInfo>, String> {
+    let db = state.db.lock().unwrap();
+    let mut stmt = db
+        .prepare(
+            "SELECT t.name, COALESCE(st.source, s.tags_source), COALESCE(st.confidence, s.tags_confidence)
+             FROM sample_tags st
+             JOIN tags t ON t.id = st.tag_id
+             JOIN samples s ON s.id = st.sample_id
+             WHERE st.sample_id = ?1
+             ORDER BY st.rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let tags = stmt
+        .query_map(params![sample_id], |row| {
+            Ok(SampleTagInfo {
+                name: row.get(0)?,
+                source: row.get(1)?,
+                confidence: row.get::<_, Option<f64>>(2)?.map(|c| c as f32),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tags)
+}
+
+// ── Tag classifier (Naive Bayes) ────────────────────────────────────
+
+/// 학습 예시가 이보다 적은 태그는 제안하지 않음
+const CLASSIFIER_MIN_EXAMPLES: u32 = 3;
+/// 이 신뢰도 이상인 태그만 제안
+const CLASSIFIER_MIN_CONFIDENCE: f32 = 0.75;
+
+/// 경로/파일명 → 분류기 토큰 (소문자, 글자/숫자 경계에서 분리, 중복 제거)
+/// 번호(01, 0042)·확장자는 버리고 808 같은 세 자리 숫자는 유지
+fn classifier_tokens(path: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut push = |token: &str| {
+        let keep = if token.chars().all(|c| c.is_ascii_digit()) {
+            token.len() == 3
+        } else {
+            token.chars().count() >= 2
+        };
+        if keep
+            && !matches!(token, "wav" | "mp3" | "aif" | "aiff" | "flac" | "ogg")
+            && !tokens.iter().any(|t| t == token)
+        {
+            tokens.push(token.to_string());
+        }
+    };
+    for word in path.to_lowercase().split(|c: char| !c.is_alphanumeric()) {
+        let mut start = 0;
+        let chars: Vec<(usize, char)> = word.char_indices().collect();
+        for w in chars.windows(2) {
+            let (_, a) = w[0];
+            let (i, b) = w[1];
+            if a.is_ascii_digit() != b.is_ascii_digit() {
+                push(&word[start..i]);
+                start = i;
+            }
+        }
+        if start < word.len() {
+            push(&word[start..]);
+        }
+    }
+    tokens
+}
+
+/// 태그별 이진 Naive Bayes (토큰 등장 여부 기준, 라플라스 보정)
+/// 사용자가 확정한 태그(tags_source = user)만 학습
+#[derive(Default)]
+struct TagClassifier {
+    docs: u32,
+    tag_docs: std::collections::HashMap<String, u32>,
+    token_docs: std::collections::HashMap<String, u32>,
+    tag_token_docs: std::collections::HashMap<String, std::collections::HashMap<String, u32>>,
+}
+
+impl TagClassifier {
+    fn train(examples: &[(Vec<String>, Vec<String>)]) -> Self {
+        let mut model = TagClassifier::default();
+        for (tokens, tags) in examples {
+            model.docs += 1;
+            for token in tokens {
+                *model.token_docs.entry(token.clone()).or_default() += 1;
+            }
+            for tag in tags {
+                *model.tag_docs.entry(tag.clone()).or_default() += 1;
+                let counts = model.tag_token_docs.entry(tag.clone()).or_default();
+                for token in tokens {
+                    *counts.entry(token.clone()).or_default() += 1;
+                }
+            }
+        }
+        model
+    }
+
+    /// 저장된 모델 (행 형식: ('', '') 전체 문서 수, (tag, '') 태그 문서 수,
+    /// ('', token) 토큰 문서 수, (tag, token) 태그+토큰 문서 수)
+    fn load(conn: &Connection) -> Self {
+        let mut model = TagClassifier::default();
+        let Ok(mut stmt) = conn.prepare("SELECT tag, token, count FROM tag_classifier") else {
+            return model;
+        };
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, u32>(2)?))
+        });
+        let Ok(rows) = rows else { return model };
+        for (tag, token, count) in rows.filter_map(|r| r.ok()) {
+            match (tag.is_empty(), token.is_empty()) {
+                (true, true) => model.docs = count,
+                (false, true) => {
+                    model.tag_docs.insert(tag, count);
+                }
+                (true, false) => {
+                    model.token_docs.insert(token, count);
+                }
+                (false, false) => {
+                    model.tag_token_docs.entry(tag).or_default().insert(token, count);
+                }
+            }
+        }
+        model
+    }
+
+    fn store(&self, conn: &Connection) -> Result<(), String> {
+        conn.execute("DELETE FROM tag_classifier", [])
+            .map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare_cached("INSERT INTO tag_classifier (tag, token, count) VALUES (?1, ?2, ?3)")
+            .map_err(|e| e.to_string())?;
+        let mut insert = |tag: &str, token: &str, count: u32| {
+            stmt.execute(params![tag, token, count])
+                .map(|_| ())
+                .map_err(|e| format!("분류기 저장 실패: {}", e))
+        };
+        insert("", "", self.docs)?;
+        for (tag, count) in &self.tag_docs {
+            insert(tag, "", *count)?;
+        }
+        for (token, count) in &self.token_docs {
+            insert("", token, *count)?;
+        }
+        for (tag, counts) in &self.tag_token_docs {
+            for (token, count) in counts {
+                insert(tag, token, *count)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 토큰 → (태그, 신뢰도) 제안 (existing에 이미 있는 태그 제외, 신뢰도 높은 순)
+    fn suggest(&self, tokens: &[String], existing: &[String]) -> Vec<(String, f32)> {
+        let mut suggestions: Vec<(String, f32)> = Vec::new();
+        for (tag, &n_tag) in &self.tag_docs {
+            if n_tag < CLASSIFIER_MIN_EXAMPLES || existing.contains(tag) {
+                continue;
+            }
+            let n_other = self.docs.saturating_sub(n_tag);
+            let tag_tokens = self.tag_token_docs.get(tag);
+            let mut log_odds = ((n_tag + 1) as f64 / (n_other + 1) as f64).ln();
+            let mut evidence = false;
+            for token in tokens {
+                // 학습 때 보지 못한 토큰은 무시
+                let Some(&df) = self.token_docs.get(token) else { continue };
+                let df_tag = tag_tokens.and_then(|c| c.get(token)).copied().unwrap_or(0);
+                let df_other = df.saturating_sub(df_tag);
+                evidence |= df_tag > 0;
+                log_odds += ((df_tag + 1) as f64 / (n_tag + 2) as f64).ln()
+                    - ((df_other + 1) as f64 / (n_other + 2) as f64).ln();
+            }
+            // 사전 확률만으로는 제안하지 않음
+            if !evidence {
+                continue;
+            }
+            let confidence = (1.0 / (1.0 + (-log_odds).exp())) as f32;
+            if confidence >= CLASSIFIER_MIN_CONFIDENCE {
+                suggestions.push((tag.clone(), confidence));
+            }
+        }
+        suggestions.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        suggestions
+    }
+}
+
+/// 사용자가 확정한 태그로 분류기를 다시 학습해 저장. 학습한 샘플 수 반환
+fn train_tag_classifier(conn: &Connection) -> Result<usize, String> {
+    let root = get_library_root(conn);
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.filename, s.local_path, p.name, s.tags
+             FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE s.tags_source = ?1 AND s.tags IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+    let examples: Vec<(Vec<String>, Vec<String>)> = stmt
+        .query_map(params![SOURCE_USER], |row| {
+            let filename: String = row.get(0)?;
+            let path = resolve_library_path(&root, &row.get::<_, String>(1)?);
+            let pack_name: Option<String> = row.get(2)?;
+            let tags: Option<String> = row.get(3)?;
+            let parse_path = reanalysis_parse_path(pack_name.as_deref(), &path, &filename);
+            Ok((classifier_tokens(&parse_path), split_tags(tags.as_deref())))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|(_, tags)| !tags.is_empty())
+        .collect();
+    TagClassifier::train(&examples).store(conn)?;
+    Ok(examples.len())
+}
+
+/// 분류기 수동 재학습. 학습한 샘플 수 반환
+#[tauri::command]
+fn retrain_tag_classifier(state: State<AppState>) -> Result<usize, String> {
+    let mut db = state.db.lock().unwrap();
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    let count = train_tag_classifier(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// 태그 교정 후 재학습까지 기다리는 시간 (연속 편집은 마지막 편집 뒤 한 번만 학습)
+const CLASSIFIER_RETRAIN_DEBOUNCE: Duration = Duration::from_millis(3000);
+
+/// 분류기 재학습 예약 — 디바운스 후 백그라운드에서 별도 연결의 단일 트랜잭션으로 학습
+/// 실패하면 `classifier-retrain-failed` 이벤트 발행
+fn schedule_classifier_retrain(app: &tauri::AppHandle) {
+    let generation = app.state::<AppState>().classifier_retrain.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(CLASSIFIER_RETRAIN_DEBOUNCE);
+        // 그사이 새 요청이 있으면 그 요청이 학습
+        if app.state::<AppState>().classifier_retrain.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let trained = open_job_db().and_then(|mut db| {
+            let tx = db.transaction().map_err(|e| e.to_string())?;
+            train_tag_classifier(&tx)?;
+            tx.commit().map_err(|e| e.to_string())
+        });
+        if let Err(e) = trained {
+            let _ = app.emit("classifier-retrain-failed", format!("태그 분류기 학습 실패: {}", e));
+        }
+    });
+}
+
+// ── Tagging / genre rules ───────────────────────────────────────────
+
+/// 기본 태그 규칙: 키워드 → 태그
+const DEFAULT_TAG_KEYWORDS: &[(&str, &str)] = &[
+    ("kick", "kick"),
+    ("snare", "snare"),
+    ("hihat", "hihat"),
+    ("hi-hat", "hihat"),
+    ("hi hat", "hihat"),
+    ("hh_", "hihat"),
+    ("_hh", "hihat"),
+    ("clap", "clap"),
+    ("cymbal", "cymbal"),
+    ("crash", "crash"),
+    ("ride", "ride"),
+    ("open hat", "open hat"),
+    ("closed hat", "closed hat"),
+    ("bass", "bass"),
+    ("sub", "sub bass"),
+    ("lead", "lead"),
+    ("pad", "pad"),
+    ("pluck", "pluck"),
+    ("chord", "chord"),
+    ("arp", "arp"),
+    ("vocal", "vocal"),
+    ("vox", "vocal"),
+    ("voice", "vocal"),
+    ("fx", "fx"),
+    ("sfx", "fx"),
+    ("riser", "riser"),
+    ("impact", "impact"),
+    ("transition", "transition"),
+    ("sweep", "sweep"),
+    ("piano", "piano"),
+    ("keys", "keys"),
+    ("guitar", "guitar"),
+    ("strings", "strings"),
+    ("brass", "brass"),
+    ("synth", "synth"),
+    ("organ", "organ"),
+    ("flute", "flute"),
+    ("bell", "bell"),
+    ("perc", "percussion"),
+    ("percussion", "percussion"),
+    ("tom", "tom"),
+    ("shaker", "shaker"),
+    ("tambourine", "tambourine"),
+    ("rim", "rimshot"),
+    ("808", "808"),
+    ("top", "top loop"),
+    ("fill", "fill"),
+    ("break", "break"),
+    ("groove", "groove"),
+    ("melody", "melody"),
+    ("melodic", "melodic"),
+    ("drum", "drums"),
+    ("drums", "drums"),
+];
+
+/// 기본 상위 카테고리 규칙: 하위 태그 중 하나라도 있으면 카테고리 태그 부여
+const DEFAULT_CATEGORY_TAGS: &[(&str, &[&str])] = &[
+    // drums: 타악기 계열
+    ("drums", &[
+        "kick", "snare", "hihat", "clap", "cymbal", "crash", "ride",
+        "open hat", "closed hat", "percussion", "tom", "shaker",
+        "tambourine", "rimshot", "808", "top loop", "fill",
+    ]),
+    // melodic: 음정이 있는 악기 계열
+    ("melodic", &[
+        "piano", "keys", "guitar", "strings", "brass", "synth", "organ",
+        "flute", "bell", "lead", "pad", "pluck", "chord", "arp", "melody",
+    ]),
+    // fx: 이펙트/트랜지션 계열
+    ("fx", &["fx", "riser", "impact", "transition", "sweep"]),
+    // bass: 저음부 계열
+    ("bass", &["bass", "sub bass", "808"]),
+];
+
+/// 기본 장르 규칙: 키워드 → 장르 (앞쪽 우선)
+const DEFAULT_GENRE_KEYWORDS: &[(&[&str], &str)] = &[
+    (&["hip hop", "hiphop", "hip-hop", "boom bap", "boom-bap"], "Hip Hop"),
+    (&["trap"], "Trap"),
+    (&["drill"], "Drill"),
+    (&["house", "deep house", "tech house"], "House"),
+    (&["techno"], "Techno"),
+    (&["edm", "electro"], "Electronic"),
+    (&["dubstep", "dub step"], "Dubstep"),
+    (&["dnb", "drum and bass", "drum & bass", "drum n bass"], "DnB"),
+    (&["pop"], "Pop"),
+    (&["rnb", "r&b", "r'n'b"], "R&B"),
+    (&["lo-fi", "lofi", "lo fi"], "Lo-Fi"),
+    (&["ambient"], "Ambient"),
+    (&["jazz"], "Jazz"),
+    (&["soul"], "Soul"),
+    (&["funk"], "Funk"),
+    (&["reggae", "dancehall", "reggaeton"], "Reggae"),
+    (&["rock", "indie"], "Rock"),
+    (&["latin", "salsa", "bossa"], "Latin"),
+    (&["afro", "afrobeat"], "Afrobeat"),
+    (&["cinematic", "film", "orchestral"], "Cinematic"),
+    (&["future bass", "future-bass"], "Future Bass"),
+    (&["trance"], "Trance"),
+    (&["garage", "uk garage"], "Garage"),
+];
+
+/// 규칙 파일(import/export) 형식 버전
+const METADATA_RULES_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleTarget {
+    Tag,
+    Genre,
+}
+
+impl RuleTarget {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RuleTarget::Tag => "tag",
+            RuleTarget::Genre => "genre",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "tag" => Some(RuleTarget::Tag),
+            "genre" => Some(RuleTarget::Genre),
+            _ => None,
+        }
+    }
+}
+
+/// keyword: 경로+파일명에 포함 (대소문자 무시)
+/// regex: 경로+파일명에 정규식 일치 (대소문자 무시)
+/// segment: 경로 구간(폴더 이름)이 정확히 일치
+/// tag: 이미 붙은 태그 → 상위 태그 (태그 규칙 전용)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleMatcher {
+    Keyword,
+    Regex,
+    Segment,
+    Tag,
+}
+
+impl RuleMatcher {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RuleMatcher::Keyword => "keyword",
+            RuleMatcher::Regex => "regex",
+            RuleMatcher::Segment => "segment",
+            RuleMatcher::Tag => "tag",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "keyword" => Some(RuleMatcher::Keyword),
+            "regex" => Some(RuleMatcher::Regex),
+            "segment" => Some(RuleMatcher::Segment),
+            "tag" => Some(RuleMatcher::Tag),
+            _ => None,
+        }
+    }
+}
+
+/// 사용자 편집 가능한 태그/장르 규칙 (목록 순서 = 적용 우선순위)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetadataRule {
+    #[serde(default)]
+    pub id: Option<i64>,
+    pub target: RuleTarget,
+    pub matcher: RuleMatcher,
+    pub pattern: String,
+    /// 부여할 태그 또는 장르
+    pub value: String,
+    #[serde(default = "rule_enabled_default")]
+    pub enabled: bool,
+}
+
+fn rule_enabled_default() -> bool {
+    true
+}
+
+/// 규칙 내보내기/가져오기 파일
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataRuleFile {
+    pub version: u32,
+    pub rules: Vec<MetadataRule>,
+}
+
+enum CompiledMatcher {
+    Keyword(String),
+    Regex(Regex),
+    Segment(String),
+    Tag(String),
+}
+
+struct CompiledRule {
+    target: RuleTarget,
+    matcher: CompiledMatcher,
+    value: String,
+}
+
+impl CompiledRule {
+    /// text: 소문자 경로(+파일명), segments: 소문자 경로 구간
+    fn matches(&self, text: &str, segments: &[String]) -> bool {
+        match &self.matcher {
+            CompiledMatcher::Keyword(kw) => text.contains(kw.as_str()),
+            CompiledMatcher::Regex(re) => re.is_match(text),
+            CompiledMatcher::Segment(seg) => segments.iter().any(|s| s == seg),
+            CompiledMatcher::Tag(_) => false,
+        }
+    }
+}
+
+/// 적용 가능한 형태로 컴파일된 규칙 집합 (워커 풀에서 공유)
+struct MetadataRuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+impl MetadataRuleSet {
+    /// 기본 규칙 (기존 내장 키워드 목록과 같은 결과)
+    fn default_rules() -> Vec<MetadataRule> {
+        let rule = |target, matcher, pattern: &str, value: &str| MetadataRule {
+            id: None,
+            target,
+            matcher,
+            pattern: pattern.to_string(),
+            value: value.to_string(),
+            enabled: true,
+        };
+        let mut rules: Vec<MetadataRule> = DEFAULT_TAG_KEYWORDS
+            .iter()
+            .map(|(kw, tag)| rule(RuleTarget::Tag, RuleMatcher::Keyword, kw, tag))
+            .collect();
+        for (category, members) in DEFAULT_CATEGORY_TAGS {
+            for member in members.iter().filter(|m| *m != category) {
+                rules.push(rule(RuleTarget::Tag, RuleMatcher::Tag, member, category));
+            }
+        }
+        for (keywords, genre) in DEFAULT_GENRE_KEYWORDS {
+            for kw in keywords.iter() {
+                rules.push(rule(RuleTarget::Genre, RuleMatcher::Keyword, kw, genre));
+            }
+        }
+        rules
+    }
+
+    /// 규칙 검증 + 컴파일 (비활성 규칙도 검증, 적용은 활성 규칙만)
+    fn compile(rules: &[MetadataRule]) -> Result<Self, String> {
+        let mut compiled = Vec::new();
+        for (i, rule) in rules.iter().enumerate() {
+            let pattern = rule.pattern.trim();
+            let value = rule.value.trim();
+            if pattern.is_empty() || value.is_empty() {
+                return Err(format!("규칙 {}: 패턴과 값을 입력하세요", i + 1));
+            }
+            if rule.target == RuleTarget::Tag && value.contains(',') {
+                return Err(format!("규칙 {}: 태그 이름에 쉼표(,)를 쓸 수 없습니다", i + 1));
+            }
+            let matcher = match rule.matcher {
+                RuleMatcher::Keyword => CompiledMatcher::Keyword(pattern.to_lowercase()),
+                RuleMatcher::Segment => CompiledMatcher::Segment(pattern.to_lowercase()),
+                RuleMatcher::Regex => CompiledMatcher::Regex(
+                    regex::RegexBuilder::new(pattern)
+                        .case_insensitive(true)
+                        .build()
+                        .map_err(|e| format!("규칙 {}: 잘못된 정규식: {}", i + 1, e))?,
+                ),
+                RuleMatcher::Tag => {
+                    if rule.target != RuleTarget::Tag {
+                        return Err(format!("규칙 {}: tag 조건은 태그 규칙에만 쓸 수 있습니다", i + 1));
+                    }
+                    CompiledMatcher::Tag(pattern.to_string())
+                }
+            };
+            if rule.enabled {
+                compiled.push(CompiledRule {
+                    target: rule.target,
+                    matcher,
+                    value: value.to_string(),
+                });
+            }
+        }
+        Ok(MetadataRuleSet { rules: compiled })
+    }
+
+    /// DB에 저장된 규칙 (잘못된 규칙은 건너뜀)
+    fn load(conn: &Connection) -> Self {
+        let rules: Vec<MetadataRule> = load_metadata_rules(conn)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|r| MetadataRuleSet::compile(std::slice::from_ref(r)).is_ok())
+            .collect();
+        MetadataRuleSet::compile(&rules).unwrap_or(MetadataRuleSet { rules: Vec::new() })
+    }
+
+    fn for_target(&self, target: RuleTarget) -> impl Iterator<Item = &CompiledRule> {
+        self.rules.iter().filter(move |r| r.target == target)
+    }
+}
+
+/// 소문자 경로 구간 (폴더/파일 이름)
+fn path_segments(path: &str) -> Vec<String> {
+    path.split(['/', '\\'])
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_lowercase())
+        .collect()
+}
+
+/// 규칙 테이블 생성 + 처음 한 번 기본 규칙 채우기
+/// (사용자가 규칙을 모두 지워도 다시 채우지 않도록 설정에 기록)
+fn init_metadata_rules(db: &Connection) -> Result<(), String> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS metadata_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            position INTEGER NOT NULL,
+            target TEXT NOT NULL,
+            matcher TEXT NOT NULL,
+            pattern TEXT NOT NULL,
+            value TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1
+        );",
+    )
+    .map_err(|e| format!("규칙 테이블 초기화 실패: {}", e))?;
+    if get_setting(db, SETTING_METADATA_RULES_SEEDED).is_none() {
+        store_metadata_rules(db, &MetadataRuleSet::default_rules())?;
+        set_setting(db, SETTING_METADATA_RULES_SEEDED, Some("1"))?;
+    }
+    Ok(())
+}
+
+fn load_metadata_rules(conn: &Connection) -> Result<Vec<MetadataRule>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, target, matcher, pattern, value, enabled
+             FROM metadata_rules ORDER BY position, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rules = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, bool>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|(id, target, matcher, pattern, value, enabled)| {
+            Some(MetadataRule {
+                id: Some(id),
+                target: RuleTarget::parse(&target)?,
+                matcher: RuleMatcher::parse(&matcher)?,
+                pattern,
+                value,
+                enabled,
+            })
+        })
+        .collect();
+    Ok(rules)
+}
+
+/// 규칙 목록 전체 교체 (목록 순서가 우선순위)
+fn store_metadata_rules(conn: &Connection, rules: &[MetadataRule]) -> Result<(), String> {
+    conn.execute("DELETE FROM metadata_rules", [])
+        .map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare_cached(
+            "INSERT INTO metadata_rules (position, target, matcher, pattern, value, enabled)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .map_err(|e| e.to_string())?;
+    for (i, rule) in rules.iter().enumerate() {
+        stmt.execute(params![
+            i as i64,
+            rule.target.as_str(),
+            rule.matcher.as_str(),
+            rule.pattern.trim(),
+            rule.value.trim(),
+            rule.enabled
+        ])
+        .map_err(|e| format!("규칙 저장 실패: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 검증 후 트랜잭션으로 교체 저장, 저장된 목록 반환
+fn replace_metadata_rules(
+    db: &mut Connection,
+    rules: &[MetadataRule],
+) -> Result<Vec<MetadataRule>, String> {
+    MetadataRuleSet::compile(rules)?;
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    store_metadata_rules(&tx, rules)?;
+    let saved = load_metadata_rules(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(saved)
+}
+
+#[tauri::command]
+fn get_metadata_rules(state: State<AppState>) -> Result<Vec<MetadataRule>, String> {
+    let db = state.db.lock().unwrap();
+    load_metadata_rules(&db)
+}
+
+/// 규칙 목록 저장 (기존 샘플에는 reanalyze_samples로 적용)
+#[tauri::command]
+fn save_metadata_rules(
+    rules: Vec<MetadataRule>,
+    state: State<AppState>,
+) -> Result<Vec<MetadataRule>, String> {
+    let mut db = state.db.lock().unwrap();
+    replace_metadata_rules(&mut db, &rules)
+}
+
+#[tauri::command]
+fn reset_metadata_rules(state: State<AppState>) -> Result<Vec<MetadataRule>, String> {
+    let mut db = state.db.lock().unwrap();
+    replace_metadata_rules(&mut db, &MetadataRuleSet::default_rules())
+}
+
+#[tauri::command]
+fn export_metadata_rules(dest_path: String, state: State<AppState>) -> Result<usize, String> {
+    let rules: Vec<MetadataRule> = {
+        let db = state.db.lock().unwrap();
+        load_metadata_rules(&db)?
+    }
+    .into_iter()
+    .map(|r| MetadataRule { id: None, ..r })
+    .collect();
+    let count = rules.len();
+    let json = serde_json::to_string_pretty(&MetadataRuleFile {
+        version: METADATA_RULES_VERSION,
+        rules,
+    })
+    .map_err(|e| e.to_string())?;
+    std::fs::write(&dest_path, json).map_err(|e| format!("규칙 파일 저장 실패: {}", e))?;
+    Ok(count)
+}
+
+/// 규칙 파일 가져오기 — replace면 교체, 아니면 기존 목록 뒤에 추가
+#[tauri::command]
+fn import_metadata_rules(
+    src_path: String,
+    replace: bool,
+    state: State<AppState>,
+) -> Result<Vec<MetadataRule>, String> {
+    let json =
+        std::fs::read_to_string(&src_path).map_err(|e| format!("규칙 파일 읽기 실패: {}", e))?;
+    let file: MetadataRuleFile =
+        serde_json::from_str(&json).map_err(|e| format!("규칙 파일 해석 실패: {}", e))?;
+    if file.version > METADATA_RULES_VERSION {
+        return Err(format!(
+            "지원하지 않는 규칙 파일 버전입니다: {} (최대 {})",
+            file.version, METADATA_RULES_VERSION
+        ));
+    }
+
+    let mut db = state.db.lock().unwrap();
+    let mut rules = if replace {
+        Vec::new()
+    } else {
+        load_metadata_rules(&db)?
+    };
+    rules.extend(file.rules.into_iter().map(|r| MetadataRule { id: None, ..r }));
+    replace_metadata_rules(&mut db, &rules)
+}
+
+/// 규칙 변경 미리보기: 제안된 규칙으로 태그/장르를 다시 분석했을 때 바뀌는 샘플
+/// 현재 규칙과 결과가 같은 샘플은 제외 (규칙 변경의 영향만 표시)
+/// applied=false는 사용자 편집/Splice 값이라 재분석해도 보존되는 변경
+#[tauri::command]
+fn preview_metadata_rules(
+    rules: Vec<MetadataRule>,
+    pack_uuid: Option<String>,
+    state: State<AppState>,
+) -> Result<Vec<ReanalyzeDiff>, String> {
+    let proposed = MetadataRuleSet::compile(&rules)?;
+    let (targets, current) = {
+        let db = state.db.lock().unwrap();
+        (
+            load_reanalyze_targets(&db, None, pack_uuid.as_deref())?,
+            MetadataRuleSet::load(&db),
+        )
+    };
+
+    let mut diffs = Vec::new();
+    for t in &targets {
+        // 태그/장르 분석기는 디코딩하지 않음
+        let audio = AnalysisContext::new(&t.path);
+        let affected: Vec<MetaField> = [MetaField::Genre, MetaField::Tags]
+            .into_iter()
+            .filter(|&field| {
+                let result = |rules| run_analyzer(field, t, &audio, rules).map(|(v, ..)| v.display());
+                result(&current) != result(&proposed)
+            })
+            .collect();
+        if affected.is_empty() {
+            continue;
+        }
+        let (changes, _) = analyze_changes(t, &affected, &audio, &proposed);
+        if !changes.is_empty() {
+            diffs.push(ReanalyzeDiff {
+                sample_id: t.id,
+                filename: t.filename.clone(),
+                changes,
+            });
+        }
+    }
+    Ok(diffs)
+}
+
+// ── Commands ────────────────────────────────────────────────────────
+
+#[tauri::command]
+fn check_library_status(state: State<AppState>) -> Result<LibraryStatus, String> {
+    let db = state.db.lock().unwrap();
+    let sample_count: i64 = db
+        .query_row("SELECT COUNT(*) FROM samples", [], |row| row.get(0))
+        .unwrap_or(0);
+    let pack_count: i64 = db
+        .query_row(
+            "SELECT COUNT(DISTINCT pack_uuid) FROM samples WHERE pack_uuid IS NOT NULL",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+    let splice_available = resolve_splice_dbs(&db).is_ok();
+
+    Ok(LibraryStatus {
+        has_data: sample_count > 0,
+        pack_count: pack_count as usize,
+        sample_count: sample_count as usize,
+        splice_available,
+    })
+}
+
+/// Splice DB(여러 계정 가능) → Slice DB 증분 동기화 (file_hash 기준 병합)
+/// - 새 샘플은 추가, 변경된 샘플은 갱신, remove_missing이면 Splice에서 사라진 샘플 제거
+///   (이번에 동기화한 sounds.db에서 가져온 샘플만 — 선택하지 않은 계정/드라이브의 샘플은 유지)
+/// - 사용자가 편집한 필드(출처 user)와 수정한 팩(user_edited)의 메타데이터는 덮어쓰지 않음
+/// - 기존 행을 UPDATE하므로 sample id와 playlist_samples 연결이 유지됨
+/// - 취소되면 전체 롤백 + 이번에 배치한 파일 삭제
+/// - 파일 배치를 먼저 끝낸 뒤 DB 기록만 트랜잭션으로 묶음 (파일 I/O 동안 쓰기 잠금을 잡지 않음)
+/// - progress_event: 진행 이벤트 이름 (수동 임포트와 자동 동기화의 진행 UI를 구분)
+fn sync_splice_dbs(
+    splice_db_paths: &[PathBuf],
+    db: &mut Connection,
+    job: &JobHandle,
+    remove_missing: bool,
+    storage_mode: StorageMode,
+    progress_event: &str,
+) -> Result<(ImportResult, Vec<i64>), String> {
+    struct SplicePack {
+        uuid: String,
+        name: Option<String>,
+        description: Option<String>,
+        cover_url: Option<String>,
+        genre: Option<String>,
+        permalink: Option<String>,
+    }
+
+    struct SpliceRow {
+        local_path: String,
+        filename: String,
+        audio_key: Option<String>,
+        bpm: Option<f64>,
+        chord_type: Option<String>,
+        duration: Option<i64>,
+        file_hash: String,
+        genre: Option<String>,
+        sample_type: Option<String>,
+        tags: Option<String>,
+        pack_uuid: Option<String>,
+        splice_db: String,
+    }
+
+    let mut splice_packs: Vec<SplicePack> = Vec::new();
+    let mut splice_samples: Vec<SpliceRow> = Vec::new();
+
+    for splice_db_path in splice_db_paths {
+        // 1. Open Splice DB (read-only)
+        let splice_db =
+            Connection::open_with_flags(splice_db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|e| format!("Splice DB 열기 실패: {}", e))?;
+
+        // 2. Read all packs from Splice
+        {
+            let mut stmt = splice_db
+                .prepare("SELECT uuid, name, description, cover_url, genre, permalink FROM packs")
+                .map_err(|e| e.to_string())?;
+            let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+            while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+                splice_packs.push(SplicePack {
+                    uuid: row.get::<_, String>(0).unwrap_or_default(),
+                    name: row.get::<_, Option<String>>(1).unwrap_or(None),
+                    description: row.get::<_, Option<String>>(2).unwrap_or(None),
+                    cover_url: row.get::<_, Option<String>>(3).unwrap_or(None),
+                    genre: row.get::<_, Option<String>>(4).unwrap_or(None),
+                    permalink: row.get::<_, Option<String>>(5).unwrap_or(None),
+                });
+            }
+        }
+
+        // 3. Read all samples from Splice
+        {
+            let mut stmt = splice_db
+                .prepare(
+                    "SELECT local_path, filename, audio_key, bpm, chord_type, duration,
+                            file_hash, genre, sample_type, tags, pack_uuid
+                     FROM samples",
+                )
+                .map_err(|e| e.to_string())?;
+            let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+            while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+                splice_samples.push(SpliceRow {
+                    local_path: row.get::<_, String>(0).unwrap_or_default(),
+                    filename: row.get::<_, String>(1).unwrap_or_default(),
+                    audio_key: row.get::<_, Option<String>>(2).unwrap_or(None),
+                    bpm: row.get::<_, Option<f64>>(3).unwrap_or(None),
+                    chord_type: row.get::<_, Option<String>>(4).unwrap_or(None),
+                    duration: row.get::<_, Option<i64>>(5).unwrap_or(None),
+                    file_hash: row.get::<_, String>(6).unwrap_or_default(),
+                    genre: row.get::<_, Option<String>>(7).unwrap_or(None),
+                    sample_type: row.get::<_, Option<String>>(8).unwrap_or(None),
+                    tags: row.get::<_, Option<String>>(9).unwrap_or(None),
+                    pack_uuid: row.get::<_, Option<String>>(10).unwrap_or(None),
+                    splice_db: splice_db_path.to_string_lossy().to_string(),
+                });
+            }
+        }
+        // Done with this Splice DB (drop)
+    }
+
+    // 4. Place audio files — 트랜잭션 밖에서 실행
+    // 팩별 저장 방식 — 기존 팩은 처음 임포트할 때의 방식을 유지 (새 팩은 storage_mode)
+    let pack_modes: std::collections::HashMap<String, StorageMode> = {
+        let mut stmt = db
+            .prepare("SELECT uuid, storage_mode FROM packs")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    StorageMode::parse(row.get::<_, Option<String>>(1)?.as_deref()),
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    let total = splice_samples.len();
+    let splice_sounds_dir = get_splice_sounds_dir().unwrap_or_else(|_| {
+        get_home_dir().unwrap_or_default().join("Splice")
+    });
+    let slice_dir = get_library_root(db);
+    let splice_prefix = splice_sounds_dir.to_string_lossy().to_string();
+    let slice_prefix = slice_dir.to_string_lossy().to_string();
+
+    let mut copied = 0usize;
+    let mut skipped = 0usize;
+    let mut failed: Vec<String> = Vec::new();
+    let mut seen_hashes: HashSet<String> = HashSet::new();
+    let mut placed = PlacedFiles::new();
+    // 배치가 끝난 샘플: (Splice 행, 병합 키, 저장 경로, 파일 크기)
+    let mut placed_rows: Vec<(&SpliceRow, String, String, Option<i64>)> = Vec::with_capacity(total);
+
+    for (i, s) in splice_samples.iter().enumerate() {
+        job.check_cancelled()?;
+
+        // Rewrite path: Splice dir → Slice dir (크로스 플랫폼)
+        let old_path_buf = PathBuf::from(&s.local_path);
+        let new_path = if let Ok(rel) = old_path_buf.strip_prefix(&splice_sounds_dir) {
+            // PathBuf 기반 상대 경로 추출 → Slice 디렉토리에 합치기
+            slice_dir.join(rel).to_string_lossy().to_string()
+        } else {
+            // strip_prefix 실패 시 문자열 치환 폴백
+            // (Windows \ / macOS / 모두 처리)
+            let normalized_local = s.local_path.replace('\\', "/");
+            let normalized_splice = splice_prefix.replace('\\', "/");
+            let normalized_slice = slice_prefix.replace('\\', "/");
+            normalized_local.replace(&normalized_splice, &normalized_slice)
+        };
+        let mode = s
+            .pack_uuid
+            .as_ref()
+            .and_then(|uuid| pack_modes.get(uuid).copied())
+            .unwrap_or(storage_mode);
+
+        // Use file_hash or generate fallback (경로 기반 — 재동기화 시에도 동일한 키)
+        let hash = if s.file_hash.is_empty() {
+            let mut hasher = DefaultHasher::new();
+            s.local_path.hash(&mut hasher);
+            format!("path-{:016x}", hasher.finish())
+        } else {
+            s.file_hash.clone()
+        };
+
+        // Place audio file (copy / hardlink / symlink / reference)
+        let stored_path = if mode == StorageMode::Reference {
+            skipped += 1;
+            s.local_path.clone()
+        } else {
+            let new_path_buf = PathBuf::from(&new_path);
+            if !new_path_buf.exists() {
+                if old_path_buf.exists() {
+                    match place_library_file(&old_path_buf, &new_path_buf, mode) {
+                        Ok(_) => {
+                            placed.push(new_path_buf.clone(), mode);
+                            copied += 1;
+                        }
+                        // 배치하지 못한 파일은 행을 만들거나 바꾸지 않음 (기존 행은 제거 대상에서도 제외)
+                        Err(e) => {
+                            failed.push(e);
+                            seen_hashes.insert(hash);
+                            continue;
+                        }
+                    }
+                }
+            } else {
+                skipped += 1;
+            }
+            to_library_path(&slice_dir, &new_path_buf)
+        };
+        let file_size = file_size_of(&resolve_library_path(&slice_dir, &stored_path));
+        placed_rows.push((s, hash, stored_path, file_size));
+
+        // Emit progress every 10 items
+        if i % 10 == 0 || i + 1 == total {
+            job.progress(
+                progress_event,
+                ImportProgress {
+                    current: i + 1,
+                    total,
+                    current_file: s.filename.clone(),
+                    current_pack: 1,
+                    total_packs: 1,
+                    current_pack_name: String::new(),
+                    job_id: job.id,
+                },
+                i + 1,
+                total,
+                &s.filename,
+            );
+        }
+    }
+
+    // 5. Merge into Slice DB — DB 기록만 하는 트랜잭션
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+
+    // 기존 Splice 샘플의 file_hash 목록 (추가/갱신 판별 + 제거 대상 계산용)
+    let existing_hashes: std::collections::HashMap<String, i64> = {
+        let mut stmt = tx
+            .prepare("SELECT file_hash, id FROM samples WHERE source = 'splice' AND file_hash IS NOT NULL")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    // 이전 버전의 대체 키(path_<순번>)는 Splice DB 순서에 따라 바뀌므로 라이브러리 경로로 찾음
+    let legacy_ids: std::collections::HashMap<String, i64> = {
+        let mut stmt = tx
+            .prepare(
+                "SELECT local_path, id FROM samples
+                 WHERE source = 'splice' AND file_hash LIKE 'path\\_%' ESCAPE '\\'",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    // Upsert packs (사용자가 수정한 팩은 메타데이터 유지)
+    for p in &splice_packs {
+        tx.execute(
+            "INSERT OR IGNORE INTO packs (uuid, name, description, cover_url, genre, permalink, source, storage_mode, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'splice', ?7, datetime('now'))",
+            params![p.uuid, p.name, p.description, p.cover_url, p.genre, p.permalink, storage_mode.as_str()],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE packs SET name = ?2, description = ?3, cover_url = ?4, genre = ?5, permalink = ?6
+             WHERE uuid = ?1 AND COALESCE(user_edited, 0) = 0
+               AND (name IS NOT ?2 OR description IS NOT ?3 OR cover_url IS NOT ?4
+                    OR genre IS NOT ?5 OR permalink IS NOT ?6)",
+            params![p.uuid, p.name, p.description, p.cover_url, p.genre, p.permalink],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    // Upsert samples
+    let mut added_ids: Vec<i64> = Vec::new();
+    let mut updated = 0usize;
+    for (s, hash, stored_path, file_size) in placed_rows {
+        // BPM: Splice DB 값은 그대로 사용, 없으면 파일명에서 파싱 시도
+        let (bpm, bpm_source, bpm_confidence) = match s.bpm {
+            Some(b) => (Some(b), Some(SOURCE_SPLICE), Some(CONFIDENCE_EXACT)),
+            None => match parse_bpm_from_filename(&s.filename) {
+                Some(b) => (Some(b), Some(SOURCE_FILENAME), Some(CONFIDENCE_FILENAME)),
+                None => (None, None, None),
+            },
+        };
+        let (bars, bpm_mismatch) =
+            loop_length_check(bpm, bpm_source, s.duration, s.sample_type.as_deref());
+        // 나머지 필드는 Splice DB 값 그대로 (값이 없으면 출처도 없음)
+        let splice_source = |present: bool| present.then_some(SOURCE_SPLICE);
+        let splice_confidence = |present: bool| present.then_some(CONFIDENCE_EXACT);
+
+        let existing_id = existing_hashes.get(&hash).copied().or_else(|| {
+            s.file_hash
+                .is_empty()
+                .then(|| legacy_ids.get(&stored_path).copied())
+                .flatten()
+        });
+        if let Some(sample_id) = existing_id {
+            // 파일 위치/길이/소속 팩은 항상 Splice 기준으로 갱신 (이전 형식의 키도 새 키로 교체)
+            let moved = tx
+                .execute(
+                    "UPDATE samples SET file_hash = ?2, local_path = ?3, duration = ?4, pack_uuid = ?5
+                     WHERE id = ?1
+                       AND (file_hash IS NOT ?2 OR local_path IS NOT ?3 OR duration IS NOT ?4
+                            OR pack_uuid IS NOT ?5)",
+                    params![sample_id, hash, stored_path, s.duration, s.pack_uuid],
+                )
+                .map_err(|e| e.to_string())?;
+            // 원본 sounds.db 기록 (다른 계정 DB로 옮겨졌거나 이전 버전에서 가져온 샘플)
+            tx.execute(
+                "UPDATE samples SET splice_db = ?2 WHERE id = ?1 AND splice_db IS NOT ?2",
+                params![sample_id, s.splice_db],
+            )
+            .map_err(|e| e.to_string())?;
+            // 파일명은 사용자가 바꾸지 않은 경우만, 메타데이터는 사용자가 편집하지 않은 필드만 갱신
+            let mut changed = tx
+                .execute(
+                    "UPDATE samples SET filename = ?2
+                     WHERE id = ?1 AND COALESCE(user_edited, 0) = 0 AND filename IS NOT ?2",
+                    params![sample_id, s.filename],
+                )
+                .map_err(|e| e.to_string())?;
+            // Splice에 값이 있는 필드만 갱신 — 없는 필드는 오디오 분석/사용자 값 등을 유지
+            let fields: [(MetaField, Vec<&dyn rusqlite::ToSql>, bool); 4] = [
+                (MetaField::Key, vec![&s.audio_key, &s.chord_type], s.audio_key.is_some()),
+                (MetaField::SampleType, vec![&s.sample_type], s.sample_type.is_some()),
+                (MetaField::Genre, vec![&s.genre], s.genre.is_some()),
+                (MetaField::Tags, vec![&s.tags], s.tags.is_some()),
+            ];
+            for (field, values, _) in fields.iter().filter(|(_, _, present)| *present) {
+                changed += write_sample_field(
+                    &tx,
+                    sample_id,
+                    *field,
+                    values,
+                    Some(SOURCE_SPLICE),
+                    Some(CONFIDENCE_EXACT),
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            if let Some(b) = s.bpm {
+                // 마디 수는 갱신 후의 샘플 타입 기준 (Splice에 타입이 없으면 기존 타입)
+                let sample_type: Option<String> = tx
+                    .query_row("SELECT sample_type FROM samples WHERE id = ?1", params![sample_id], |row| {
+                        row.get(0)
+                    })
+                    .map_err(|e| e.to_string())?;
+                let (bars, mismatch) =
+                    loop_length_check(Some(b), Some(SOURCE_SPLICE), s.duration, sample_type.as_deref());
+                changed += write_sample_field(
+                    &tx,
+                    sample_id,
+                    MetaField::Bpm,
+                    &[&b, &bars, &mismatch],
+                    Some(SOURCE_SPLICE),
+                    Some(CONFIDENCE_EXACT),
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            // 길이/타입만 바뀌었거나 BPM이 사용자 값이어도 마디 수/모순 표시는 현재 값 기준으로 유지
+            changed += refresh_loop_bars(&tx, sample_id).map_err(|e| e.to_string())?;
+            if moved + changed > 0 {
+                updated += 1;
+            }
+        } else {
+            let inserted = tx
+                .execute(
+                    "INSERT OR IGNORE INTO samples
+                     (local_path, filename, audio_key, bpm, chord_type, duration,
+                      file_hash, genre, sample_type, tags, pack_uuid, file_size,
+                      bpm_source, bpm_confidence, key_source, key_confidence,
+                      sample_type_source, sample_type_confidence, genre_source, genre_confidence,
+                      tags_source, tags_confidence, bars, bpm_mismatch, splice_db, source, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12,
+                             ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25,
+                             'splice', datetime('now'))",
+                    params![
+                        stored_path,
+                        s.filename,
+                        s.audio_key,
+                        bpm,
+                        s.chord_type,
+                        s.duration,
+                        hash,
+                        s.genre,
+                        s.sample_type,
+                        s.tags,
+                        s.pack_uuid,
+                        file_size,
+                        bpm_source,
+                        bpm_confidence,
+                        splice_source(s.audio_key.is_some()),
+                        splice_confidence(s.audio_key.is_some()),
+                        splice_source(s.sample_type.is_some()),
+                        splice_confidence(s.sample_type.is_some()),
+                        splice_source(s.genre.is_some()),
+                        splice_confidence(s.genre.is_some()),
+                        splice_source(s.tags.is_some()),
+                        splice_confidence(s.tags.is_some()),
+                        bars,
+                        bpm_mismatch,
+                        s.splice_db
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            if inserted > 0 {
+                added_ids.push(tx.last_insert_rowid());
+            }
+        }
+        seen_hashes.insert(hash);
+    }
+
+    // 6. Splice에서 사라진 샘플 정리 (옵션)
+    let mut removed = 0usize;
+    // 삭제할 라이브러리 파일 — 커밋이 성공한 뒤에 지움 (실패 시 행이 없는 파일을 가리키지 않도록)
+    let mut stale_files: Vec<(String, StorageMode)> = Vec::new();
+    if remove_missing {
+        // 이번에 동기화한 sounds.db에서 가져온 샘플만 제거 대상
+        // 원본 DB 기록이 없는 이전 샘플은 탐지된 모든 DB를 동기화할 때만 제거
+        let synced_dbs: HashSet<String> = splice_db_paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let synced_all = find_splice_dbs()
+            .iter()
+            .all(|p| synced_dbs.contains(p.to_string_lossy().as_ref()));
+        let stale: Vec<(i64, String, StorageMode)> = {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT s.id, s.file_hash, s.local_path, p.storage_mode, s.splice_db
+                     FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                     WHERE s.source = 'splice'",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, String>(2)?,
+                        StorageMode::parse(row.get::<_, Option<String>>(3)?.as_deref()),
+                        row.get::<_, Option<String>>(4)?,
+                    ))
+                })
+                .map_err(|e| e.to_string())?;
+            rows.filter_map(|r| r.ok())
+                .filter(|(_, _, _, _, origin)| match origin {
+                    Some(origin) => synced_dbs.contains(origin),
+                    None => synced_all,
+                })
+                .filter(|(_, hash, _, _, _)| !hash.as_ref().map(|h| seen_hashes.contains(h)).unwrap_or(false))
+                .map(|(id, _, path, mode, _)| (id, path, mode))
+                .collect()
+        };
+
+        for (id, path, mode) in &stale {
+            tx.execute("DELETE FROM playlist_samples WHERE sample_id = ?1", params![id])
+                .map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM samples WHERE id = ?1", params![id])
+                .map_err(|e| format!("샘플 삭제 실패: {}", e))?;
+            stale_files.push((resolve_library_path(&slice_dir, path), *mode));
+            removed += 1;
+        }
+
+        // 샘플이 하나도 남지 않은 Splice 팩 제거
+        tx.execute(
+            "DELETE FROM packs WHERE source = 'splice'
+               AND NOT EXISTS (SELECT 1 FROM samples s WHERE s.pack_uuid = packs.uuid)",
+            [],
+        )
+        .map_err(|e| format!("팩 삭제 실패: {}", e))?;
+    }
+
+    job.check_cancelled()?;
+    tx.commit().map_err(|e| e.to_string())?;
+    placed.keep();
+    // 라이브러리 안에 배치해 둔 파일만 삭제
+    for (path, mode) in &stale_files {
+        remove_library_file(&slice_dir, path, *mode);
+    }
+
+    // Return result
+    let total_packs: i64 = db
+        .query_row(
+            "SELECT COUNT(DISTINCT p.uuid) FROM packs p JOIN samples s ON s.pack_uuid = p.uuid",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    let result = ImportResult {
+        files_copied: copied,
+        files_skipped: skipped,
+        total_packs: total_packs as usize,
+        added: added_ids.len(),
+        updated,
+        removed,
+        failed,
+    };
+    Ok((result, added_ids))
+}
+
+/// Splice 라이브러리 동기화
+/// remove_missing: true면 Splice에서 사라진 샘플을 Slice에서도 제거
+/// storage_mode: 새 팩의 파일 저장 방식 (없으면 마지막으로 사용한 방식)
+/// 백그라운드 작업으로 실행 (취소 시 전체 롤백)
+#[tauri::command]
+async fn import_from_splice(
+    remove_missing: Option<bool>,
+    storage_mode: Option<StorageMode>,
+    app: tauri::AppHandle,
+) -> Result<ImportResult, String> {
+    run_job(app, JobKind::SpliceImport, move |job| {
+        let state = job.app.state::<AppState>();
+        let _busy = BusyGuard::new(&state.busy);
+        let mut db = open_job_db()?;
+        let splice_db_paths = resolve_splice_dbs(&db)?;
+        let storage_mode = match storage_mode {
+            Some(mode) => {
+                set_setting(&db, SETTING_SPLICE_STORAGE_MODE, Some(mode.as_str()))?;
+                mode
+            }
+            None => StorageMode::parse(get_setting(&db, SETTING_SPLICE_STORAGE_MODE).as_deref()),
+        };
+        let (result, _) = sync_splice_dbs(
+            &splice_db_paths,
+            &mut db,
+            job,
+            remove_missing.unwrap_or(false),
+            storage_mode,
+            "import-progress",
+        )?;
+        Ok(result)
+    })
+    .await
+}
+
+/// 자동 탐지된 + 수동 지정된 모든 Splice DB와 팩/샘플 수
+#[tauri::command]
+fn list_splice_dbs(state: State<AppState>) -> Result<Vec<SpliceDbInfo>, String> {
+    let db = state.db.lock().unwrap();
+    let selected = resolve_splice_dbs(&db).unwrap_or_default();
+    let override_path = get_setting(&db, SETTING_SPLICE_DB_OVERRIDE).map(PathBuf::from);
+    drop(db);
+
+    let mut paths = find_splice_dbs();
+    if let Some(p) = &override_path {
+        if p.exists() && !paths.contains(p) {
+            paths.push(p.clone());
+        }
+    }
+
+    let infos = paths
+        .into_iter()
+        .map(|path| {
+            let (pack_count, sample_count) = count_splice_db(&path).unwrap_or((0, 0));
+            // users/<계정>/<해시>/sounds.db → "<계정>/<해시>"
+            let account = path
+                .parent()
+                .map(|hash_dir| {
+                    let hash = hash_dir.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    match hash_dir.parent().and_then(|u| u.file_name()).and_then(|n| n.to_str()) {
+                        Some(user) => format!("{}/{}", user, hash),
+                        None => hash.to_string(),
+                    }
+                })
+                .unwrap_or_default();
+            SpliceDbInfo {
+                path: path.to_string_lossy().to_string(),
+                account,
+                pack_count,
+                sample_count,
+                selected: selected.contains(&path),
+                is_override: override_path.as_ref() == Some(&path),
+            }
+        })
+        .collect();
+
+    Ok(infos)
+}
+
+/// Splice DB의 (팩 수, 샘플 수) — 유효한 sounds.db인지 확인하는 용도로도 사용
+fn count_splice_db(path: &Path) -> Result<(usize, usize), String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Splice DB 열기 실패: {}", e))?;
+    let packs: i64 = conn
+        .query_row("SELECT COUNT(*) FROM packs", [], |row| row.get(0))
+        .map_err(|e| format!("Splice DB 형식이 아닙니다: {}", e))?;
+    let samples: i64 = conn
+        .query_row("SELECT COUNT(*) FROM samples", [], |row| row.get(0))
+        .map_err(|e| format!("Splice DB 형식이 아닙니다: {}", e))?;
+    Ok((packs as usize, samples as usize))
+}
+
+/// 임포트할 Splice DB 선택 (빈 목록이면 자동 선택으로 복귀)
+#[tauri::command]
+fn set_splice_db_selection(
+    paths: Vec<String>,
+    app: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<(), String> {
+    for path in &paths {
+        count_splice_db(Path::new(path))?;
+    }
+    {
+        let db = state.db.lock().unwrap();
+        if paths.is_empty() {
+            set_setting(&db, SETTING_SPLICE_DB_SELECTION, None)?;
+        } else {
+            let json = serde_json::to_string(&paths).map_err(|e| e.to_string())?;
+            set_setting(&db, SETTING_SPLICE_DB_SELECTION, Some(&json))?;
+        }
+    }
+    restart_library_watcher(&app)
+}
+
+/// sounds.db 경로 수동 지정 (파일 또는 sounds.db가 든 폴더, None이면 해제)
+#[tauri::command]
+fn set_splice_db_override(
+    path: Option<String>,
+    app: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<Option<String>, String> {
+    let resolved = match path {
+        Some(p) => {
+            let mut db_path = PathBuf::from(&p);
+            if db_path.is_dir() {
+                db_path = db_path.join("sounds.db");
+            }
+            if !db_path.is_file() {
+                return Err("sounds.db 파일을 찾을 수 없습니다".to_string());
+            }
+            count_splice_db(&db_path)?;
+            Some(db_path.to_string_lossy().to_string())
+        }
+        None => None,
+    };
+    {
+        let db = state.db.lock().unwrap();
+        set_setting(&db, SETTING_SPLICE_DB_OVERRIDE, resolved.as_deref())?;
+    }
+    restart_library_watcher(&app)?;
+    Ok(resolved)
+}
+
+// ── Background jobs ─────────────────────────────────────────────────
+
+/// 완료된 작업은 최근 것만 목록에 유지
+const MAX_FINISHED_JOBS: usize = 50;
+
+const JOB_CANCELLED: &str = "작업이 취소되었습니다";
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 실행 중인 작업 컨텍스트 — 진행 이벤트에 job_id를 붙이고 취소 요청을 확인
+/// 자동 동기화처럼 작업 목록 밖에서 실행될 때는 id가 없고 취소되지 않음
+struct JobHandle {
+    id: Option<u64>,
+    app: tauri::AppHandle,
+    cancel: Arc<AtomicBool>,
+}
+
+impl JobHandle {
+    fn detached(app: &tauri::AppHandle) -> Self {
+        JobHandle {
+            id: None,
+            app: app.clone(),
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(JOB_CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 진행 이벤트 발행 + 작업 목록의 진행 상황 갱신
+    fn progress<S: Serialize + Clone>(
+        &self,
+        event: &str,
+        payload: S,
+        current: usize,
+        total: usize,
+        current_file: &str,
+    ) {
+        let _ = self.app.emit(event, payload);
+        if let Some(id) = self.id {
+            let state = self.app.state::<AppState>();
+            let mut jobs = state.jobs.lock().unwrap();
+            if let Some(entry) = jobs.get_mut(&id) {
+                entry.info.current = current;
+                entry.info.total = total;
+                entry.info.current_file = current_file.to_string();
+            }
+        }
+    }
+}
+
+/// 작업 등록 → 블로킹 스레드에서 실행 → 결과/상태 기록
+/// 호출한 명령은 끝날 때까지 await하므로 기존처럼 결과를 그대로 받음
+/// 시작 시 `job-started`, 종료 시 `job-finished` 이벤트 발행 (payload: JobInfo)
+/// 취소되어도 작업이 부분 결과를 Ok로 돌려주면(커밋된 부분 작업) 그대로 반환
+async fn run_job<T, F>(app: tauri::AppHandle, kind: JobKind, f: F) -> Result<T, String>
+where
+    T: Serialize + Send + 'static,
+    F: FnOnce(&JobHandle) -> Result<T, String> + Send + 'static,
+{
+    let job = {
+        let state = app.state::<AppState>();
+        let id = state.next_job_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let info = JobInfo {
+            id,
+            kind,
+            status: JobStatus::Running,
+            current: 0,
+            total: 0,
+            current_file: String::new(),
+            result: None,
+            error: None,
+            started_at: unix_now(),
+            finished_at: None,
+        };
+        let _ = app.emit("job-started", info.clone());
+        state.jobs.lock().unwrap().insert(
+            id,
+            JobEntry {
+                info,
+                cancel: cancel.clone(),
+            },
+        );
+        JobHandle {
+            id: Some(id),
+            app: app.clone(),
+            cancel,
+        }
+    };
+    let id = job.id.unwrap_or_default();
+
+    let (outcome, cancelled) = tauri::async_runtime::spawn_blocking(move || {
+        let outcome = f(&job);
+        (outcome, job.is_cancelled())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let state = app.state::<AppState>();
+    let mut jobs = state.jobs.lock().unwrap();
+    if let Some(entry) = jobs.get_mut(&id) {
+        entry.info.status = match (&outcome, cancelled) {
+            (_, true) => JobStatus::Cancelled,
+            (Ok(_), false) => JobStatus::Completed,
+            (Err(_), false) => JobStatus::Failed,
+        };
+        match &outcome {
+            Ok(result) => entry.info.result = serde_json::to_value(result).ok(),
+            Err(e) => entry.info.error = Some(e.clone()),
+        }
+        entry.info.finished_at = Some(unix_now());
+        let _ = app.emit("job-finished", entry.info.clone());
+    }
+
+    // 오래된 완료 작업 정리
+    let finished: Vec<u64> = jobs
+        .iter()
+        .filter(|(_, e)| e.info.status != JobStatus::Running)
+        .map(|(id, _)| *id)
+        .collect();
+    for old in finished.iter().take(finished.len().saturating_sub(MAX_FINISHED_JOBS)) {
+        jobs.remove(old);
+    }
+
+    outcome
+}
+
+/// 실행 중/완료된 작업 목록 (최신순)
+#[tauri::command]
+fn list_jobs(state: State<AppState>) -> Result<Vec<JobInfo>, String> {
+    let jobs = state.jobs.lock().unwrap();
+    Ok(jobs.values().rev().map(|e| e.info.clone()).collect())
+}
+
+/// 작업 취소 요청 — 실행 중이던 작업이면 true
+/// 실제 정리(롤백/부분 커밋)는 작업 쪽에서 다음 확인 지점에 수행
+#[tauri::command]
+fn cancel_job(job_id: u64, state: State<AppState>) -> Result<bool, String> {
+    let jobs = state.jobs.lock().unwrap();
+    match jobs.get(&job_id) {
+        Some(entry) if entry.info.status == JobStatus::Running => {
+            entry.cancel.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        Some(_) => Ok(false),
+        None => Err("작업을 찾을 수 없습니다".to_string()),
+    }
+}
+
+// ── Library watcher (Splice 다운로드 자동 동기화) ───────────────────
+
+/// 마지막 파일 변경 후 이 시간 동안 조용하면 동기화 실행
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(2000);
+
+/// 임포트/익스포트 실행 중 표시 — drop 시 자동 해제
+struct BusyGuard<'a>(&'a AtomicUsize);
+
+impl<'a> BusyGuard<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        BusyGuard(counter)
+    }
+}
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 동기화를 트리거할 만한 변경인지 확인 (오디오 파일 또는 sounds.db/-wal/-shm)
+fn is_relevant_watch_event(event: &notify::Event) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    event.paths.iter().any(|p| {
+        let is_splice_db = p
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with("sounds.db"))
+            .unwrap_or(false);
+        is_splice_db || is_audio_file(p)
+    })
+}
+
+/// Splice 다운로드 폴더 + sounds.db 감시 시작
+/// 변경이 감지되면 디바운스 후 증분 동기화 → `library-changed` 이벤트 발행
+/// 진행 상황은 `library-sync-progress`, 실패는 `library-sync-failed`(오류 메시지)로 알림
+fn start_library_watcher(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut slot = state.watcher.lock().unwrap();
+    if slot.is_some() {
+        return Ok(());
+    }
+
+    let splice_db_paths = resolve_splice_dbs(&state.db.lock().unwrap())?;
+    let sounds_dir = get_splice_sounds_dir()?;
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| format!("파일 감시 시작 실패: {}", e))?;
+
+    for db_dir in splice_db_paths.iter().filter_map(|p| p.parent()) {
+        watcher
+            .watch(db_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Splice DB 감시 실패: {}", e))?;
+    }
+    if sounds_dir.is_dir() {
+        watcher
+            .watch(&sounds_dir, RecursiveMode::Recursive)
+            .map_err(|e| format!("Splice 폴더 감시 실패: {}", e))?;
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut pending = false;
+        let mut last_change = Instant::now();
+        loop {
+            match rx.recv_timeout(WATCH_DEBOUNCE) {
+                Ok(Ok(event)) => {
+                    if is_relevant_watch_event(&event) {
+                        pending = true;
+                        last_change = Instant::now();
+                    }
+                }
+                Ok(Err(_)) => {}
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    if !pending || last_change.elapsed() < WATCH_DEBOUNCE {
+                        continue;
+                    }
+                    let state = app.state::<AppState>();
+                    // 임포트/익스포트 중이면 끝날 때까지 대기 (다음 타임아웃에 재시도)
+                    if state.busy.load(Ordering::SeqCst) > 0 {
+                        continue;
+                    }
+                    pending = false;
+
+                    // 별도 연결 사용 — 파일 배치 동안 메인 연결(UI 명령)을 막지 않음
+                    let synced = open_job_db().and_then(|mut db| {
+                        let storage_mode = StorageMode::parse(
+                            get_setting(&db, SETTING_SPLICE_STORAGE_MODE).as_deref(),
+                        );
+                        sync_splice_dbs(
+                            &splice_db_paths,
+                            &mut db,
+                            &JobHandle::detached(&app),
+                            false,
+                            storage_mode,
+                            "library-sync-progress",
+                        )
+                    });
+                    match synced {
+                        Ok((result, added_sample_ids)) => {
+                            if result.added + result.updated + result.removed > 0 {
+                                let _ = app.emit(
+                                    "library-changed",
+                                    LibraryChanged {
+                                        added_sample_ids,
+                                        added: result.added,
+                                        updated: result.updated,
+                                        removed: result.removed,
+                                    },
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            let _ = app.emit("library-sync-failed", format!("자동 동기화 실패: {}", e));
+                        }
+                    }
+                }
+                // 감시자가 drop되면 채널이 닫히면서 스레드 종료
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    });
+
+    *slot = Some(watcher);
+    Ok(())
+}
+
+/// Splice DB 선택이 바뀌었을 때 감시 대상 갱신 (감시 중일 때만)
+fn restart_library_watcher(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let was_running = state.watcher.lock().unwrap().take().is_some();
+    if was_running {
+        start_library_watcher(app)?;
+    }
+    Ok(())
+}
+
+/// 자동 동기화 켜기/끄기 (다음 실행에도 유지) — 현재 감시 중인지 반환
+#[tauri::command]
+fn set_library_watcher(enabled: bool, app: tauri::AppHandle, state: State<AppState>) -> Result<bool, String> {
+    set_setting(
+        &state.db.lock().unwrap(),
+        SETTING_LIBRARY_WATCHER,
+        Some(if enabled { "1" } else { "0" }),
+    )?;
+    if enabled {
+        start_library_watcher(&app)?;
+    } else {
+        state.watcher.lock().unwrap().take();
+    }
+    Ok(state.watcher.lock().unwrap().is_some())
+}
+
+#[tauri::command]
+fn scan_library(state: State<AppState>) -> Result<LibraryData, String> {
+    let db = state.db.lock().unwrap();
+    let mut stmt = db
+        .prepare(
+            "SELECT p.uuid, p.name, p.genre, p.cover_url, COUNT(s.id) as sample_count, p.created_at,
+                    p.storage_mode
+             FROM packs p
+             JOIN samples s ON s.pack_uuid = p.uuid
+             GROUP BY p.uuid
+             ORDER BY p.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let packs: Vec<Pack> = stmt
+        .query_map([], |row| {
+            Ok(Pack {
+                uuid: row.get(0)?,
+                name: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                genre: row.get(2)?,
+                cover_url: row.get(3)?,
+                sample_count: row.get::<_, i64>(4)? as usize,
+                created_at: row.get(5)?,
+                storage_mode: StorageMode::parse(row.get::<_, Option<String>>(6)?.as_deref()),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let total_samples = packs.iter().map(|p| p.sample_count).sum();
+
+    Ok(LibraryData {
+        packs,
+        total_samples,
+    })
+}
+
+#[tauri::command]
+fn get_all_samples(state: State<AppState>) -> Result<Vec<Sample>, String> {
+    let db = state.db.lock().unwrap();
+    let root = get_library_root(&db);
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {}
+             FROM samples s
+             LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             ORDER BY s.filename COLLATE NOCASE",
+            SAMPLE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let samples: Vec<Sample> = stmt
+        .query_map([], |row| sample_from_row(row, &root))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(samples)
+}
+
+#[tauri::command]
+fn get_pack_samples(pack_uuid: String, state: State<AppState>) -> Result<Vec<Sample>, String> {
+    let db = state.db.lock().unwrap();
+    let root = get_library_root(&db);
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {}
+             FROM samples s
+             LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE s.pack_uuid = ?1
+             ORDER BY s.filename COLLATE NOCASE",
+            SAMPLE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let samples: Vec<Sample> = stmt
+        .query_map(params![pack_uuid], |row| sample_from_row(row, &root))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(samples)
+}
+
+/// 폴더 트리 구조를 재귀적으로 스캔
+fn build_folder_tree(dir: &Path) -> FolderNode {
+    let name = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Unknown")
+        .to_string();
+
+    let mut audio_count = 0usize;
+    let mut children = Vec::new();
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        let mut entries: Vec<_> = entries.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            if path.is_dir() {
+                // .으로 시작하는 숨김 폴더 제외
+                let fname = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if !fname.starts_with('.') {
+                    let child = build_folder_tree(&path);
+                    if child.total_audio_count > 0 {
+                        children.push(child);
+                    }
+                }
+            } else if is_audio_file(&path) {
+                audio_count += 1;
+            }
+        }
+    }
+
+    let total_audio_count = audio_count + children.iter().map(|c| c.total_audio_count).sum::<usize>();
+
+    FolderNode {
+        name,
+        path: dir.to_string_lossy().to_string(),
+        audio_count,
+        total_audio_count,
+        children,
+    }
+}
+
+/// 외부 폴더(또는 ZIP 파일)의 트리 구조 스캔 (팩 선택 UI용)
+#[tauri::command]
+fn scan_external_folder(folder_path: String) -> Result<FolderNode, String> {
+    let folder = PathBuf::from(&folder_path);
+    let tree = if folder.is_file() && is_archive_path(&folder) {
+        scan_archive_tree(&folder)?
+    } else if folder.is_dir() {
+        build_folder_tree(&folder)
+    } else {
+        return Err("유효한 폴더 또는 ZIP 파일이 아닙니다".to_string());
+    };
+    if tree.total_audio_count == 0 {
+        return Err("오디오 파일을 찾을 수 없습니다".to_string());
+    }
+    Ok(tree)
+}
+
+/// 팩 이름 충돌 확인
+/// selected_paths: 임포트할 폴더/ZIP 경로 — 임포트와 같은 방식으로 팩을 나눠서 팩 이름으로 확인
+/// (내보내기 번들은 사이드카의 원래 팩 이름). 백그라운드 스레드에서 실행
+#[tauri::command]
+async fn check_pack_name_conflicts(
+    selected_paths: Vec<String>,
+    app: tauri::AppHandle,
+) -> Result<Vec<PackConflict>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut pack_names: Vec<String> = Vec::new();
+        for pack in collect_import_packs(&selected_paths)? {
+            if !pack_names.contains(&pack.name) {
+                pack_names.push(pack.name);
+            }
+        }
+        let state = app.state::<AppState>();
+        let db = state.db.lock().unwrap();
+        find_pack_name_conflicts(&db, &pack_names)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn find_pack_name_conflicts(db: &Connection, pack_names: &[String]) -> Result<Vec<PackConflict>, String> {
+    let mut conflicts = Vec::new();
+
+    for name in pack_names {
+        let result: Result<(String, usize), _> = db.query_row(
+            "SELECT p.uuid, COUNT(s.id) FROM packs p
+             LEFT JOIN samples s ON s.pack_uuid = p.uuid
+             WHERE p.name = ?1
+             GROUP BY p.uuid",
+            params![name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        );
+        if let Ok((uuid, count)) = result {
+            conflicts.push(PackConflict {
+                name: name.clone(),
+                existing_uuid: uuid,
+                existing_sample_count: count,
+            });
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// export_samples가 오디오 파일 옆에 쓰는 `<stem>_metadata.json`
+/// 임포트 시 같은 이름의 사이드카가 있으면 값과 팩 묶음을 그대로 복원
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+struct SampleSidecar {
+    filename: Option<String>,
+    audio_key: Option<String>,
+    bpm: Option<f64>,
+    chord_type: Option<String>,
+    duration_ms: Option<i64>,
+    genre: Option<String>,
+    sample_type: Option<String>,
+    tags: Option<Vec<String>>,
+    pack_name: Option<String>,
+    pack_uuid: Option<String>,
+    pack_genre: Option<String>,
+}
+
+impl SampleSidecar {
+    fn path_for(audio_path: &Path) -> Option<PathBuf> {
+        let stem = audio_path.file_stem()?.to_str()?;
+        Some(audio_path.with_file_name(format!("{}_metadata.json", stem)))
+    }
+
+    /// 오디오 파일 옆의 사이드카 (없거나 JSON이 아니면 None)
+    fn read(audio_path: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(Self::path_for(audio_path)?).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// 원래 팩 (내보낸 라이브러리의 팩 UUID/이름이 하나라도 있을 때)
+    fn origin(&self) -> Option<PackOrigin> {
+        if self.pack_uuid.is_none() && self.pack_name.is_none() {
+            return None;
+        }
+        Some(PackOrigin {
+            uuid: self.pack_uuid.clone(),
+            name: self.pack_name.clone(),
+            genre: self.pack_genre.clone(),
+        })
+    }
+}
+
+/// 사이드카에 기록된 원래 팩
+#[derive(Debug, Clone, PartialEq)]
+struct PackOrigin {
+    uuid: Option<String>,
+    name: Option<String>,
+    genre: Option<String>,
+}
+
+/// 임포트할 파일 하나 — 폴더면 실제 경로, ZIP이면 아카이브 안 경로와 항목 번호
+struct ImportSource {
+    path: PathBuf,
+    archive_index: Option<usize>,
+    sidecar: Option<SampleSidecar>,
+}
+
+impl ImportSource {
+    fn from_file(path: PathBuf) -> Self {
+        ImportSource {
+            sidecar: SampleSidecar::read(&path),
+            path,
+            archive_index: None,
+        }
+    }
+}
+
+/// 임포트할 팩 하나 — 선택한 폴더, 또는 내보내기 번들 안에서 원래 팩별로 나눈 묶음
+struct ImportPack {
+    name: String,
+    /// 라이브러리 안 상대 경로의 기준 폴더 (ZIP이면 아카이브 안 경로)
+    folder: PathBuf,
+    files: Vec<ImportSource>,
+    origin: Option<PackOrigin>,
+    /// ZIP에서 임포트하면 원본 아카이브 — 오디오 파일만 라이브러리로 풀어서 임포트
+    archive: Option<PathBuf>,
+}
+
+/// 오디오 파일을 사이드카의 원래 팩별로 나눔 (사이드카 없는 파일은 폴더 이름의 팩)
+fn split_import_packs(
+    name: &str,
+    folder: &Path,
+    files: Vec<ImportSource>,
+    archive: Option<&Path>,
+) -> Vec<ImportPack> {
+    let pack = |name: String, files: Vec<ImportSource>, origin: Option<PackOrigin>| ImportPack {
+        name,
+        folder: folder.to_path_buf(),
+        files,
+        origin,
+        archive: archive.map(Path::to_path_buf),
+    };
+    let mut plain = Vec::new();
+    let mut bundles: Vec<ImportPack> = Vec::new();
+    for file in files {
+        let Some(origin) = file.sidecar.as_ref().and_then(|s| s.origin()) else {
+            plain.push(file);
+            continue;
+        };
+        match bundles
+            .iter_mut()
+            .find(|p| p.origin.as_ref().is_some_and(|o| o.uuid == origin.uuid && o.name == origin.name))
+        {
+            Some(p) => p.files.push(file),
+            None => bundles.push(pack(
+                origin.name.clone().unwrap_or_else(|| name.to_string()),
+                vec![file],
+                Some(origin),
+            )),
+        }
+    }
+    let mut packs = Vec::new();
+    if !plain.is_empty() {
+        packs.push(pack(name.to_string(), plain, None));
+    }
+    packs.extend(bundles);
+    packs
+}
+
+// ── ZIP archive import ──────────────────────────────────────────────
+
+/// ZIP 항목 수 상한 (압축 폭탄 방지)
+const ARCHIVE_MAX_ENTRIES: usize = 200_000;
+/// ZIP 안 오디오 파일의 압축 해제 크기 합계 상한
+const ARCHIVE_MAX_TOTAL_BYTES: u64 = 32 * 1024 * 1024 * 1024;
+/// 사이드카 JSON 하나의 압축 해제 크기 상한
+const ARCHIVE_MAX_SIDECAR_BYTES: u64 = 1024 * 1024;
+
+fn is_archive_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+}
+
+/// 아카이브 안 폴더를 가리키는 경로("…/Pack.zip/Drums") → (아카이브, 안쪽 폴더)
+fn split_archive_path(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let archive = path
+        .ancestors()
+        .find(|a| is_archive_path(a) && a.is_file())?;
+    let inner = path.strip_prefix(archive).ok()?.to_path_buf();
+    Some((archive.to_path_buf(), inner))
+}
+
+/// macOS 압축 부산물(__MACOSX, ._ 리소스 포크)과 숨김 파일/폴더
+fn is_archive_junk(path: &Path) -> bool {
+    path.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        name == "__MACOSX" || name.starts_with('.')
+    })
+}
+
+fn open_archive(path: &Path) -> Result<zip::ZipArchive<std::fs::File>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("ZIP 파일 열기 실패: {}", e))?;
+    zip::ZipArchive::new(file).map_err(|e| format!("ZIP 파일 읽기 실패: {}", e))
+}
+
+/// 아카이브 안 오디오 파일 목록 (항목 번호, 안전한 상대 경로) — 사이드카 JSON은 함께 읽음
+/// 항목 수나 오디오 파일의 압축 해제 크기 합계가 상한을 넘으면 에러
+fn list_archive_audio(archive: &mut zip::ZipArchive<std::fs::File>) -> Result<Vec<ImportSource>, String> {
+    use std::io::Read;
+
+    if archive.len() > ARCHIVE_MAX_ENTRIES {
+        return Err(format!(
+            "ZIP 파일의 항목이 너무 많습니다 ({}개, 최대 {}개)",
+            archive.len(),
+            ARCHIVE_MAX_ENTRIES
+        ));
+    }
+    let mut audio = Vec::new();
+    let mut total_size = 0u64;
+    let mut sidecars: std::collections::HashMap<PathBuf, usize> = std::collections::HashMap::new();
+    for i in 0..archive.len() {
+        let Ok(entry) = archive.by_index_raw(i) else {
+            continue;
+        };
+        // 절대 경로/".." 항목은 enclosed_name이 None (zip slip 방지)
+        let Some(path) = entry.enclosed_name() else {
+            continue;
+        };
+        if entry.is_dir() || is_archive_junk(&path) {
+            continue;
+        }
+        if is_audio_file(&path) {
+            total_size = total_size.saturating_add(entry.size());
+            if total_size > ARCHIVE_MAX_TOTAL_BYTES {
+                return Err(format!(
+                    "ZIP 파일의 압축 해제 크기가 너무 큽니다 (최대 {} GB)",
+                    ARCHIVE_MAX_TOTAL_BYTES / (1024 * 1024 * 1024)
+                ));
+            }
+            audio.push((i, path));
+        } else if path.to_string_lossy().ends_with("_metadata.json")
+            && entry.size() <= ARCHIVE_MAX_SIDECAR_BYTES
+        {
+            sidecars.insert(path, i);
+        }
+    }
+    audio.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let sources = audio
+        .into_iter()
+        .map(|(index, path)| {
+            let sidecar = SampleSidecar::path_for(&path)
+                .and_then(|p| sidecars.get(&p).copied())
+                .and_then(|i| {
+                    // 항목 정보의 크기를 믿지 않고 읽는 양도 제한
+                    let mut json = String::new();
+                    archive
+                        .by_index(i)
+                        .ok()?
+                        .take(ARCHIVE_MAX_SIDECAR_BYTES)
+                        .read_to_string(&mut json)
+                        .ok()?;
+                    serde_json::from_str(&json).ok()
+                });
+            ImportSource {
+                path,
+                archive_index: Some(index),
+                sidecar,
+            }
+        })
+        .collect();
+    Ok(sources)
+}
+
+/// 아카이브 안 경로 목록으로 폴더 트리 구성 (paths는 이 노드 기준 상대 경로)
+fn build_archive_tree(name: String, node_path: PathBuf, paths: &[&Path]) -> FolderNode {
+    let mut audio_count = 0usize;
+    let mut dirs: std::collections::BTreeMap<String, Vec<&Path>> = std::collections::BTreeMap::new();
+    for path in paths {
+        let mut components = path.components();
+        let first = components.next().map(|c| c.as_os_str().to_string_lossy().to_string());
+        let rest = components.as_path();
+        match first {
+            Some(dir) if !rest.as_os_str().is_empty() => dirs.entry(dir).or_default().push(rest),
+            Some(_) => audio_count += 1,
+            None => {}
+        }
+    }
+    let children: Vec<FolderNode> = dirs
+        .into_iter()
+        .map(|(dir, rest)| build_archive_tree(dir.clone(), node_path.join(&dir), &rest))
+        .collect();
+    let total_audio_count = audio_count + children.iter().map(|c| c.total_audio_count).sum::<usize>();
+
+    FolderNode {
+        name,
+        path: node_path.to_string_lossy().to_string(),
+        audio_count,
+        total_audio_count,
+        children,
+    }
+}
+
+/// ZIP 파일의 폴더 트리 (루트 이름은 확장자를 뺀 파일명, 하위 경로는 "…/Pack.zip/폴더")
+fn scan_archive_tree(archive_path: &Path) -> Result<FolderNode, String> {
+    let mut archive = open_archive(archive_path)?;
+    let entries = list_archive_audio(&mut archive)?;
+    let paths: Vec<&Path> = entries.iter().map(|e| e.path.as_path()).collect();
+    let name = archive_path
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Unknown")
+        .to_string();
+    Ok(build_archive_tree(name, archive_path.to_path_buf(), &paths))
+}
+
+/// 선택한 아카이브(또는 그 안 폴더)의 오디오 파일 → 임포트할 팩 목록
+fn archive_import_packs(archive_path: &Path, inner: &Path) -> Result<Vec<ImportPack>, String> {
+    let mut archive = open_archive(archive_path)?;
+    let files: Vec<ImportSource> = list_archive_audio(&mut archive)?
+        .into_iter()
+        .filter(|e| e.path.starts_with(inner))
+        .collect();
+    let name = match inner.file_name() {
+        Some(n) => n.to_string_lossy().to_string(),
+        None => archive_path
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown Pack")
+            .to_string(),
+    };
+    Ok(split_import_packs(&name, inner, files, Some(archive_path)))
+}
+
+/// 팩의 오디오 파일을 아카이브에서 dest_base 아래로 풀기 (이미 있는 파일은 그대로 사용)
+/// 새로 푼 파일은 placed에 기록 → (라이브러리 안 파일 목록, 새로 푼 수, 이미 있던 수)
+/// 항목 정보보다 많이 풀리는 항목(크기를 속인 압축 폭탄)은 에러
+fn extract_archive_pack(
+    pack: &ImportPack,
+    archive_path: &Path,
+    dest_base: &Path,
+    job: &JobHandle,
+    placed: &mut PlacedFiles,
+) -> Result<(Vec<ImportSource>, usize, usize), String> {
+    use std::io::Read;
+
+    let mut archive = open_archive(archive_path)?;
+    let mut files = Vec::with_capacity(pack.files.len());
+    let (mut extracted, mut existing) = (0usize, 0usize);
+    for source in &pack.files {
+        job.check_cancelled()?;
+        let rel = source.path.strip_prefix(&pack.folder).unwrap_or(&source.path);
+        let dest = dest_base.join(rel);
+        if dest.exists() {
+            existing += 1;
+        } else if let Some(index) = source.archive_index {
+            if let Some(parent) = dest.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            let entry = archive.by_index(index).map_err(|e| e.to_string())?;
+            let declared = entry.size();
+            let mut out = std::fs::File::create(&dest)
+                .map_err(|e| format!("파일 배치 실패 ({}): {}", dest.display(), e))?;
+            placed.push(dest.clone(), StorageMode::Copy);
+            let written = std::io::copy(&mut entry.take(declared.saturating_add(1)), &mut out)
+                .map_err(|e| format!("압축 해제 실패 ({}): {}", source.path.display(), e))?;
+            if written > declared {
+                return Err(format!(
+                    "압축 해제 실패 ({}): 항목 크기 정보와 실제 크기가 다릅니다",
+                    source.path.display()
+                ));
+            }
+            extracted += 1;
+        }
+        files.push(ImportSource {
+            path: dest,
+            archive_index: None,
+            sidecar: source.sidecar.clone(),
+        });
+    }
+    Ok((files, extracted, existing))
+}
+
+/// 임포트할 파일 하나의 배치/분석 결과 (워커 스레드에서 생성, DB 기록은 순차)
+struct ImportedFile {
+    filename: String,
+    dest_path: PathBuf,
+    placed: Result<bool, String>, // Ok(true): 새로 배치, Ok(false): 이미 있음/제자리 참조
+    file_size: Option<i64>,
+    duration_ms: Option<i64>,
+    bpm: Option<(f64, &'static str, f32)>,
+    /// 루프 마디 수, 파일명 BPM/길이 모순
+    bars: Option<i32>,
+    bpm_mismatch: bool,
+    key: Option<(String, Option<String>, &'static str, f32)>,
+    sample_type: (String, &'static str, f32),
+    genre: Option<(String, &'static str, f32)>,
+    tags: Option<(String, &'static str, f32)>,
+    /// 규칙 외 출처(오디오 분류, 학습 분류기)로 붙인 태그와 출처/신뢰도
+    suggested_tags: Vec<(String, &'static str, f32)>,
+    /// 파일에 내장된 루프 포인트/큐 마커
+    markers: SampleMarkers,
+}
+
+/// 임포트 중 태그/장르 부여에 쓰는 규칙과 분류기 (팩마다 DB에서 한 번 로드)
+struct ImportTaggers {
+    rules: MetadataRuleSet,
+    classifier: TagClassifier,
+}
+
+impl ImportTaggers {
+    fn load(conn: &Connection) -> Self {
+        ImportTaggers {
+            rules: MetadataRuleSet::load(conn),
+            classifier: TagClassifier::load(conn),
+        }
+    }
+}
+
+/// 파일 배치 + 메타데이터 분석 (DB 접근 없음 — 워커 풀에서 병렬 실행)
+fn analyze_import_file(
+    src: &ImportSource,
+    pack_name: &str,
+    pack_folder: &Path,
+    dest_base: &Path,
+    storage_mode: StorageMode,
+    pack_genre: Option<&str>,
+    taggers: &ImportTaggers,
+) -> ImportedFile {
+    let src_path = src.path.as_path();
+    let src_str = src_path.to_string_lossy().to_string();
+    let filename = src_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    // Slice 내보내기 번들이면 사이드카 값이 내장 값/휴리스틱보다 우선
+    let sidecar = src.sidecar.clone().unwrap_or_default();
+    let from_sidecar = |v: &Option<String>| v.clone().filter(|v| !v.trim().is_empty());
+
+    // 상대 경로 유지
+    let rel_path = src_path
+        .strip_prefix(pack_folder)
+        .unwrap_or(src_path)
+        .to_string_lossy()
+        .to_string();
+    let dest_path = dest_base.join(&rel_path);
+
+    // 파일 배치 (저장 방식에 따라 복사/링크/제자리 참조)
+    let (dest_path, placed) = match place_library_file(src_path, &dest_path, storage_mode) {
+        Ok((path, newly)) => (path, Ok(newly)),
+        Err(e) => (dest_path, Err(e)),
+    };
+    let dest_str = dest_path.to_string_lossy().to_string();
+
+    // 메타데이터 파싱
+    let full_path_for_parse = format!("{}/{}", pack_name, rel_path);
+    let duration_ms = compute_duration_ms(&dest_str)
+        .or_else(|| compute_duration_ms(&src_str));
+    let audio_path = if dest_path.exists() { &dest_str } else { &src_str };
+    let audio = AnalysisContext::new(audio_path);
+
+    // 파일에 내장된 ACID/smpl/cue 청크와 태그가 있으면 휴리스틱보다 우선
+    let embedded = audio.embedded();
+    let sidecar_type = sidecar
+        .sample_type
+        .as_deref()
+        .filter(|t| matches!(*t, "loop" | "oneshot"));
+    let sample_type = match (sidecar_type, embedded.sample_type) {
+        (Some(t), _) => (t.to_string(), SOURCE_SIDECAR, CONFIDENCE_EXACT),
+        (None, Some(t)) => (t.to_string(), SOURCE_EMBEDDED, CONFIDENCE_EMBEDDED),
+        (None, None) => parse_sample_type(&full_path_for_parse, duration_ms, Some(&audio)),
+    };
+    let is_loop = sample_type.0 == "loop";
+
+    // BPM: 사이드카 → 내장 값 → 파일명 → 오디오 분석 순으로 시도
+    // (2초 이상 샘플이면 루프 여부와 관계없이 오디오 분석 시도, 루프는 길이로 마디 수 교차 검증)
+    let bpm = sidecar
+        .bpm
+        .filter(|b| *b > 0.0)
+        .map(|b| (b, SOURCE_SIDECAR, CONFIDENCE_EXACT))
+        .or_else(|| embedded.bpm.map(|b| (b, SOURCE_EMBEDDED, CONFIDENCE_EMBEDDED)))
+        .or_else(|| {
+            parse_bpm_from_filename(&full_path_for_parse)
+                .map(|b| (b, SOURCE_FILENAME, CONFIDENCE_FILENAME))
+        })
+        .or_else(|| {
+            let long_enough = duration_ms.map(|d| d >= 2000).unwrap_or(false);
+            if long_enough {
+                audio
+                    .detect_bpm(duration_ms.filter(|_| is_loop))
+                    .map(|b| (b, SOURCE_AUDIO, CONFIDENCE_AUDIO_BPM))
+            } else {
+                None
+            }
+        });
+    let (bars, bpm_mismatch) = loop_length_check(
+        bpm.map(|b| b.0),
+        bpm.map(|b| b.1),
+        duration_ms,
+        Some(&sample_type.0),
+    );
+
+    let sidecar_tags: Vec<String> = sidecar
+        .tags
+        .iter()
+        .flatten()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let (tags, suggested_tags) = if sidecar_tags.is_empty() {
+        import_file_tags(&full_path_for_parse, &filename, &sample_type.0, duration_ms, &audio, taggers)
+    } else {
+        (Some((sidecar_tags.join(","), SOURCE_SIDECAR, CONFIDENCE_EXACT)), Vec::new())
+    };
+
+    // 키: 사이드카 → 내장 값 → 파일명 → 오디오 분석(크로마그램, 원샷/타악기 제외) 순으로 시도
+    let key = match from_sidecar(&sidecar.audio_key) {
+        Some(key) => Some((key, from_sidecar(&sidecar.chord_type), SOURCE_SIDECAR, CONFIDENCE_EXACT)),
+        None => match (embedded.key.clone(), parse_key_from_filename(&full_path_for_parse)) {
+            (Some((key, chord)), _) => Some((key, chord, SOURCE_EMBEDDED, CONFIDENCE_EMBEDDED)),
+            (None, Some(key)) => Some((key, None, SOURCE_FILENAME, CONFIDENCE_FILENAME)),
+            (None, None) if should_detect_key(Some(&sample_type.0), tags.as_ref().map(|t| t.0.as_str())) => {
+                match audio.detect_key() {
+                    Some((key, chord, conf)) if conf >= KEY_MIN_CONFIDENCE => {
+                        Some((key, Some(chord), SOURCE_AUDIO, conf))
+                    }
+                    _ => None,
+                }
+            }
+            (None, None) => None,
+        },
+    };
+    let genre = from_sidecar(&sidecar.genre)
+        .map(|g| (g, SOURCE_SIDECAR, CONFIDENCE_EXACT))
+        .or_else(|| embedded.genre.clone().map(|g| (g, SOURCE_EMBEDDED, CONFIDENCE_EMBEDDED)))
+        .or_else(|| {
+            parse_genre_from_path(&taggers.rules, &full_path_for_parse)
+                .map(|g| (g, SOURCE_PATH, CONFIDENCE_PATH))
+        })
+        .or_else(|| pack_genre.map(|g| (g.to_string(), SOURCE_PACK, CONFIDENCE_PACK)));
+    let markers = embedded.markers.clone();
+
+    ImportedFile {
+        // 번들로 내보낼 때 중복 이름에 붙은 " (2)" 등은 원래 파일명으로 되돌림
+        filename: from_sidecar(&sidecar.filename).unwrap_or(filename),
+        file_size: file_size_of(&dest_str),
+        dest_path,
+        placed,
+        duration_ms,
+        bpm,
+        bars,
+        bpm_mismatch,
+        key,
+        sample_type,
+        genre,
+        tags,
+        suggested_tags,
+        markers,
+    }
+}
+
+/// (값, 출처, 신뢰도)
+type SourcedValue = (String, &'static str, f32);
+
+/// 경로 규칙 + 원샷 타악기 분류 + 학습 분류기로 붙인 태그 → (태그, 규칙 외 제안 태그)
+fn import_file_tags(
+    full_path_for_parse: &str,
+    filename: &str,
+    sample_type: &str,
+    duration_ms: Option<i64>,
+    audio: &AnalysisContext,
+    taggers: &ImportTaggers,
+) -> (Option<SourcedValue>, Vec<SourcedValue>) {
+    let mut tags_vec = parse_tags_from_path(&taggers.rules, full_path_for_parse, filename);
+    let from_rules = !tags_vec.is_empty();
+    let mut suggested_tags: Vec<(String, &'static str, f32)> = Vec::new();
+    // 원샷 타악기: 파일명에 종류가 없으면 오디오로 분류
+    if sample_type == "oneshot" && duration_ms.is_some_and(|d| d <= DRUM_HIT_MAX_MS) {
+        if let Some((tag, conf)) = merge_drum_hit_tag(&taggers.rules, &mut tags_vec, audio) {
+            suggested_tags.push((tag, SOURCE_AUDIO, conf));
+        }
+    }
+    // 사용자 교정으로 학습한 분류기 제안 (이미 붙은 태그 제외)
+    for (tag, conf) in taggers
+        .classifier
+        .suggest(&classifier_tokens(full_path_for_parse), &tags_vec)
+    {
+        tags_vec.push(tag.clone());
+        suggested_tags.push((tag, SOURCE_CLASSIFIER, conf));
+    }
+    let tags = if tags_vec.is_empty() {
+        None
+    } else {
+        let (source, conf) = match suggested_tags.first() {
+            Some((_, source, _)) if !from_rules => (
+                *source,
+                suggested_tags.iter().map(|(_, _, c)| *c).fold(1.0, f32::min),
+            ),
+            _ => (SOURCE_PATH, CONFIDENCE_PATH),
+        };
+        Some((tags_vec.join(","), source, conf))
+    };
+    (tags, suggested_tags)
+}
+
+/// 팩 하나의 임포트 결과
+#[derive(Default)]
+struct PackImportCounts {
+    copied: usize,
+    skipped: usize,
+    added: usize,
+    updated: usize,
+    removed: usize,
+    failed: Vec<String>,
+}
+
+/// 교체 임포트에서 기존 샘플 행을 새 분석 결과로 제자리 갱신 (id/플레이리스트 연결 유지)
+/// 사용자가 편집한 필드와 파일명은 유지하고, 새 분석에 값이 없는 필드는 기존 값을 둠. 바뀌었으면 true
+fn update_imported_sample(
+    conn: &Connection,
+    sample_id: i64,
+    file: &ImportedFile,
+    stored_path: &str,
+) -> rusqlite::Result<bool> {
+    let mut changed = conn.execute(
+        "UPDATE samples SET local_path = ?2, duration = ?3, file_size = ?4
+         WHERE id = ?1 AND (local_path IS NOT ?2 OR duration IS NOT ?3 OR file_size IS NOT ?4)",
+        params![sample_id, stored_path, file.duration_ms, file.file_size],
+    )?;
+    changed += conn.execute(
+        "UPDATE samples SET filename = ?2
+         WHERE id = ?1 AND COALESCE(user_edited, 0) = 0 AND filename IS NOT ?2",
+        params![sample_id, file.filename],
+    )?;
+    let (sample_type, source, confidence) = &file.sample_type;
+    changed += write_sample_field(
+        conn,
+        sample_id,
+        MetaField::SampleType,
+        &[sample_type],
+        Some(source),
+        Some(*confidence),
+    )?;
+    if let Some((key, chord, source, confidence)) = &file.key {
+        changed += write_sample_field(conn, sample_id, MetaField::Key, &[key, chord], Some(source), Some(*confidence))?;
+    }
+    if let Some((genre, source, confidence)) = &file.genre {
+        changed += write_sample_field(conn, sample_id, MetaField::Genre, &[genre], Some(source), Some(*confidence))?;
+    }
+    if let Some((tags, source, confidence)) = &file.tags {
+        changed += write_sample_field(conn, sample_id, MetaField::Tags, &[tags], Some(source), Some(*confidence))?;
+    }
+    if let Some((bpm, source, confidence)) = &file.bpm {
+        changed += write_sample_field(
+            conn,
+            sample_id,
+            MetaField::Bpm,
+            &[bpm, &file.bars, &file.bpm_mismatch],
+            Some(source),
+            Some(*confidence),
+        )?;
+    }
+    // BPM이 사용자 값이면 새 길이/타입 기준으로 마디 수만 다시 계산
+    changed += refresh_loop_bars(conn, sample_id)?;
+    store_sample_markers(conn, sample_id, &file.markers)?;
+    Ok(changed > 0)
+}
+
+/// 단일 팩을 임포트하는 내부 헬퍼
+/// replace_uuid: Some이면 기존 팩을 교체 — 기존 샘플은 경로로 찾아 제자리 갱신하고
+/// 새 폴더에 없는 샘플만 삭제 (해당 UUID 재사용)
+/// origin: 내보내기 번들의 원래 팩 — 그 UUID를 재사용하고, 이미 있는 팩이면 그 팩에 추가
+/// 파일 배치/분석은 트랜잭션 밖에서 하고, DB 기록만 짧은 트랜잭션으로 커밋
+/// (다른 연결의 쓰기가 파일 복사 동안 잠기지 않도록)
+/// 새로 배치한 파일은 placed에 기록 (취소/오류 시 호출자가 정리)
+fn import_single_pack(
+    pack_name: &str,
+    pack_folder: &Path,
+    audio_files: &[ImportSource],
+    dest_base: &Path,
+    db: &mut Connection,
+    job: &JobHandle,
+    global_offset: usize,
+    global_total: usize,
+    replace_uuid: Option<&str>,
+    origin: Option<&PackOrigin>,
+    pack_index: usize,
+    total_packs: usize,
+    storage_mode: StorageMode,
+    placed: &mut PlacedFiles,
+) -> Result<PackImportCounts, String> {
+    let pack_path_str = pack_folder.to_string_lossy().to_string();
+    let library_root = get_library_root(db);
+
+    // 교체 모드: 기존 팩의 UUID 사용
+    // 새로 추가 모드: 폴더 경로 기반 UUID 생성
+    let pack_uuid = if let Some(uuid) = replace_uuid {
+        uuid.to_string()
+    } else if let Some(uuid) = origin.and_then(|o| o.uuid.clone()) {
+        uuid
+    } else if origin.is_some() {
+        generate_pack_uuid(&format!("{}/{}", pack_path_str, pack_name))
+    } else {
+        generate_pack_uuid(&pack_path_str)
+    };
+
+    let taggers = ImportTaggers::load(db);
+    let genre = match origin {
+        Some(o) => o.genre.clone(),
+        None => parse_genre_from_path(&taggers.rules, &pack_path_str),
+    };
+
+    // 1. 파일 배치 + 분석 — 워커 풀에서 병렬 실행 (파일마다 디코딩은 최대 1회)
+    let done = AtomicUsize::new(0);
+    let results: Vec<Result<ImportedFile, String>> = audio_files
+        .par_iter()
+        .map(|src| -> Result<ImportedFile, String> {
+            job.check_cancelled()?;
+            let file = analyze_import_file(
+                src,
+                pack_name,
+                pack_folder,
+                dest_base,
+                storage_mode,
+                genre.as_deref(),
+                &taggers,
+            );
+
+            // 진행 상황 전송 (전역 인덱스 기준)
+            let n = done.fetch_add(1, Ordering::SeqCst) + 1;
+            let global_i = global_offset + n - 1;
+            if global_i % 5 == 0 || global_i + 1 == global_total {
+                job.progress(
+                    "import-progress",
+                    ImportProgress {
+                        current: global_i + 1,
+                        total: global_total,
+                        current_file: file.filename.clone(),
+                        current_pack: pack_index + 1,
+                        total_packs,
+                        current_pack_name: pack_name.to_string(),
+                        job_id: job.id,
+                    },
+                    global_i + 1,
+                    global_total,
+                    &file.filename,
+                );
+            }
+            Ok(file)
+        })
+        .collect();
+
+    // 취소/오류로 중단되어도 이미 배치한 파일은 정리되도록 먼저 기록
+    for file in results.iter().flatten() {
+        if file.placed == Ok(true) {
+            placed.push(file.dest_path.clone(), storage_mode);
+        }
+    }
+    let files: Vec<ImportedFile> = results.into_iter().collect::<Result<_, _>>()?;
+
+    // 2. DB 기록 — 팩 단위 트랜잭션에서 순차 실행 (파일 I/O 없음)
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+
+    // 팩 등록 (번들의 원래 팩이 이미 라이브러리에 있으면 그 팩 정보 유지)
+    let keep_existing = replace_uuid.is_none()
+        && origin.is_some()
+        && tx
+            .query_row("SELECT 1 FROM packs WHERE uuid = ?1", params![pack_uuid], |_| Ok(()))
+            .is_ok();
+    if !keep_existing {
+        tx.execute(
+            "INSERT OR REPLACE INTO packs (uuid, name, description, cover_url, genre, permalink, source, storage_mode, created_at)
+             VALUES (?1, ?2, ?3, NULL, ?4, NULL, 'external', ?5, datetime('now'))",
+            params![
+                pack_uuid,
+                pack_name,
+                format!("외부 임포트: {}", pack_name),
+                genre,
+                storage_mode.as_str()
+            ],
+        )
+        .map_err(|e| format!("팩 등록 실패: {}", e))?;
+    }
+
+    let mut counts = PackImportCounts::default();
+
+    // 교체 모드: 기존 샘플을 file_hash 또는 저장 경로로 찾음 (라이브러리를 옮겼으면 해시가 달라짐)
+    let mut existing_by_hash: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+    let mut existing_by_path: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+    if replace_uuid.is_some() {
+        let mut stmt = tx
+            .prepare("SELECT id, file_hash, local_path FROM samples WHERE pack_uuid = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![pack_uuid], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        for (id, hash, path) in rows.filter_map(|r| r.ok()) {
+            if let Some(hash) = hash {
+                existing_by_hash.insert(hash, id);
+            }
+            existing_by_path.insert(path, id);
+        }
+    }
+    let mut matched: HashSet<i64> = HashSet::new();
+
+    let mut insert = tx
+        .prepare_cached(
+            "INSERT OR IGNORE INTO samples
+             (local_path, filename, audio_key, bpm, chord_type, duration,
+              file_hash, genre, sample_type, tags, pack_uuid, file_size,
+              bpm_source, bpm_confidence, key_source, key_confidence,
+              sample_type_source, sample_type_confidence, genre_source, genre_confidence,
+              tags_source, tags_confidence, bars, bpm_mismatch, source, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12,
+                     ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24,
+                     'external', datetime('now'))",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut mark_suggested = tx
+        .prepare_cached(
+            "UPDATE sample_tags SET source = ?3, confidence = ?4
+             WHERE sample_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+        )
+        .map_err(|e| e.to_string())?;
+
+    for file in &files {
+        match &file.placed {
+            Ok(true) => counts.copied += 1,
+            Ok(false) => counts.skipped += 1,
+            // 배치하지 못한 파일은 없는 경로를 가리키는 행을 만들지 않음
+            Err(e) => {
+                counts.failed.push(e.clone());
+                continue;
+            }
+        }
+
+        // file_hash: dest_path 기반으로 생성
+        let dest_str = file.dest_path.to_string_lossy().to_string();
+        let mut hasher = DefaultHasher::new();
+        dest_str.hash(&mut hasher);
+        let file_hash = format!("ext-{:016x}", hasher.finish());
+        let stored_path = to_library_path(&library_root, &file.dest_path);
+
+        let existing_id = existing_by_hash
+            .get(&file_hash)
+            .or_else(|| existing_by_path.get(&stored_path))
+            .copied();
+        if let Some(sample_id) = existing_id {
+            if !matched.insert(sample_id) {
+                continue;
+            }
+            if update_imported_sample(&tx, sample_id, file, &stored_path).map_err(|e| e.to_string())? {
+                counts.updated += 1;
+            }
+            for (tag, source, confidence) in &file.suggested_tags {
+                mark_suggested
+                    .execute(params![sample_id, tag, source, confidence])
+                    .map_err(|e| e.to_string())?;
+            }
+            continue;
+        }
+
+        let inserted = insert
+            .execute(params![
+                stored_path,
+                file.filename,
+                file.key.as_ref().map(|k| &k.0),
+                file.bpm.map(|b| b.0),
+                file.key.as_ref().and_then(|k| k.1.as_ref()),
+                file.duration_ms,
+                file_hash,
+                file.genre.as_ref().map(|g| &g.0),
+                file.sample_type.0,
+                file.tags.as_ref().map(|t| &t.0),
+                pack_uuid,
+                file.file_size,
+                file.bpm.map(|b| b.1),
+                file.bpm.map(|b| b.2),
+                file.key.as_ref().map(|k| k.2),
+                file.key.as_ref().map(|k| k.3),
+                file.sample_type.1,
+                file.sample_type.2,
+                file.genre.as_ref().map(|g| g.1),
+                file.genre.as_ref().map(|g| g.2),
+                file.tags.as_ref().map(|t| t.1),
+                file.tags.as_ref().map(|t| t.2),
+                file.bars,
+                file.bpm_mismatch
+            ])
+            .map_err(|e| e.to_string())?;
+        if inserted > 0 {
+            let sample_id = tx.last_insert_rowid();
+            for (tag, source, confidence) in &file.suggested_tags {
+                mark_suggested
+                    .execute(params![sample_id, tag, source, confidence])
+                    .map_err(|e| e.to_string())?;
+            }
+            if !file.markers.is_empty() {
+                store_sample_markers(&tx, sample_id, &file.markers).map_err(|e| e.to_string())?;
+            }
+        }
+        counts.added += inserted;
+    }
+
+    // 교체 모드: 새 폴더에 없는 샘플만 삭제
+    let disappeared: HashSet<i64> = existing_by_path
+        .values()
+        .copied()
+        .filter(|id| !matched.contains(id))
+        .collect();
+    for id in disappeared {
+        tx.execute("DELETE FROM playlist_samples WHERE sample_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM samples WHERE id = ?1", params![id])
+            .map_err(|e| format!("기존 샘플 삭제 실패: {}", e))?;
+        counts.removed += 1;
+    }
+
+    drop(insert);
+    drop(mark_suggested);
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(counts)
+}
+
+/// 외부 폴더에서 샘플팩 임포트
+/// selected_paths: 팩으로 임포트할 폴더 경로 목록 (ZIP 파일, ZIP 안 폴더 "…/Pack.zip/Drums" 포함)
+/// replace_map: 팩 이름 → 기존 팩 UUID (교체할 팩 매핑, 없으면 새로 추가 — 이름은 check_pack_name_conflicts 결과)
+/// storage_mode: 파일 저장 방식 (없으면 copy)
+/// 백그라운드 작업으로 실행 — 팩 단위로 커밋하므로 취소하면 진행 중이던 팩만 롤백하고
+/// 그때까지 끝난 팩의 결과를 반환
+#[tauri::command]
+async fn import_external_folder(
+    selected_paths: Vec<String>,
+    replace_map: std::collections::HashMap<String, String>,
+    storage_mode: Option<StorageMode>,
+    app: tauri::AppHandle,
+) -> Result<ImportResult, String> {
+    if selected_paths.is_empty() {
+        return Err("임포트할 폴더를 선택해주세요".to_string());
+    }
+    run_job(app, JobKind::ExternalImport, move |job| {
+        import_external_folder_job(&selected_paths, &replace_map, storage_mode, job)
+    })
+    .await
+}
+
+fn import_external_folder_job(
+    selected_paths: &[String],
+    replace_map: &std::collections::HashMap<String, String>,
+    storage_mode: Option<StorageMode>,
+    job: &JobHandle,
+) -> Result<ImportResult, String> {
+    let state = job.app.state::<AppState>();
+    let _busy = BusyGuard::new(&state.busy);
+
+    let packs = collect_import_packs(selected_paths)?;
+    if packs.is_empty() {
+        return Err("선택된 폴더에서 오디오 파일을 찾을 수 없습니다".to_string());
+    }
+
+    let total_packs = packs.len();
+    let global_total: usize = packs.iter().map(|p| p.files.len()).sum();
+
+    let mut total_copied = 0usize;
     let mut total_skipped = 0usize;
+    let mut total_added = 0usize;
+    let mut total_updated = 0usize;
+    let mut total_removed = 0usize;
+    let mut failed: Vec<String> = Vec::new();
     let mut global_offset = 0usize;
+    let mut imported_packs = 0usize;
+
+    let mut db = open_job_db()?;
+    let slice_dir = get_library_root(&db);
+
+    for (pack_idx, pack) in packs.iter().enumerate() {
+        // 사이드카의 팩 이름은 경로 구분자를 포함할 수 있으므로 폴더 이름으로 정리
+        let dir_name = pack.name.replace(['/', '\\'], "_");
+        let dest_base = slice_dir.join("External").join(dir_name.trim_start_matches('.'));
+        let file_count = pack.files.len();
+        let replace_uuid = replace_map.get(&pack.name).map(|s| s.as_str());
+
+        // 팩 단위로 커밋 — 실패/취소 시 이 팩의 DB 변경과 새로 배치한 파일만 되돌림
+        let mut placed = PlacedFiles::new();
+
+        // ZIP이면 오디오 파일을 먼저 풀고, 풀린 파일을 제자리에서 임포트 (항상 복사 저장)
+        let extracted_files;
+        let (pack_folder, files, pack_storage, extracted) = match &pack.archive {
+            Some(archive) => match extract_archive_pack(pack, archive, &dest_base, job, &mut placed) {
+                Ok((files, new, existing)) => {
+                    extracted_files = files;
+                    (dest_base.as_path(), extracted_files.as_slice(), StorageMode::Copy, Some((new, existing)))
+                }
+                Err(_) if job.is_cancelled() => break,
+                Err(e) => return Err(e),
+            },
+            None => (
+                pack.folder.as_path(),
+                pack.files.as_slice(),
+                storage_mode.unwrap_or_default(),
+                None,
+            ),
+        };
+        let imported = import_single_pack(
+            &pack.name,
+            pack_folder,
+            files,
+            &dest_base,
+            &mut db,
+            job,
+            global_offset,
+            global_total,
+            replace_uuid,
+            pack.origin.as_ref(),
+            pack_idx,
+            total_packs,
+            pack_storage,
+            &mut placed,
+        );
+        let counts = match imported {
+            Ok(counts) => counts,
+            Err(_) if job.is_cancelled() => break,
+            Err(e) => return Err(e),
+        };
+        // ZIP에서 푼 파일은 푼 시점 기준으로 새 파일/기존 파일 수를 셈
+        let (copied, skipped) = extracted.unwrap_or((counts.copied, counts.skipped));
+        placed.keep();
+
+        total_copied += copied;
+        total_skipped += skipped;
+        total_added += counts.added;
+        total_updated += counts.updated;
+        total_removed += counts.removed;
+        failed.extend(counts.failed);
+        global_offset += file_count;
+        imported_packs += 1;
+    }
+
+    Ok(ImportResult {
+        files_copied: total_copied,
+        files_skipped: total_skipped,
+        total_packs: imported_packs,
+        added: total_added,
+        updated: total_updated,
+        removed: total_removed,
+        failed,
+    })
+}
+
+/// 선택한 폴더/ZIP 경로의 오디오 파일 → 임포트할 팩 목록 (내보내기 번들이면 원래 팩별로 나눔)
+/// 충돌 확인과 임포트가 같은 팩 이름을 쓰도록 둘 다 여기서 수집
+fn collect_import_packs(selected_paths: &[String]) -> Result<Vec<ImportPack>, String> {
+    let mut packs: Vec<ImportPack> = Vec::new();
+    for path_str in selected_paths {
+        let folder = PathBuf::from(path_str);
+        // ZIP 파일 또는 그 안의 폴더
+        if let Some((archive, inner)) = split_archive_path(&folder) {
+            packs.extend(archive_import_packs(&archive, &inner)?);
+            continue;
+        }
+        if !folder.is_dir() {
+            continue;
+        }
+        let name = folder
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown Pack")
+            .to_string();
+        let audio_files = collect_audio_files(&folder)
+            .into_iter()
+            .map(ImportSource::from_file)
+            .collect();
+        packs.extend(split_import_packs(&name, &folder, audio_files, None));
+    }
+    Ok(packs)
+}
+
+/// Waveform 데이터 반환 (DB 캐시 사용, peaks + frequency colors)
+#[tauri::command]
+fn get_waveform(path: String, state: State<AppState>) -> Result<WaveformData, String> {
+    // 프론트엔드는 절대 경로를 넘기므로 DB 저장 형식으로 변환
+    let stored_path = {
+        let db = state.db.lock().unwrap();
+        to_library_path(&get_library_root(&db), Path::new(&path))
+    };
+
+    // Check DB cache — peaks와 colors 모두 있어야 캐시 히트
+    {
+        let db = state.db.lock().unwrap();
+        if let Ok((peaks_json, colors_json, dur_opt)) = db.query_row(
+            "SELECT waveform_peaks, waveform_colors, duration FROM samples WHERE local_path = ?1",
+            params![&stored_path],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                ))
+            },
+        ) {
+            if let (Some(peaks_str), Some(colors_str)) = (&peaks_json, &colors_json) {
+                if let (Ok(peaks), Ok(colors)) = (
+                    serde_json::from_str::<Vec<f32>>(peaks_str),
+                    serde_json::from_str::<Vec<[f32; 3]>>(colors_str),
+                ) {
+                    let duration_secs = dur_opt.map(|d| d as f64 / 1000.0).unwrap_or(0.0);
+                    return Ok(WaveformData {
+                        peaks,
+                        colors,
+                        duration_secs,
+                    });
+                }
+            }
+        }
+    } // release lock before expensive computation
+
+    // Compute waveform (peaks + frequency colors)
+    let waveform = compute_waveform_internal(&path, 128)?;
+
+    // Store in DB cache
+    {
+        let db = state.db.lock().unwrap();
+        if let (Ok(peaks_json), Ok(colors_json)) = (
+            serde_json::to_string(&waveform.peaks),
+            serde_json::to_string(&waveform.colors),
+        ) {
+            let _ = db.execute(
+                "UPDATE samples SET waveform_peaks = ?1, waveform_colors = ?2 WHERE local_path = ?3",
+                params![peaks_json, colors_json, &stored_path],
+            );
+        }
+    }
+
+    Ok(waveform)
+}
+
+// ── Library health / relink ─────────────────────────────────────────
+
+/// 오디오 파일이 실제로 디코딩되는지 확인 (앞쪽 패킷 몇 개만 디코딩)
+fn check_audio_decodable(file_path: &str) -> Result<(), String> {
+    let file = std::fs::File::open(file_path).map_err(|e| format!("파일 열기 실패: {}", e))?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = Path::new(file_path).extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| format!("포맷 프로브 실패: {}", e))?;
+
+    let mut format = probed.format;
+    let track = format
+        .default_track()
+        .ok_or_else(|| "기본 트랙을 찾을 수 없습니다".to_string())?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("디코더 생성 실패: {}", e))?;
+
+    for _ in 0..16 {
+        let packet = format
+            .next_packet()
+            .map_err(|e| format!("패킷 읽기 실패: {}", e))?;
+        if packet.track_id() != track_id {
+            continue;
+        }
+        match decoder.decode(&packet) {
+            Ok(_) => return Ok(()),
+            Err(symphonia::core::errors::Error::DecodeError(_)) => continue,
+            Err(e) => return Err(format!("디코딩 실패: {}", e)),
+        }
+    }
+    Err("디코딩 가능한 오디오 데이터가 없습니다".to_string())
+}
+
+/// 라이브러리 상태 점검: 누락/0바이트/디코딩 불가 파일 탐지
+/// 정상 파일의 크기는 재연결 비교용으로 DB에 기록. `health-progress` 이벤트 발행
+/// 백그라운드 스레드에서 실행
+#[tauri::command]
+async fn scan_library_health(app: tauri::AppHandle) -> Result<HealthReport, String> {
+    tauri::async_runtime::spawn_blocking(move || scan_library_health_blocking(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn scan_library_health_blocking(app: &tauri::AppHandle) -> Result<HealthReport, String> {
+    let state = app.state::<AppState>();
+    let rows: Vec<(i64, String, String, Option<String>)> = {
+        let db = state.db.lock().unwrap();
+        let root = get_library_root(&db);
+        let mut stmt = db
+            .prepare(
+                "SELECT s.id, s.filename, s.local_path, p.name
+                 FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                 ORDER BY s.id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    resolve_library_path(&root, &row.get::<_, String>(2)?),
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    }; // 파일 검사 중에는 DB lock 해제
+
+    let total = rows.len();
+    let mut issues = Vec::new();
+    let mut sizes: Vec<(i64, i64)> = Vec::new();
+
+    for (i, (id, filename, path, pack_name)) in rows.iter().enumerate() {
+        let issue = match std::fs::metadata(path) {
+            Err(_) => Some(("missing", None)),
+            Ok(meta) if meta.len() == 0 => Some(("empty", None)),
+            Ok(meta) => match check_audio_decodable(path) {
+                Ok(()) => {
+                    sizes.push((*id, meta.len() as i64));
+                    None
+                }
+                Err(e) => Some(("undecodable", Some(e))),
+            },
+        };
+        if let Some((kind, detail)) = issue {
+            issues.push(HealthIssue {
+                sample_id: *id,
+                filename: filename.clone(),
+                local_path: path.clone(),
+                pack_name: pack_name.clone(),
+                issue: kind.to_string(),
+                detail,
+            });
+        }
+
+        if i % 20 == 0 || i + 1 == total {
+            let _ = app.emit(
+                "health-progress",
+                ScanProgress {
+                    current: i + 1,
+                    total,
+                    current_file: filename.clone(),
+                },
+            );
+        }
+    }
+
+    {
+        let mut db = state.db.lock().unwrap();
+        let tx = db.transaction().map_err(|e| e.to_string())?;
+        for (id, size) in &sizes {
+            tx.execute(
+                "UPDATE samples SET file_size = ?1 WHERE id = ?2",
+                params![size, id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(HealthReport {
+        scanned: total,
+        issues,
+    })
+}
+
+/// 파형 피크 유사도 (피어슨 상관계수, 0.0–1.0으로 클램프)
+fn peaks_similarity(a: &[f32], b: &[f32]) -> f32 {
+    pearson_correlation(a, b).clamp(0.0, 1.0)
+}
+
+/// 누락된 샘플의 재연결 후보 탐색
+/// search_dir 아래 오디오 파일을 파일명/크기로 추린 뒤, 파형 지문(캐시된 피크 + 길이)으로 점수화
+/// 결과는 적용 전에 사용자가 검토 (`apply_relinks`). `relink-progress` 이벤트 발행
+/// 백그라운드 스레드에서 실행
+#[tauri::command]
+async fn find_relink_candidates(
+    sample_ids: Vec<i64>,
+    search_dir: String,
+    app: tauri::AppHandle,
+) -> Result<Vec<RelinkProposal>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        find_relink_candidates_blocking(&sample_ids, &search_dir, &app)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn find_relink_candidates_blocking(
+    sample_ids: &[i64],
+    search_dir: &str,
+    app: &tauri::AppHandle,
+) -> Result<Vec<RelinkProposal>, String> {
+    let state = app.state::<AppState>();
+    let search_root = PathBuf::from(search_dir);
+    if !search_root.is_dir() {
+        return Err("유효한 폴더가 아닙니다".to_string());
+    }
+
+    struct Missing {
+        id: i64,
+        filename: String,
+        path: String,
+        size: Option<i64>,
+        duration: Option<i64>,
+        peaks: Option<Vec<f32>>,
+    }
+
+    let missing: Vec<Missing> = {
+        let db = state.db.lock().unwrap();
+        let root = get_library_root(&db);
+        let mut out = Vec::new();
+        for id in sample_ids {
+            let row = db.query_row(
+                "SELECT filename, local_path, file_size, duration, waveform_peaks FROM samples WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(Missing {
+                        id: *id,
+                        filename: row.get(0)?,
+                        path: resolve_library_path(&root, &row.get::<_, String>(1)?),
+                        size: row.get(2)?,
+                        duration: row.get(3)?,
+                        peaks: row
+                            .get::<_, Option<String>>(4)?
+                            .and_then(|json| serde_json::from_str(&json).ok()),
+                    })
+                },
+            );
+            if let Ok(m) = row {
+                out.push(m);
+            }
+        }
+        out
+    };
+
+    // 검색 폴더 인덱스: 소문자 파일명 → 경로들, 크기 → 경로들
+    let files = collect_audio_files(&search_root);
+    let mut by_name: std::collections::HashMap<String, Vec<PathBuf>> = std::collections::HashMap::new();
+    let mut by_size: std::collections::HashMap<i64, Vec<PathBuf>> = std::collections::HashMap::new();
+    for f in &files {
+        if let Some(name) = f.file_name().and_then(|n| n.to_str()) {
+            by_name.entry(name.to_lowercase()).or_default().push(f.clone());
+        }
+        if let Some(size) = file_size_of(&f.to_string_lossy()) {
+            by_size.entry(size).or_default().push(f.clone());
+        }
+    }
+
+    let total = missing.len();
+    let mut proposals = Vec::new();
+
+    for (i, m) in missing.iter().enumerate() {
+        let mut pool: Vec<PathBuf> = by_name
+            .get(&m.filename.to_lowercase())
+            .cloned()
+            .unwrap_or_default();
+        if let Some(size) = m.size {
+            for p in by_size.get(&size).into_iter().flatten() {
+                if !pool.contains(p) {
+                    pool.push(p.clone());
+                }
+            }
+        }
+
+        let mut candidates: Vec<RelinkCandidate> = pool
+            .iter()
+            .map(|p| {
+                let path_str = p.to_string_lossy().to_string();
+                let name_match = p
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.eq_ignore_ascii_case(&m.filename))
+                    .unwrap_or(false);
+                let size_match = m.size.is_some() && file_size_of(&path_str) == m.size;
+
+                // 지문: 캐시된 파형 피크가 있으면 상관계수, 없으면 길이 일치도
+                let fingerprint_score = match &m.peaks {
+                    Some(peaks) => compute_waveform_internal(&path_str, peaks.len())
+                        .ok()
+                        .map(|w| peaks_similarity(peaks, &w.peaks)),
+                    None => m.duration.and_then(|d| {
+                        let cand = compute_duration_ms(&path_str)?;
+                        let diff = (cand - d).abs() as f32 / d.max(1) as f32;
+                        Some((1.0 - diff * 10.0).clamp(0.0, 1.0))
+                    }),
+                };
+
+                let score = if name_match { 0.4 } else { 0.0 }
+                    + if size_match { 0.3 } else { 0.0 }
+                    + fingerprint_score.unwrap_or(0.0) * 0.3;
+                RelinkCandidate {
+                    path: path_str,
+                    score,
+                    name_match,
+                    size_match,
+                    fingerprint_score,
+                }
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        candidates.truncate(5);
+
+        if !candidates.is_empty() {
+            proposals.push(RelinkProposal {
+                sample_id: m.id,
+                filename: m.filename.clone(),
+                old_path: m.path.clone(),
+                candidates,
+            });
+        }
+
+        if i % 5 == 0 || i + 1 == total {
+            let _ = app.emit(
+                "relink-progress",
+                ScanProgress {
+                    current: i + 1,
+                    total,
+                    current_file: m.filename.clone(),
+                },
+            );
+        }
+    }
+
+    Ok(proposals)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RelinkChoice {
+    pub sample_id: i64,
+    pub new_path: String,
+}
+
+/// 검토한 재연결을 한 트랜잭션으로 적용 — 적용된 샘플 수 반환
+/// 라이브러리 밖 파일은 팩 저장 방식대로 원래 라이브러리 위치에 복사/링크하고,
+/// reference 팩이거나 원래 위치가 라이브러리 밖/사용 중이면 제자리 참조로 저장
+/// (라이브러리 밖 파일은 샘플/팩을 삭제해도 지워지지 않음)
+#[tauri::command]
+fn apply_relinks(relinks: Vec<RelinkChoice>, state: State<AppState>) -> Result<usize, String> {
+    for r in &relinks {
+        if !Path::new(&r.new_path).is_file() {
+            return Err(format!("파일을 찾을 수 없습니다: {}", r.new_path));
+        }
+    }
+
+    // 1. 기존 경로 + 팩 저장 방식 조회
+    let (root, targets) = {
+        let db = state.db.lock().unwrap();
+        let root = get_library_root(&db);
+        let mut stmt = db
+            .prepare(
+                "SELECT s.local_path, p.storage_mode
+                 FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                 WHERE s.id = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut targets: Vec<(&RelinkChoice, PathBuf, StorageMode)> = Vec::new();
+        for r in &relinks {
+            let found = stmt.query_row(params![r.sample_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    StorageMode::parse(row.get::<_, Option<String>>(1)?.as_deref()),
+                ))
+            });
+            if let Ok((old_path, mode)) = found {
+                targets.push((r, PathBuf::from(resolve_library_path(&root, &old_path)), mode));
+            }
+        }
+        (root, targets)
+    }; // DB lock released
+
+    // 2. 라이브러리 밖 파일을 원래 위치에 배치 (DB 잠금 밖에서)
+    let mut placed = PlacedFiles::new();
+    let mut new_paths: Vec<(i64, PathBuf)> = Vec::with_capacity(targets.len());
+    for (r, old_path, mode) in targets {
+        let new_path = PathBuf::from(&r.new_path);
+        let into_library = mode != StorageMode::Reference
+            && !new_path.starts_with(&root)
+            && old_path.starts_with(&root)
+            && !old_path.exists();
+        let stored = if into_library {
+            let (dest, newly) = place_library_file(&new_path, &old_path, mode)?;
+            if newly {
+                placed.push(dest.clone(), mode);
+            }
+            dest
+        } else {
+            new_path
+        };
+        new_paths.push((r.sample_id, stored));
+    }
+
+    // 3. 경로 교체 + 이전 파일 기준 파형 캐시 삭제
+    let mut db = state.db.lock().unwrap();
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    let mut applied = 0usize;
+    for (sample_id, path) in &new_paths {
+        applied += tx
+            .execute(
+                "UPDATE samples SET local_path = ?1, file_size = ?2,
+                        waveform_peaks = NULL, waveform_colors = NULL
+                 WHERE id = ?3",
+                params![
+                    to_library_path(&root, path),
+                    file_size_of(&path.to_string_lossy()),
+                    sample_id
+                ],
+            )
+            .map_err(|e| format!("재연결 실패: {}", e))?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    placed.keep();
+    Ok(applied)
+}
+
+/// 기존 샘플의 키를 오디오 분석으로 감지
+/// 키가 비어 있는 샘플에만 적용 (overwrite=true면 기존 키도 교체). 사용자가 편집한 키는 건드리지 않음
+/// 원샷/타악기 샘플은 감지하지 않음
+/// 신뢰도가 KEY_MIN_CONFIDENCE 미만이면 결과만 반환. `key-progress` 이벤트 발행
+/// 백그라운드 스레드에서 실행
+#[tauri::command]
+async fn detect_sample_keys(
+    sample_ids: Vec<i64>,
+    overwrite: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<Vec<KeyDetection>, String> {
+    let overwrite = overwrite.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || {
+        detect_sample_keys_blocking(&sample_ids, overwrite, &app)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn detect_sample_keys_blocking(
+    sample_ids: &[i64],
+    overwrite: bool,
+    app: &tauri::AppHandle,
+) -> Result<Vec<KeyDetection>, String> {
+    let state = app.state::<AppState>();
+    let rows: Vec<(i64, String, String, bool)> = {
+        let db = state.db.lock().unwrap();
+        let root = get_library_root(&db);
+        let mut stmt = db
+            .prepare(
+                "SELECT filename, local_path, audio_key IS NOT NULL AND audio_key != '',
+                        sample_type, tags
+                 FROM samples WHERE id = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        sample_ids
+            .iter()
+            .filter_map(|id| {
+                stmt.query_row(params![id], |row| {
+                    Ok((
+                        *id,
+                        row.get::<_, String>(0)?,
+                        resolve_library_path(&root, &row.get::<_, String>(1)?),
+                        row.get::<_, bool>(2)?,
+                        should_detect_key(
+                            row.get::<_, Option<String>>(3)?.as_deref(),
+                            row.get::<_, Option<String>>(4)?.as_deref(),
+                        ),
+                    ))
+                })
+                .ok()
+            })
+            .filter(|(_, _, _, _, pitched)| *pitched)
+            .map(|(id, filename, path, has_key, _)| (id, filename, path, has_key))
+            .collect()
+    }; // 분석 중에는 DB lock 해제
+
+    let total = rows.len();
+    let mut results = Vec::new();
+    let mut updates: Vec<(i64, String, String, f32)> = Vec::new();
+
+    for (i, (id, filename, path, has_key)) in rows.iter().enumerate() {
+        let _ = app.emit(
+            "key-progress",
+            ScanProgress {
+                current: i + 1,
+                total,
+                current_file: filename.clone(),
+            },
+        );
+
+        let Some((key, chord, confidence)) = detect_key_from_audio(path) else {
+            continue;
+        };
+        let apply = confidence >= KEY_MIN_CONFIDENCE && (overwrite || !has_key);
+        if apply {
+            updates.push((*id, key.clone(), chord.clone(), confidence));
+        }
+        results.push(KeyDetection {
+            sample_id: *id,
+            audio_key: Some(key),
+            chord_type: Some(chord),
+            confidence,
+            applied: apply,
+        });
+    }
+
+    let mut db = state.db.lock().unwrap();
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    for (id, key, chord, confidence) in &updates {
+        let changed = write_sample_field(
+            &tx,
+            *id,
+            MetaField::Key,
+            &[key, chord],
+            Some(SOURCE_AUDIO),
+            Some(*confidence),
+        )
+        .map_err(|e| e.to_string())?;
+        if changed == 0 {
+            if let Some(r) = results.iter_mut().find(|r| r.sample_id == *id) {
+                r.applied = false;
+            }
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(results)
+}
+
+/// 재분석 대상 샘플 (분석 입력 + 변경 전 값/출처)
+struct ReanalyzeTarget {
+    id: i64,
+    filename: String,
+    path: String,
+    parse_path: String,
+    pack_genre: Option<String>,
+    duration: Option<i64>,
+    before: [(Option<String>, Option<String>); 5], // META_FIELDS 순서 (표시 값, 출처)
+}
+
+/// 분석기 결과 값
+enum AnalyzedValue {
+    /// (BPM, 마디 수, 파일명 BPM/길이 모순)
+    Bpm(f64, Option<i32>, bool),
+    Key(String, Option<String>),
+    Text(String),
+}
+
+impl AnalyzedValue {
+    fn display(&self) -> String {
+        match self {
+            AnalyzedValue::Bpm(b, _, _) => format_bpm(*b),
+            AnalyzedValue::Key(key, Some(chord)) => format!("{} {}", key, chord),
+            AnalyzedValue::Key(key, None) => key.clone(),
+            AnalyzedValue::Text(t) => t.clone(),
+        }
+    }
+
+    fn values(&self) -> Vec<&dyn rusqlite::ToSql> {
+        match self {
+            AnalyzedValue::Bpm(b, bars, mismatch) => vec![b, bars, mismatch],
+            AnalyzedValue::Key(key, chord) => vec![key, chord],
+            AnalyzedValue::Text(t) => vec![t],
+        }
+    }
+}
+
+/// 적용할 분석 결과 (필드, 값, 출처, 신뢰도) — 값이 None이면 규칙으로 채웠던 값을 지움
+type FieldWrite = (MetaField, Option<AnalyzedValue>, &'static str, f32);
+
+/// 파일명 파서용 경로 — 임포트 때처럼 "팩 이름/팩 내부 경로" 형태로 복원
+fn reanalysis_parse_path(pack_name: Option<&str>, abs_path: &str, filename: &str) -> String {
+    let Some(pack) = pack_name else {
+        return filename.to_string();
+    };
+    let normalized = abs_path.replace('\\', "/");
+    match normalized.rfind(&format!("/{}/", pack)) {
+        Some(pos) => normalized[pos + 1..].to_string(),
+        None => format!("{}/{}", pack, filename),
+    }
+}
+
+/// 분석기 하나 실행 → (값, 출처, 신뢰도). 결과가 없으면 None (기존 값 유지)
+fn run_analyzer(
+    field: MetaField,
+    t: &ReanalyzeTarget,
+    audio: &AnalysisContext,
+    rules: &MetadataRuleSet,
+) -> Option<(AnalyzedValue, &'static str, f32)> {
+    match field {
+        MetaField::Bpm => {
+            let sample_type = t.before[2].0.as_deref();
+            audio
+                .embedded()
+                .bpm
+                .map(|b| (b, SOURCE_EMBEDDED, CONFIDENCE_EMBEDDED))
+                .or_else(|| {
+                    parse_bpm_from_filename(&t.parse_path)
+                        .map(|b| (b, SOURCE_FILENAME, CONFIDENCE_FILENAME))
+                })
+                .or_else(|| {
+                    if t.duration.map(|d| d >= 2000).unwrap_or(false) {
+                        let loop_duration = t.duration.filter(|_| sample_type == Some("loop"));
+                        audio
+                            .detect_bpm(loop_duration)
+                            .map(|b| (b, SOURCE_AUDIO, CONFIDENCE_AUDIO_BPM))
+                    } else {
+                        None
+                    }
+                })
+                .map(|(b, source, confidence)| {
+                    let (bars, mismatch) =
+                        loop_length_check(Some(b), Some(source), t.duration, sample_type);
+                    (AnalyzedValue::Bpm(b, bars, mismatch), source, confidence)
+                })
+        }
+        MetaField::Key => match (audio.embedded().key.clone(), parse_key_from_filename(&t.parse_path)) {
+            (Some((key, chord)), _) => {
+                Some((AnalyzedValue::Key(key, chord), SOURCE_EMBEDDED, CONFIDENCE_EMBEDDED))
+            }
+            (None, Some(key)) => Some((AnalyzedValue::Key(key, None), SOURCE_FILENAME, CONFIDENCE_FILENAME)),
+            (None, None) if !should_detect_key(t.before[2].0.as_deref(), t.before[4].0.as_deref()) => None,
+            (None, None) => audio
+                .detect_key()
+                .filter(|(_, _, conf)| *conf >= KEY_MIN_CONFIDENCE)
+                .map(|(key, chord, conf)| (AnalyzedValue::Key(key, Some(chord)), SOURCE_AUDIO, conf)),
+        },
+        MetaField::SampleType => {
+            let (sample_type, source, conf) = match audio.embedded().sample_type {
+                Some(t) => (t.to_string(), SOURCE_EMBEDDED, CONFIDENCE_EMBEDDED),
+                None => parse_sample_type(&t.parse_path, t.duration, Some(audio)),
+            };
+            Some((AnalyzedValue::Text(sample_type), source, conf))
+        }
+        MetaField::Genre => audio
+            .embedded()
+            .genre
+            .clone()
+            .map(|g| (AnalyzedValue::Text(g), SOURCE_EMBEDDED, CONFIDENCE_EMBEDDED))
+            .or_else(|| {
+                parse_genre_from_path(rules, &t.parse_path)
+                    .map(|g| (AnalyzedValue::Text(g), SOURCE_PATH, CONFIDENCE_PATH))
+            })
+            .or_else(|| {
+                t.pack_genre
+                    .clone()
+                    .map(|g| (AnalyzedValue::Text(g), SOURCE_PACK, CONFIDENCE_PACK))
+            }),
+        MetaField::Tags => {
+            let mut tags = parse_tags_from_path(rules, &t.parse_path, &t.filename);
+            let oneshot = t.before[2].0.as_deref() == Some("oneshot");
+            if oneshot && t.duration.is_some_and(|d| d <= DRUM_HIT_MAX_MS) {
+                merge_drum_hit_tag(rules, &mut tags, audio);
+            }
+            (!tags.is_empty())
+                .then(|| (AnalyzedValue::Text(tags.join(",")), SOURCE_PATH, CONFIDENCE_PATH))
+        }
+    }
+}
+
+/// 기존 샘플에 분석기를 다시 실행 (sample_ids 또는 pack_uuid 대상)
+/// analyzers: "bpm" | "key" | "sample_type" | "genre" | "tags"
+/// 사용자가 편집한 필드와 Splice DB 값은 보존하고 diff에 applied=false로 표시
+/// 백그라운드 스레드에서 실행, `reanalyze-progress` 이벤트 발행
+#[tauri::command]
+async fn reanalyze_samples(
+    sample_ids: Option<Vec<i64>>,
+    pack_uuid: Option<String>,
+    analyzers: Vec<String>,
+    app: tauri::AppHandle,
+) -> Result<Vec<ReanalyzeDiff>, String> {
+    let fields: Vec<MetaField> = analyzers
+        .iter()
+        .map(|a| MetaField::parse(a).ok_or_else(|| format!("알 수 없는 분석기: {}", a)))
+        .collect::<Result<_, _>>()?;
+    if fields.is_empty() {
+        return Err("실행할 분석기가 없습니다".to_string());
+    }
+    if sample_ids.is_none() && pack_uuid.is_none() {
+        return Err("재분석할 샘플 또는 팩을 지정하세요".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        reanalyze_samples_blocking(sample_ids, pack_uuid, &fields, &app)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 재분석 대상 샘플 (sample_ids 우선, 없으면 pack_uuid, 둘 다 없으면 전체)
+fn load_reanalyze_targets(
+    db: &Connection,
+    sample_ids: Option<&[i64]>,
+    pack_uuid: Option<&str>,
+) -> Result<Vec<ReanalyzeTarget>, String> {
+    let root = get_library_root(db);
+    let (filter, args): (String, Vec<rusqlite::types::Value>) = match (sample_ids, pack_uuid) {
+        (Some(ids), _) => (
+            format!(
+                "s.id IN ({})",
+                ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ")
+            ),
+            ids.iter().map(|id| rusqlite::types::Value::Integer(*id)).collect(),
+        ),
+        (None, Some(uuid)) => (
+            "s.pack_uuid = ?".to_string(),
+            vec![rusqlite::types::Value::Text(uuid.to_string())],
+        ),
+        (None, None) => ("1".to_string(), Vec::new()),
+    };
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT s.id, s.filename, s.local_path, p.name, p.genre, s.duration,
+                    s.bpm, s.audio_key, s.chord_type, s.sample_type, s.genre, s.tags,
+                    s.bpm_source, s.key_source, s.sample_type_source, s.genre_source, s.tags_source
+             FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE {}
+             ORDER BY s.id",
+            filter
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(args), |row| {
+            let filename: String = row.get(1)?;
+            let path = resolve_library_path(&root, &row.get::<_, String>(2)?);
+            let pack_name: Option<String> = row.get(3)?;
+            let key = row.get::<_, Option<String>>(7)?.map(|k| {
+                match row.get::<_, Option<String>>(8).ok().flatten() {
+                    Some(chord) => format!("{} {}", k, chord),
+                    None => k,
+                }
+            });
+            Ok(ReanalyzeTarget {
+                id: row.get(0)?,
+                parse_path: reanalysis_parse_path(pack_name.as_deref(), &path, &filename),
+                filename,
+                path,
+                pack_genre: row.get(4)?,
+                duration: row.get(5)?,
+                before: [
+                    (row.get::<_, Option<f64>>(6)?.map(format_bpm), row.get(12)?),
+                    (key, row.get(13)?),
+                    (row.get(9)?, row.get(14)?),
+                    (row.get(10)?, row.get(15)?),
+                    (row.get(11)?, row.get(16)?),
+                ],
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(rows)
+}
+
+/// 분석기 결과와 저장된 값 비교 → (변경 내역, 적용할 쓰기)
+fn analyze_changes(
+    t: &ReanalyzeTarget,
+    fields: &[MetaField],
+    audio: &AnalysisContext,
+    rules: &MetadataRuleSet,
+) -> (Vec<FieldChange>, Vec<FieldWrite>) {
+    let mut changes = Vec::new();
+    let mut writes = Vec::new();
+    for &field in fields {
+        let idx = META_FIELDS.iter().position(|f| *f == field).unwrap_or(0);
+        let (before, before_source) = &t.before[idx];
+        let Some((value, source, confidence)) = run_analyzer(field, t, audio, rules) else {
+            // 규칙(경로 키워드)으로 채운 장르/태그인데 이제 규칙이 아무것도 주지 않으면 지움
+            // (규칙을 삭제하거나 좁혔을 때 이전 결과가 남지 않도록)
+            let rule_sourced = matches!(field, MetaField::Genre | MetaField::Tags)
+                && before_source.as_deref() == Some(SOURCE_PATH);
+            if rule_sourced && before.is_some() {
+                changes.push(FieldChange {
+                    field: field.as_str().to_string(),
+                    before: before.clone(),
+                    after: None,
+                    source: SOURCE_PATH.to_string(),
+                    confidence: 0.0,
+                    applied: true,
+                });
+                writes.push((field, None, SOURCE_PATH, 0.0));
+            }
+            continue;
+        };
+        let after = value.display();
+        if before.as_deref() == Some(after.as_str()) && before_source.as_deref() == Some(source) {
+            continue;
+        }
+        // 사용자 편집 값, Splice DB 값, 내보내기 번들에서 복원한 값은 휴리스틱으로 덮어쓰지 않음
+        let protected = matches!(
+            before_source.as_deref(),
+            Some(SOURCE_USER) | Some(SOURCE_SPLICE) | Some(SOURCE_SIDECAR)
+        );
+        changes.push(FieldChange {
+            field: field.as_str().to_string(),
+            before: before.clone(),
+            after: Some(after),
+            source: source.to_string(),
+            confidence,
+            applied: !protected,
+        });
+        if !protected {
+            writes.push((field, Some(value), source, confidence));
+        }
+    }
+    (changes, writes)
+}
+
+fn reanalyze_samples_blocking(
+    sample_ids: Option<Vec<i64>>,
+    pack_uuid: Option<String>,
+    fields: &[MetaField],
+    app: &tauri::AppHandle,
+) -> Result<Vec<ReanalyzeDiff>, String> {
+    let state = app.state::<AppState>();
+
+    let (targets, rules) = {
+        let db = state.db.lock().unwrap();
+        (
+            load_reanalyze_targets(&db, sample_ids.as_deref(), pack_uuid.as_deref())?,
+            MetadataRuleSet::load(&db),
+        )
+    }; // 분석 중에는 DB lock 해제
+
+    let total = targets.len();
+    let mut analyzed: Vec<(ReanalyzeDiff, Vec<FieldWrite>)> = Vec::new();
+
+    for (i, t) in targets.iter().enumerate() {
+        let _ = app.emit(
+            "reanalyze-progress",
+            ScanProgress {
+                current: i + 1,
+                total,
+                current_file: t.filename.clone(),
+            },
+        );
+
+        // 분석기들이 디코딩 결과를 공유
+        let audio = AnalysisContext::new(&t.path);
+        let (changes, writes) = analyze_changes(t, fields, &audio, &rules);
+
+        if !changes.is_empty() {
+            analyzed.push((
+                ReanalyzeDiff {
+                    sample_id: t.id,
+                    filename: t.filename.clone(),
+                    changes,
+                },
+                writes,
+            ));
+        }
+    }
+
+    let mut db = state.db.lock().unwrap();
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    for (diff, writes) in analyzed.iter_mut() {
+        for (field, value, source, confidence) in writes.iter() {
+            let changed = match value {
+                Some(value) => write_sample_field(
+                    &tx,
+                    diff.sample_id,
+                    *field,
+                    &value.values(),
+                    Some(source),
+                    Some(*confidence),
+                ),
+                // 값과 출처를 함께 지움 (이후 다른 분석기가 다시 채울 수 있도록)
+                None => write_sample_field(
+                    &tx,
+                    diff.sample_id,
+                    *field,
+                    &vec![&rusqlite::types::Null as &dyn rusqlite::ToSql; field.columns().0.len()],
+                    None,
+                    None,
+                ),
+            }
+            .map_err(|e| e.to_string())?;
+            // 분석 도중 사용자가 편집한 경우 등
+            if changed == 0 {
+                if let Some(c) = diff.changes.iter_mut().find(|c| c.field == field.as_str()) {
+                    c.applied = false;
+                }
+            }
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(analyzed.into_iter().map(|(diff, _)| diff).collect())
+}
+
+// ── Update commands ──────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct SampleUpdate {
+    pub id: i64,
+    pub filename: String,
+    pub tags: Option<String>,
+    pub genre: Option<String>,
+    pub audio_key: Option<String>,
+    pub chord_type: Option<String>,
+    pub bpm: Option<f64>,
+    pub sample_type: Option<String>,
+}
+
+#[tauri::command]
+fn update_sample(
+    update: SampleUpdate,
+    app: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<Sample, String> {
+    let db = state.db.lock().unwrap();
+    let root = get_library_root(&db);
+    let (previous_tags, duration): (Option<String>, Option<i64>) = db
+        .query_row(
+            "SELECT tags, duration FROM samples WHERE id = ?1",
+            params![update.id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap_or((None, None));
+    // BPM이나 타입을 바꾸면 마디 수를 다시 계산 (사용자 BPM은 모순으로 표시하지 않음)
+    let (bars, _) = loop_length_check(
+        update.bpm,
+        Some(SOURCE_USER),
+        duration,
+        update.sample_type.as_deref(),
+    );
+
+    // 실제로 바뀐 필드만 출처를 user로 표시 — 이후 재임포트/재분석에서 보호됨
+    // (장르는 팩 장르를 상속해 보여준 값과 비교)
+    db.execute(
+        "UPDATE samples SET
+            user_edited = CASE WHEN filename IS NOT ?1 THEN 1 ELSE user_edited END,
+            tags_source = CASE WHEN tags IS NOT ?2 THEN 'user' ELSE tags_source END,
+            tags_confidence = CASE WHEN tags IS NOT ?2 THEN 1.0 ELSE tags_confidence END,
+            genre_source = CASE WHEN COALESCE(genre, (SELECT genre FROM packs WHERE uuid = pack_uuid)) IS NOT ?3
+                                THEN 'user' ELSE genre_source END,
+            genre_confidence = CASE WHEN COALESCE(genre, (SELECT genre FROM packs WHERE uuid = pack_uuid)) IS NOT ?3
+                                    THEN 1.0 ELSE genre_confidence END,
+            key_source = CASE WHEN audio_key IS NOT ?4 OR chord_type IS NOT ?5 THEN 'user' ELSE key_source END,
+            key_confidence = CASE WHEN audio_key IS NOT ?4 OR chord_type IS NOT ?5 THEN 1.0 ELSE key_confidence END,
+            bpm_source = CASE WHEN bpm IS NOT ?6 THEN 'user' ELSE bpm_source END,
+            bpm_confidence = CASE WHEN bpm IS NOT ?6 THEN 1.0 ELSE bpm_confidence END,
+            sample_type_source = CASE WHEN sample_type IS NOT ?7 THEN 'user' ELSE sample_type_source END,
+            sample_type_confidence = CASE WHEN sample_type IS NOT ?7 THEN 1.0 ELSE sample_type_confidence END,
+            bars = CASE WHEN bpm IS NOT ?6 OR sample_type IS NOT ?7 THEN ?9 ELSE bars END,
+            bpm_mismatch = CASE WHEN bpm IS NOT ?6 OR sample_type IS NOT ?7 THEN 0 ELSE bpm_mismatch END,
+            filename = ?1, tags = ?2, genre = ?3, audio_key = ?4, chord_type = ?5, bpm = ?6, sample_type = ?7
+         WHERE id = ?8",
+        params![
+            update.filename,
+            update.tags,
+            update.genre,
+            update.audio_key,
+            update.chord_type,
+            update.bpm,
+            update.sample_type,
+            update.id,
+            bars,
+        ],
+    )
+    .map_err(|e| format!("샘플 업데이트 실패: {}", e))?;
+
+    // 태그 교정은 분류기 학습 데이터 — 편집 응답을 막지 않도록 백그라운드에서 재학습
+    if previous_tags != update.tags {
+        schedule_classifier_retrain(&app);
+    }
+
+    // 업데이트된 샘플을 다시 조회해서 반환
+    let sample = db
+        .query_row(
+            &format!(
+                "SELECT {}
+                 FROM samples s
+                 LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                 WHERE s.id = ?1",
+                SAMPLE_COLUMNS
+            ),
+            params![update.id],
+            |row| sample_from_row(row, &root),
+        )
+        .map_err(|e| format!("업데이트된 샘플 조회 실패: {}", e))?;
+
+    Ok(sample)
+}
+
+// ── Pack update ─────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct PackUpdate {
+    pub uuid: String,
+    pub name: String,
+    pub genre: Option<String>,
+}
+
+#[tauri::command]
+fn update_pack(update: PackUpdate, state: State<AppState>) -> Result<Pack, String> {
+    let db = state.db.lock().unwrap();
+
+    db.execute(
+        "UPDATE packs SET name = ?1, genre = ?2, user_edited = 1 WHERE uuid = ?3",
+        params![update.name, update.genre, update.uuid],
+    )
+    .map_err(|e| format!("팩 업데이트 실패: {}", e))?;
+
+    // 업데이트된 팩을 다시 조회해서 반환
+    let pack = db
+        .query_row(
+            "SELECT p.uuid, p.name, p.genre, p.cover_url, COUNT(s.id) as sample_count, p.created_at,
+                    p.storage_mode
+             FROM packs p
+             LEFT JOIN samples s ON s.pack_uuid = p.uuid
+             WHERE p.uuid = ?1
+             GROUP BY p.uuid",
+            params![update.uuid],
+            |row| {
+                Ok(Pack {
+                    uuid: row.get(0)?,
+                    name: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    genre: row.get(2)?,
+                    cover_url: row.get(3)?,
+                    sample_count: row.get::<_, i64>(4)? as usize,
+                    created_at: row.get(5)?,
+                    storage_mode: StorageMode::parse(row.get::<_, Option<String>>(6)?.as_deref()),
+                })
+            },
+        )
+        .map_err(|e| format!("업데이트된 팩 조회 실패: {}", e))?;
+
+    Ok(pack)
+}
+
+// ── Delete commands ──────────────────────────────────────────────────
+
+/// 개별 샘플 삭제 (DB + 파일)
+#[tauri::command]
+fn delete_sample(sample_id: i64, state: State<AppState>) -> Result<(), String> {
+    let db = state.db.lock().unwrap();
+
+    // 1. 파일 경로 + 팩 저장 방식 조회
+    let (local_path, mode): (Option<String>, StorageMode) = db
+        .query_row(
+            "SELECT s.local_path, p.storage_mode
+             FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE s.id = ?1",
+            params![sample_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    StorageMode::parse(row.get::<_, Option<String>>(1)?.as_deref()),
+                ))
+            },
+        )
+        .map_err(|e| format!("샘플 조회 실패: {}", e))?;
+
+    // 2. 실제 파일 삭제 (reference 팩은 원본 유지)
+    if let Some(ref path) = local_path {
+        let root = get_library_root(&db);
+        remove_library_file(&root, &resolve_library_path(&root, path), mode);
+    }
+
+    // 3. DB에서 삭제
+    db.execute("DELETE FROM samples WHERE id = ?1", params![sample_id])
+        .map_err(|e| format!("샘플 삭제 실패: {}", e))?;
+
+    Ok(())
+}
+
+/// 팩 삭제 (소속 샘플 파일 + DB 레코드 일괄 삭제)
+#[tauri::command]
+fn delete_pack(pack_uuid: String, state: State<AppState>) -> Result<usize, String> {
+    let db = state.db.lock().unwrap();
+
+    // 1. 소속 샘플의 파일 경로 모두 조회 (절대 경로로 변환)
+    let root = get_library_root(&db);
+    let paths: Vec<String> = {
+        let mut stmt = db
+            .prepare("SELECT local_path FROM samples WHERE pack_uuid = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![&pack_uuid], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok())
+            .map(|p| resolve_library_path(&root, &p))
+            .collect()
+    };
+
+    let count = paths.len();
+    let mode: StorageMode = db
+        .query_row(
+            "SELECT storage_mode FROM packs WHERE uuid = ?1",
+            params![&pack_uuid],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .map(|m| StorageMode::parse(m.as_deref()))
+        .unwrap_or_default();
+
+    // 2. 실제 파일 삭제 (reference 팩은 원본 유지)
+    for path in &paths {
+        remove_library_file(&root, path, mode);
+    }
+
+    // 3. DB에서 샘플 삭제
+    db.execute(
+        "DELETE FROM samples WHERE pack_uuid = ?1",
+        params![&pack_uuid],
+    )
+    .map_err(|e| format!("샘플 삭제 실패: {}", e))?;
+
+    // 4. DB에서 팩 삭제
+    db.execute("DELETE FROM packs WHERE uuid = ?1", params![&pack_uuid])
+        .map_err(|e| format!("팩 삭제 실패: {}", e))?;
+
+    // 5. 빈 디렉토리 정리 시도 (reference 팩은 사용자 폴더이므로 건너뜀)
+    if mode == StorageMode::Reference {
+        return Ok(count);
+    }
+    for path in &paths {
+        let p = Path::new(path);
+        if let Some(parent) = p.parent() {
+            // 디렉토리가 비어있으면 삭제 (실패해도 무시)
+            let _ = std::fs::remove_dir(parent);
+        }
+    }
+
+    Ok(count)
+}
+
+/// 모든 샘플 삭제 (DB 레코드 + 오디오 파일 일괄 삭제)
+#[tauri::command]
+fn delete_all_samples(state: State<AppState>) -> Result<usize, String> {
+    let db = state.db.lock().unwrap();
+
+    // 1. 모든 샘플의 파일 경로 + 팩 저장 방식 조회
+    let root = get_library_root(&db);
+    let paths: Vec<(String, StorageMode)> = {
+        let mut stmt = db
+            .prepare(
+                "SELECT s.local_path, p.storage_mode
+                 FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    resolve_library_path(&root, &row.get::<_, String>(0)?),
+                    StorageMode::parse(row.get::<_, Option<String>>(1)?.as_deref()),
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    let count = paths.len();
+
+    // 2. 실제 파일 삭제 (reference 팩은 원본 유지)
+    for (path, mode) in &paths {
+        remove_library_file(&root, path, *mode);
+    }
+
+    // 3. DB 초기화
+    db.execute_batch("DELETE FROM samples; DELETE FROM packs;")
+        .map_err(|e| format!("데이터 삭제 실패: {}", e))?;
+
+    // 4. 빈 디렉토리 정리 (slice.db가 있을 수 있는 루트는 유지)
+    if root.exists() {
+        remove_empty_dirs(&root);
+    }
+
+    Ok(count)
+}
+
+// ── Library location ────────────────────────────────────────────────
+
+/// 파일 이동 — 다른 볼륨이면 복사 후 삭제 (심볼릭 링크는 링크 자체를 다시 만듦)
+fn move_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    if src.symlink_metadata()?.file_type().is_symlink() {
+        create_symlink(&std::fs::read_link(src)?, dest)?;
+    } else {
+        std::fs::copy(src, dest)?;
+    }
+    std::fs::remove_file(src)
+}
+
+/// 현재 라이브러리 루트 경로
+#[tauri::command]
+fn get_library_location(state: State<AppState>) -> Result<String, String> {
+    let db = state.db.lock().unwrap();
+    Ok(get_library_root(&db).to_string_lossy().to_string())
+}
+
+/// 파일 이동 없이 라이브러리 루트만 변경
+/// (외장 드라이브나 다른 컴퓨터로 이미 옮긴 라이브러리를 다시 연결할 때)
+#[tauri::command]
+fn set_library_location(path: String, state: State<AppState>) -> Result<String, String> {
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err("유효한 폴더가 아닙니다".to_string());
+    }
+    let mut db = state.db.lock().unwrap();
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    set_setting(&tx, SETTING_LIBRARY_ROOT, Some(&path))?;
+    relativize_library_paths(&tx, &root)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// 라이브러리 파일을 새 루트로 이동하고 DB를 한 트랜잭션으로 갱신
+/// 이동 중 실패하면 이미 옮긴 파일을 되돌림. `relocate-progress` 이벤트 발행
+/// (reference 팩의 원본 파일은 라이브러리 밖에 있으므로 이동하지 않음)
+/// 백그라운드 스레드에서 실행
+#[tauri::command]
+async fn relocate_library(new_root: String, app: tauri::AppHandle) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || relocate_library_blocking(&new_root, &app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn relocate_library_blocking(new_root: &str, app: &tauri::AppHandle) -> Result<usize, String> {
+    let state = app.state::<AppState>();
+    let _busy = BusyGuard::new(&state.busy);
+
+    // 라이브러리 내부(상대 경로) 파일만 이동 대상
+    let (old_root, rel_paths) = {
+        let db = state.db.lock().unwrap();
+        let mut stmt = db
+            .prepare("SELECT DISTINCT local_path FROM samples")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        let rel_paths: Vec<String> = rows
+            .filter_map(|r| r.ok())
+            .filter(|p| !Path::new(p).is_absolute())
+            .collect();
+        (get_library_root(&db), rel_paths)
+    }; // 파일 이동 중에는 DB lock 해제
+
+    let new_root_path = PathBuf::from(new_root);
+    if new_root_path == old_root {
+        return Err("현재 라이브러리 위치와 같습니다".to_string());
+    }
+    if new_root_path.starts_with(&old_root) || old_root.starts_with(&new_root_path) {
+        return Err("기존 라이브러리 폴더의 상위/하위 폴더로는 이동할 수 없습니다".to_string());
+    }
+    std::fs::create_dir_all(&new_root_path)
+        .map_err(|e| format!("폴더 생성 실패: {}", e))?;
+
+    let total = rel_paths.len();
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let rollback = |moved: &[(PathBuf, PathBuf)]| {
+        for (src, dest) in moved.iter().rev() {
+            let _ = move_file(dest, src);
+        }
+    };
+
+    for (i, rel) in rel_paths.iter().enumerate() {
+        let src = PathBuf::from(resolve_library_path(&old_root, rel));
+        let dest = PathBuf::from(resolve_library_path(&new_root_path, rel));
+
+        // 이미 사라진 파일은 건너뜀 (경로는 새 루트 기준으로 유지)
+        if src.symlink_metadata().is_ok() {
+            if let Err(e) = move_file(&src, &dest) {
+                rollback(&moved);
+                return Err(format!("파일 이동 실패 ({}): {}", rel, e));
+            }
+            moved.push((src, dest));
+        }
+
+        if i % 10 == 0 || i + 1 == total {
+            let _ = app.emit(
+                "relocate-progress",
+                RelocateProgress {
+                    current: i + 1,
+                    total,
+                    current_file: rel.clone(),
+                },
+            );
+        }
+    }
+
+    // DB 갱신: 루트 변경 + 새 루트 하위 절대 경로 정리
+    let committed = (|| -> Result<(), String> {
+        let mut db = state.db.lock().unwrap();
+        let tx = db.transaction().map_err(|e| e.to_string())?;
+        set_setting(&tx, SETTING_LIBRARY_ROOT, Some(new_root))?;
+        relativize_library_paths(&tx, &new_root_path)?;
+        tx.commit().map_err(|e| e.to_string())
+    })();
+    if let Err(e) = committed {
+        rollback(&moved);
+        return Err(e);
+    }
+
+    remove_empty_dirs(&old_root);
+    Ok(moved.len())
+}
+
+// ── ZIP export helper ────────────────────────────────────────────────
+
+/// 오디오 파일을 ZIP에 스트리밍할 때 한 번에 읽는 크기
+const EXPORT_CHUNK_BYTES: usize = 1 << 20;
+/// export-progress 이벤트 최소 간격 (청크마다 보내면 UI가 밀림)
+const EXPORT_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+/// 이보다 큰 항목은 ZIP64로 기록 (Deflate가 크기를 조금 늘릴 수 있어 4GB보다 여유를 둠)
+const ZIP64_ENTRY_THRESHOLD: u64 = u32::MAX as u64 - (64 << 20);
+
+/// 이미 압축된 포맷은 Deflate로 거의 줄지 않으므로 그대로 저장 (WAV/AIFF 등 PCM은 Deflate)
+fn zip_compression_for(path: &Path) -> CompressionMethod {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "mp3" | "flac" | "ogg" | "opus" | "m4a" | "aac" => CompressionMethod::Stored,
+        _ => CompressionMethod::Deflated,
+    }
+}
+
+fn make_unique_name(base: &str, used: &mut HashSet<String>) -> String {
+    if used.insert(base.to_string()) {
+        return base.to_string();
+    }
+    let stem = Path::new(base)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(base);
+    let ext = Path::new(base).extension().and_then(|s| s.to_str());
+    for i in 2.. {
+        let candidate = match ext {
+            Some(e) => format!("{} ({}).{}", stem, i, e),
+            None => format!("{} ({})", stem, i),
+        };
+        if used.insert(candidate.clone()) {
+            return candidate;
+        }
+    }
+    unreachable!()
+}
 
-    let mut db = state.db.lock().unwrap();
-    let tx = db.transaction().map_err(|e| e.to_string())?;
+/// 선택된 샘플을 ZIP 파일로 내보내기 (오디오는 청크 단위로 스트리밍, 4GB 초과 시 ZIP64)
+/// 백그라운드 작업으로 실행 — 취소하면 만들던 ZIP 파일 삭제
+#[tauri::command]
+async fn export_samples(
+    sample_ids: Vec<i64>,
+    dest_path: String,
+    app: tauri::AppHandle,
+) -> Result<usize, String> {
+    run_job(app, JobKind::Export, move |job| {
+        let result = export_samples_job(&sample_ids, &dest_path, job);
+        if result.is_err() {
+            let _ = std::fs::remove_file(&dest_path);
+        }
+        result
+    })
+    .await
+}
 
-    for (pack_idx, (pack_name, pack_folder, audio_files)) in packs.iter().enumerate() {
-        let dest_base = slice_dir.join("External").join(pack_name);
-        let file_count = audio_files.len();
-        let replace_uuid = replace_map.get(pack_name).map(|s| s.as_str());
+fn export_samples_job(sample_ids: &[i64], dest_path: &str, job: &JobHandle) -> Result<usize, String> {
+    let state = job.app.state::<AppState>();
+    let _busy = BusyGuard::new(&state.busy);
 
-        let (copied, skipped) = import_single_pack(
-            pack_name,
-            pack_folder,
-            audio_files,
-            &dest_base,
-            &tx,
-            &app,
-            global_offset,
-            global_total,
-            replace_uuid,
-            pack_idx,
-            total_packs,
-        )?;
+    // 1. DB에서 샘플 정보 조회
+    let samples: Vec<Sample> = {
+        let db = open_job_db()?;
+        let root = get_library_root(&db);
+        let placeholders: String = sample_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        let query = format!(
+            "SELECT {}
+             FROM samples s
+             LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE s.id IN ({})",
+            SAMPLE_COLUMNS, placeholders
+        );
+        let mut stmt = db.prepare(&query).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(sample_ids.iter()), |row| {
+                sample_from_row(row, &root)
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    }; // DB lock released
 
-        total_copied += copied;
-        total_skipped += skipped;
-        global_offset += file_count;
+    if samples.is_empty() {
+        return Err("내보낼 샘플이 없습니다".to_string());
     }
 
-    tx.commit().map_err(|e| e.to_string())?;
+    // 2. ZIP 파일 생성
+    let file = std::fs::File::create(dest_path)
+        .map_err(|e| format!("ZIP 파일 생성 실패: {}", e))?;
+    let mut zip = zip::ZipWriter::new(std::io::BufWriter::new(file));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
 
-    Ok(ImportResult {
-        files_copied: total_copied,
-        files_skipped: total_skipped,
-        total_packs,
-    })
-}
+    let total = samples.len();
+    let mut used_names: HashSet<String> = HashSet::new();
+    let mut exported = 0usize;
 
-/// Waveform 데이터 반환 (DB 캐시 사용, peaks + frequency colors)
-#[tauri::command]
-fn get_waveform(path: String, state: State<AppState>) -> Result<WaveformData, String> {
-    // Check DB cache — peaks와 colors 모두 있어야 캐시 히트
-    {
-        let db = state.db.lock().unwrap();
-        if let Ok((peaks_json, colors_json, dur_opt)) = db.query_row(
-            "SELECT waveform_peaks, waveform_colors, duration FROM samples WHERE local_path = ?1",
-            params![&path],
-            |row| {
-                Ok((
-                    row.get::<_, Option<String>>(0)?,
-                    row.get::<_, Option<String>>(1)?,
-                    row.get::<_, Option<i64>>(2)?,
-                ))
+    // 진행률은 오디오 바이트 기준 (없는 파일은 건너뜀)
+    let sizes: Vec<Option<u64>> = samples
+        .iter()
+        .map(|s| std::fs::metadata(&s.local_path).ok().filter(|m| m.is_file()).map(|m| m.len()))
+        .collect();
+    let bytes_total: u64 = sizes.iter().flatten().sum();
+    let mut bytes_done = 0u64;
+    let mut last_progress: Option<Instant> = None;
+    let report = |current: usize, bytes_done: u64, current_file: &str| {
+        job.progress(
+            "export-progress",
+            ExportProgress {
+                current,
+                total,
+                current_file: current_file.to_string(),
+                job_id: job.id,
+                bytes_done,
+                bytes_total,
             },
-        ) {
-            if let (Some(peaks_str), Some(colors_str)) = (&peaks_json, &colors_json) {
-                if let (Ok(peaks), Ok(colors)) = (
-                    serde_json::from_str::<Vec<f32>>(peaks_str),
-                    serde_json::from_str::<Vec<[f32; 3]>>(colors_str),
-                ) {
-                    let duration_secs = dur_opt.map(|d| d as f64 / 1000.0).unwrap_or(0.0);
-                    return Ok(WaveformData {
-                        peaks,
-                        colors,
-                        duration_secs,
-                    });
+            current,
+            total,
+            current_file,
+        );
+    };
+    let mut buf = vec![0u8; EXPORT_CHUNK_BYTES];
+
+    for (i, sample) in samples.iter().enumerate() {
+        job.check_cancelled()?;
+
+        // 고유한 오디오 파일명 생성
+        let audio_name = make_unique_name(&sample.filename, &mut used_names);
+
+        // 메타데이터 파일명 생성 (오디오 파일 확장자 제거 + _metadata.json)
+        let audio_stem = Path::new(&audio_name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown");
+        let meta_filename = format!("{}_metadata.json", audio_stem);
+        let meta_name = make_unique_name(&meta_filename, &mut used_names);
+
+        // 오디오 파일 추가 — 메모리에 통째로 올리지 않고 청크 단위로 복사
+        let audio_path = Path::new(&sample.local_path);
+        if let Some(size) = sizes[i] {
+            use std::io::Read;
+
+            let read_err = |e: std::io::Error| format!("오디오 파일 읽기 실패 ({}): {}", sample.filename, e);
+            let mut src = std::fs::File::open(audio_path).map_err(read_err)?;
+            let audio_options = options
+                .compression_method(zip_compression_for(audio_path))
+                .large_file(size >= ZIP64_ENTRY_THRESHOLD);
+            zip.start_file(&audio_name, audio_options)
+                .map_err(|e| e.to_string())?;
+            loop {
+                let n = src.read(&mut buf).map_err(read_err)?;
+                if n == 0 {
+                    break;
+                }
+                zip.write_all(&buf[..n]).map_err(|e| e.to_string())?;
+                bytes_done += n as u64;
+                if last_progress.is_none_or(|t| t.elapsed() >= EXPORT_PROGRESS_INTERVAL) {
+                    job.check_cancelled()?;
+                    report(i, bytes_done, &sample.filename);
+                    last_progress = Some(Instant::now());
                 }
             }
         }
-    } // release lock before expensive computation
 
-    // Compute waveform (peaks + frequency colors)
-    let waveform = compute_waveform_internal(&path, 128)?;
+        // 메타데이터 JSON 생성 (임포트 시 SampleSidecar로 복원)
+        let metadata = SampleSidecar {
+            filename: Some(sample.filename.clone()),
+            audio_key: sample.audio_key.clone(),
+            bpm: sample.bpm,
+            chord_type: sample.chord_type.clone(),
+            duration_ms: sample.duration,
+            genre: sample.genre.clone(),
+            sample_type: sample.sample_type.clone(),
+            tags: sample
+                .tags
+                .as_ref()
+                .map(|t| t.split(',').map(|s| s.trim().to_string()).collect()),
+            pack_name: sample.pack_name.clone(),
+            pack_uuid: sample.pack_uuid.clone(),
+            pack_genre: sample.pack_genre.clone(),
+        };
 
-    // Store in DB cache
-    {
-        let db = state.db.lock().unwrap();
-        if let (Ok(peaks_json), Ok(colors_json)) = (
-            serde_json::to_string(&waveform.peaks),
-            serde_json::to_string(&waveform.colors),
-        ) {
-            let _ = db.execute(
-                "UPDATE samples SET waveform_peaks = ?1, waveform_colors = ?2 WHERE local_path = ?3",
-                params![peaks_json, colors_json, &path],
-            );
+        let json_bytes = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+        zip.start_file(&meta_name, options)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(json_bytes.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        exported += 1;
+
+        // 진행 상황 이벤트 발행 (마지막 파일은 항상 — 100%로 끝나도록)
+        if i + 1 == total || last_progress.is_none_or(|t| t.elapsed() >= EXPORT_PROGRESS_INTERVAL) {
+            report(i + 1, bytes_done, &sample.filename);
+            last_progress = Some(Instant::now());
         }
     }
 
-    Ok(waveform)
+    // BufWriter drop 시 flush 오류가 묻히지 않도록 명시적으로 비움
+    zip.finish()
+        .map_err(|e| e.to_string())?
+        .flush()
+        .map_err(|e| format!("ZIP 파일 쓰기 실패: {}", e))?;
+    Ok(exported)
 }
 
-// ── Update commands ──────────────────────────────────────────────────
+// ── Playlist commands ────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
-pub struct SampleUpdate {
-    pub id: i64,
-    pub filename: String,
-    pub tags: Option<String>,
-    pub genre: Option<String>,
-    pub audio_key: Option<String>,
-    pub chord_type: Option<String>,
-    pub bpm: Option<i32>,
-    pub sample_type: Option<String>,
+/// 현재 스마트 플레이리스트 규칙 스키마 버전
+const SMART_RULES_VERSION: u32 = 1;
+
+/// playlists.rules JSON → 규칙 (NULL이면 None, 더 새 버전이면 에러)
+fn parse_playlist_rules(json: Option<&str>) -> Result<Option<SmartPlaylistRules>, String> {
+    let Some(json) = json else { return Ok(None) };
+    let rules: SmartPlaylistRules =
+        serde_json::from_str(json).map_err(|e| format!("스마트 규칙 해석 실패: {}", e))?;
+    if rules.version > SMART_RULES_VERSION {
+        return Err(format!(
+            "지원하지 않는 스마트 규칙 버전입니다: {} (최대 {})",
+            rules.version, SMART_RULES_VERSION
+        ));
+    }
+    // 이전 버전 규칙 변환은 버전이 올라갈 때 여기에 추가
+    Ok(Some(rules))
 }
 
-#[tauri::command]
-fn update_sample(update: SampleUpdate, state: State<AppState>) -> Result<Sample, String> {
-    let db = state.db.lock().unwrap();
+/// 규칙 검증 후 현재 버전으로 직렬화
+fn serialize_playlist_rules(rules: Option<SmartPlaylistRules>) -> Result<Option<String>, String> {
+    let Some(mut rules) = rules else { return Ok(None) };
+    if let (Some(min), Some(max)) = (rules.bpm_min, rules.bpm_max) {
+        if min > max {
+            return Err("BPM 범위가 올바르지 않습니다".to_string());
+        }
+    }
+    (rules.added_after, rules.added_before) =
+        normalize_date_range(rules.added_after.as_deref(), rules.added_before.as_deref())?;
+    if let Some(t) = rules.sample_type.as_deref() {
+        if !matches!(t, "all" | "oneshot" | "loop") {
+            return Err(format!("알 수 없는 샘플 유형: {}", t));
+        }
+    }
+    rules.version = SMART_RULES_VERSION;
+    serde_json::to_string(&rules)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
 
-    db.execute(
-        "UPDATE samples SET filename = ?1, tags = ?2, genre = ?3, audio_key = ?4, chord_type = ?5, bpm = ?6, sample_type = ?7 WHERE id = ?8",
-        params![
-            update.filename,
-            update.tags,
-            update.genre,
-            update.audio_key,
-            update.chord_type,
-            update.bpm,
-            update.sample_type,
-            update.id,
-        ],
-    )
-    .map_err(|e| format!("샘플 업데이트 실패: {}", e))?;
+impl SmartPlaylistRules {
+    /// 검색과 같은 WHERE/ORDER BY 빌더를 쓰도록 SampleFilter로 변환
+    fn to_filter(&self) -> SampleFilter {
+        SampleFilter {
+            q: self.q.clone(),
+            genres: self.genres.clone(),
+            instruments: self.instruments.clone(),
+            bpm_min: self.bpm_min,
+            bpm_max: self.bpm_max,
+            keys: self.keys.clone(),
+            sample_type: self.sample_type.clone(),
+            include: self.include_tags.clone(),
+            exclude: self.exclude_tags.clone(),
+            // 기본: 최근 추가순
+            sort_by: Some(self.sort_by.clone().unwrap_or_else(|| "recent".to_string())),
+            sort_dir: self.sort_dir.clone(),
+            pack_uuid: self.pack_uuid.clone(),
+            added_after: self.added_after.clone(),
+            added_before: self.added_before.clone(),
+            ..Default::default()
+        }
+    }
+}
 
-    // 업데이트된 샘플을 다시 조회해서 반환
-    let sample = db
+/// 스마트 플레이리스트 규칙에 맞는 샘플 수
+fn count_smart_playlist(db: &Connection, rules: &SmartPlaylistRules) -> Result<usize, String> {
+    let (where_sql, args) = build_filter_sql(&rules.to_filter());
+    let count: i64 = db
         .query_row(
-            "SELECT s.id, s.local_path, s.filename, s.audio_key, s.bpm, s.chord_type,
-                    s.duration, COALESCE(s.genre, p.genre) as genre,
-                    s.sample_type, s.tags,
-                    s.pack_uuid, p.name as pack_name, p.genre as pack_genre,
-                    s.created_at
-             FROM samples s
-             LEFT JOIN packs p ON s.pack_uuid = p.uuid
-             WHERE s.id = ?1",
-            params![update.id],
-            |row| {
-                Ok(Sample {
-                    id: row.get(0)?,
-                    local_path: row.get(1)?,
-                    filename: row.get(2)?,
-                    audio_key: row.get(3)?,
-                    bpm: row.get(4)?,
-                    chord_type: row.get(5)?,
-                    duration: row.get(6)?,
-                    genre: row.get(7)?,
-                    sample_type: row.get(8)?,
-                    tags: row.get(9)?,
-                    pack_uuid: row.get(10)?,
-                    pack_name: row.get(11)?,
-                    pack_genre: row.get(12)?,
-                    created_at: row.get(13)?,
-                })
-            },
+            &format!(
+                "SELECT COUNT(*) FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid WHERE {}",
+                where_sql
+            ),
+            rusqlite::params_from_iter(args.iter()),
+            |row| row.get(0),
         )
-        .map_err(|e| format!("업데이트된 샘플 조회 실패: {}", e))?;
-
-    Ok(sample)
+        .map_err(|e| e.to_string())?;
+    let count = count as usize;
+    Ok(rules.limit.map_or(count, |l| count.min(l)))
 }
 
-// ── Pack update ─────────────────────────────────────────────────────
-
-#[derive(Debug, Deserialize)]
-pub struct PackUpdate {
-    pub uuid: String,
-    pub name: String,
-    pub genre: Option<String>,
+fn is_smart_playlist(db: &Connection, playlist_id: i64) -> bool {
+    db.query_row(
+        "SELECT rules IS NOT NULL FROM playlists WHERE id = ?1",
+        params![playlist_id],
+        |row| row.get::<_, bool>(0),
+    )
+    .unwrap_or(false)
 }
 
 #[tauri::command]
-fn update_pack(update: PackUpdate, state: State<AppState>) -> Result<Pack, String> {
+fn get_playlists(state: State<AppState>) -> Result<Vec<Playlist>, String> {
     let db = state.db.lock().unwrap();
+    let mut stmt = db
+        .prepare(
+            "SELECT p.id, p.name, p.color, p.created_at, COUNT(ps.sample_id) as sample_count, p.rules
+             FROM playlists p
+             LEFT JOIN playlist_samples ps ON ps.playlist_id = p.id
+             GROUP BY p.id
+             ORDER BY p.created_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
 
-    db.execute(
-        "UPDATE packs SET name = ?1, genre = ?2 WHERE uuid = ?3",
-        params![update.name, update.genre, update.uuid],
-    )
-    .map_err(|e| format!("팩 업데이트 실패: {}", e))?;
+    let mut playlists: Vec<Playlist> = stmt
+        .query_map([], |row| {
+            Ok(Playlist {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                created_at: row.get(3)?,
+                sample_count: row.get::<_, i64>(4)? as usize,
+                // 해석할 수 없는 규칙은 목록을 막지 않고 일반 플레이리스트처럼 표시
+                rules: parse_playlist_rules(row.get::<_, Option<String>>(5)?.as_deref())
+                    .ok()
+                    .flatten(),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
 
-    // 업데이트된 팩을 다시 조회해서 반환
-    let pack = db
-        .query_row(
-            "SELECT p.uuid, p.name, p.genre, p.cover_url, COUNT(s.id) as sample_count, p.created_at
-             FROM packs p
-             LEFT JOIN samples s ON s.pack_uuid = p.uuid
-             WHERE p.uuid = ?1
-             GROUP BY p.uuid",
-            params![update.uuid],
-            |row| {
-                Ok(Pack {
-                    uuid: row.get(0)?,
-                    name: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
-                    genre: row.get(2)?,
-                    cover_url: row.get(3)?,
-                    sample_count: row.get::<_, i64>(4)? as usize,
-                    created_at: row.get(5)?,
-                })
-            },
-        )
-        .map_err(|e| format!("업데이트된 팩 조회 실패: {}", e))?;
+    for playlist in playlists.iter_mut() {
+        if let Some(rules) = &playlist.rules {
+            playlist.sample_count = count_smart_playlist(&db, rules)?;
+        }
+    }
 
-    Ok(pack)
+    Ok(playlists)
 }
 
-// ── Delete commands ──────────────────────────────────────────────────
-
-/// 개별 샘플 삭제 (DB + 파일)
 #[tauri::command]
-fn delete_sample(sample_id: i64, state: State<AppState>) -> Result<(), String> {
+fn create_playlist(
+    name: String,
+    color: Option<String>,
+    rules: Option<SmartPlaylistRules>,
+    state: State<AppState>,
+) -> Result<Playlist, String> {
     let db = state.db.lock().unwrap();
+    let rules_json = serialize_playlist_rules(rules)?;
+    db.execute(
+        "INSERT INTO playlists (name, color, rules) VALUES (?1, ?2, ?3)",
+        params![name, color, rules_json],
+    )
+    .map_err(|e| format!("플레이리스트 생성 실패: {}", e))?;
 
-    // 1. 파일 경로 조회
-    let local_path: Option<String> = db
+    let id = db.last_insert_rowid();
+    let mut playlist = db
         .query_row(
-            "SELECT local_path FROM samples WHERE id = ?1",
-            params![sample_id],
-            |row| row.get(0),
+            "SELECT id, name, color, created_at, rules FROM playlists WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok(Playlist {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    created_at: row.get(3)?,
+                    sample_count: 0,
+                    rules: parse_playlist_rules(row.get::<_, Option<String>>(4)?.as_deref())
+                        .ok()
+                        .flatten(),
+                })
+            },
         )
-        .map_err(|e| format!("샘플 조회 실패: {}", e))?;
+        .map_err(|e| e.to_string())?;
 
-    // 2. 실제 파일 삭제
-    if let Some(ref path) = local_path {
-        let p = Path::new(path);
-        if p.exists() {
-            let _ = std::fs::remove_file(p);
-        }
+    if let Some(rules) = &playlist.rules {
+        playlist.sample_count = count_smart_playlist(&db, rules)?;
     }
 
-    // 3. DB에서 삭제
-    db.execute("DELETE FROM samples WHERE id = ?1", params![sample_id])
-        .map_err(|e| format!("샘플 삭제 실패: {}", e))?;
+    Ok(playlist)
+}
 
+/// 스마트 규칙 설정/변경 (None이면 일반 플레이리스트로 전환, 기존 고정 목록은 유지)
+#[tauri::command]
+fn update_playlist_rules(
+    playlist_id: i64,
+    rules: Option<SmartPlaylistRules>,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let db = state.db.lock().unwrap();
+    let rules_json = serialize_playlist_rules(rules)?;
+    db.execute(
+        "UPDATE playlists SET rules = ?1 WHERE id = ?2",
+        params![rules_json, playlist_id],
+    )
+    .map_err(|e| format!("스마트 규칙 저장 실패: {}", e))?;
     Ok(())
 }
 
-/// 팩 삭제 (소속 샘플 파일 + DB 레코드 일괄 삭제)
 #[tauri::command]
-fn delete_pack(pack_uuid: String, state: State<AppState>) -> Result<usize, String> {
+fn rename_playlist(playlist_id: i64, name: String, state: State<AppState>) -> Result<(), String> {
     let db = state.db.lock().unwrap();
+    db.execute(
+        "UPDATE playlists SET name = ?1 WHERE id = ?2",
+        params![name, playlist_id],
+    )
+    .map_err(|e| format!("플레이리스트 이름 변경 실패: {}", e))?;
+    Ok(())
+}
 
-    // 1. 소속 샘플의 파일 경로 모두 조회
-    let paths: Vec<String> = {
-        let mut stmt = db
-            .prepare("SELECT local_path FROM samples WHERE pack_uuid = ?1")
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map(params![&pack_uuid], |row| row.get::<_, String>(0))
-            .map_err(|e| e.to_string())?;
-        rows.filter_map(|r| r.ok()).collect()
-    };
-
-    let count = paths.len();
-
-    // 2. 실제 파일 삭제
-    for path in &paths {
-        let p = Path::new(path);
-        if p.exists() {
-            let _ = std::fs::remove_file(p);
-        }
-    }
-
-    // 3. DB에서 샘플 삭제
+#[tauri::command]
+fn update_playlist_color(playlist_id: i64, color: Option<String>, state: State<AppState>) -> Result<(), String> {
+    let db = state.db.lock().unwrap();
     db.execute(
-        "DELETE FROM samples WHERE pack_uuid = ?1",
-        params![&pack_uuid],
+        "UPDATE playlists SET color = ?1 WHERE id = ?2",
+        params![color, playlist_id],
     )
-    .map_err(|e| format!("샘플 삭제 실패: {}", e))?;
+    .map_err(|e| format!("플레이리스트 색상 변경 실패: {}", e))?;
+    Ok(())
+}
 
-    // 4. DB에서 팩 삭제
-    db.execute("DELETE FROM packs WHERE uuid = ?1", params![&pack_uuid])
-        .map_err(|e| format!("팩 삭제 실패: {}", e))?;
+#[tauri::command]
+fn delete_playlist(playlist_id: i64, state: State<AppState>) -> Result<(), String> {
+    let db = state.db.lock().unwrap();
+    db.execute("DELETE FROM playlist_samples WHERE playlist_id = ?1", params![playlist_id])
+        .map_err(|e| e.to_string())?;
+    db.execute("DELETE FROM playlists WHERE id = ?1", params![playlist_id])
+        .map_err(|e| format!("플레이리스트 삭제 실패: {}", e))?;
+    Ok(())
+}
 
-    // 5. 빈 디렉토리 정리 시도
-    for path in &paths {
-        let p = Path::new(path);
-        if let Some(parent) = p.parent() {
-            // 디렉토리가 비어있으면 삭제 (실패해도 무시)
-            let _ = std::fs::remove_dir(parent);
-        }
+#[tauri::command]
+fn add_to_playlist(playlist_id: i64, sample_ids: Vec<i64>, state: State<AppState>) -> Result<(), String> {
+    let db = state.db.lock().unwrap();
+    if is_smart_playlist(&db, playlist_id) {
+        return Err("스마트 플레이리스트에는 샘플을 직접 추가할 수 없습니다".to_string());
     }
+    let mut stmt = db
+        .prepare("INSERT OR IGNORE INTO playlist_samples (playlist_id, sample_id) VALUES (?1, ?2)")
+        .map_err(|e| e.to_string())?;
+    for sid in &sample_ids {
+        stmt.execute(params![playlist_id, sid]).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
 
-    Ok(count)
+#[tauri::command]
+fn remove_from_playlist(playlist_id: i64, sample_ids: Vec<i64>, state: State<AppState>) -> Result<(), String> {
+    let db = state.db.lock().unwrap();
+    if is_smart_playlist(&db, playlist_id) {
+        return Err("스마트 플레이리스트에서는 샘플을 직접 제거할 수 없습니다".to_string());
+    }
+    for sid in &sample_ids {
+        db.execute(
+            "DELETE FROM playlist_samples WHERE playlist_id = ?1 AND sample_id = ?2",
+            params![playlist_id, sid],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
 
-/// 모든 샘플 삭제 (DB 레코드 + 오디오 파일 일괄 삭제)
 #[tauri::command]
-fn delete_all_samples(state: State<AppState>) -> Result<usize, String> {
+fn get_playlist_samples(playlist_id: i64, state: State<AppState>) -> Result<Vec<Sample>, String> {
     let db = state.db.lock().unwrap();
+    let root = get_library_root(&db);
 
-    // 1. 모든 샘플의 파일 경로 조회
-    let paths: Vec<String> = {
+    // 스마트 플레이리스트: 저장된 규칙을 호출 시점에 평가
+    let rules_json: Option<String> = db
+        .query_row(
+            "SELECT rules FROM playlists WHERE id = ?1",
+            params![playlist_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("플레이리스트를 찾을 수 없습니다: {}", e))?;
+    if let Some(rules) = parse_playlist_rules(rules_json.as_deref())? {
+        let filter = rules.to_filter();
+        let (where_sql, args) = build_filter_sql(&filter);
+        let limit = rules
+            .limit
+            .map_or_else(|| "-1".to_string(), |l| l.to_string());
         let mut stmt = db
-            .prepare("SELECT local_path FROM samples")
+            .prepare(&format!(
+                "SELECT {}
+                 FROM samples s
+                 LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                 WHERE {}
+                 ORDER BY {}
+                 LIMIT {}",
+                SAMPLE_COLUMNS,
+                where_sql,
+                build_order_sql(&filter),
+                limit
+            ))
             .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map([], |row| row.get::<_, String>(0))
-            .map_err(|e| e.to_string())?;
-        rows.filter_map(|r| r.ok()).collect()
-    };
+        let samples = stmt
+            .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+                sample_from_row(row, &root)
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        return Ok(samples);
+    }
 
-    let count = paths.len();
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {}
+             FROM playlist_samples ps
+             JOIN samples s ON s.id = ps.sample_id
+             LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE ps.playlist_id = ?1
+             ORDER BY ps.added_at DESC",
+            SAMPLE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
 
-    // 2. 실제 파일 삭제
-    for path in &paths {
-        let p = Path::new(path);
-        if p.exists() {
-            let _ = std::fs::remove_file(p);
-        }
-    }
+    let samples = stmt
+        .query_map(params![playlist_id], |row| sample_from_row(row, &root))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
 
-    // 3. DB 초기화
-    db.execute_batch("DELETE FROM samples; DELETE FROM packs;")
-        .map_err(|e| format!("데이터 삭제 실패: {}", e))?;
+    Ok(samples)
+}
 
-    // 4. 빈 디렉토리 정리
-    if let Some(home) = dirs::home_dir() {
-        let slice_dir = home.join("Slice");
-        if slice_dir.exists() {
-            // 하위 빈 디렉토리 재귀 삭제 (slice.db가 있는 루트는 유지)
-            fn remove_empty_dirs(dir: &Path) {
-                if let Ok(entries) = std::fs::read_dir(dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.is_dir() {
-                            remove_empty_dirs(&path);
-                            let _ = std::fs::remove_dir(&path); // 비어있을 때만 성공
-                        }
-                    }
-                }
-            }
-            remove_empty_dirs(&slice_dir);
-        }
+// ── Search (FTS5) ───────────────────────────────────────────────────
+
+const SEARCH_INDEX_VERSION: &str = "1";
+const SEARCH_DEFAULT_PAGE_SIZE: usize = 50;
+const SEARCH_MAX_PAGE_SIZE: usize = 500;
+/// 프론트엔드와 같이 두 글자 미만 검색어는 무시
+const SEARCH_MIN_QUERY_CHARS: usize = 2;
+/// trigram 토크나이저가 인덱스로 찾을 수 있는 최소 길이 (더 짧으면 LIKE로 대체)
+const SEARCH_TRIGRAM_CHARS: usize = 3;
+
+/// 부분 문자열 검색을 위해 trigram 토크나이저 사용 (대소문자 무시)
+/// 팩 이름/장르가 바뀌면 해당 팩 샘플 행을 다시 쓰도록 트리거로 동기화
+const SEARCH_INDEX_SQL: &str = "
+    CREATE VIRTUAL TABLE IF NOT EXISTS samples_fts
+        USING fts5(filename, tags, pack_name, genre, tokenize = 'trigram');
+    CREATE TRIGGER IF NOT EXISTS samples_fts_insert AFTER INSERT ON samples BEGIN
+        INSERT INTO samples_fts (rowid, filename, tags, pack_name, genre)
+        VALUES (new.id, new.filename, new.tags,
+                (SELECT name FROM packs WHERE uuid = new.pack_uuid),
+                COALESCE(new.genre, (SELECT genre FROM packs WHERE uuid = new.pack_uuid)));
+    END;
+    CREATE TRIGGER IF NOT EXISTS samples_fts_delete AFTER DELETE ON samples BEGIN
+        DELETE FROM samples_fts WHERE rowid = old.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS samples_fts_update
+        AFTER UPDATE OF filename, tags, genre, pack_uuid ON samples BEGIN
+        DELETE FROM samples_fts WHERE rowid = old.id;
+        INSERT INTO samples_fts (rowid, filename, tags, pack_name, genre)
+        VALUES (new.id, new.filename, new.tags,
+                (SELECT name FROM packs WHERE uuid = new.pack_uuid),
+                COALESCE(new.genre, (SELECT genre FROM packs WHERE uuid = new.pack_uuid)));
+    END;
+    CREATE TRIGGER IF NOT EXISTS packs_fts_insert AFTER INSERT ON packs BEGIN
+        DELETE FROM samples_fts WHERE rowid IN (SELECT id FROM samples WHERE pack_uuid = new.uuid);
+        INSERT INTO samples_fts (rowid, filename, tags, pack_name, genre)
+        SELECT id, filename, tags, new.name, COALESCE(genre, new.genre)
+        FROM samples WHERE pack_uuid = new.uuid;
+    END;
+    CREATE TRIGGER IF NOT EXISTS packs_fts_update AFTER UPDATE OF name, genre ON packs BEGIN
+        DELETE FROM samples_fts WHERE rowid IN (SELECT id FROM samples WHERE pack_uuid = new.uuid);
+        INSERT INTO samples_fts (rowid, filename, tags, pack_name, genre)
+        SELECT id, filename, tags, new.name, COALESCE(genre, new.genre)
+        FROM samples WHERE pack_uuid = new.uuid;
+    END;";
+
+fn init_search_index(db: &Connection) -> Result<(), String> {
+    db.execute_batch(SEARCH_INDEX_SQL)
+        .map_err(|e| format!("검색 인덱스 초기화 실패: {}", e))?;
+    if get_setting(db, SETTING_SEARCH_INDEX_VERSION).as_deref() != Some(SEARCH_INDEX_VERSION) {
+        rebuild_search_index(db)?;
+        set_setting(db, SETTING_SEARCH_INDEX_VERSION, Some(SEARCH_INDEX_VERSION))?;
     }
+    Ok(())
+}
 
-    Ok(count)
+/// 인덱스를 samples/packs 기준으로 다시 채움 (트리거 도입 전 데이터 포함)
+fn rebuild_search_index(db: &Connection) -> Result<(), String> {
+    db.execute_batch(
+        "DELETE FROM samples_fts;
+         INSERT INTO samples_fts (rowid, filename, tags, pack_name, genre)
+         SELECT s.id, s.filename, s.tags, p.name, COALESCE(s.genre, p.genre)
+         FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid;",
+    )
+    .map_err(|e| format!("검색 인덱스 재구축 실패: {}", e))
 }
 
-// ── ZIP export helper ────────────────────────────────────────────────
+/// 프론트엔드 formatKey와 같은 키 표기 ("C#m", "F")
+const KEY_LABEL_SQL: &str = "(UPPER(SUBSTR(s.audio_key, 1, 1)) || SUBSTR(s.audio_key, 2)
+     || CASE WHEN s.chord_type = 'minor' THEN 'm' ELSE '' END)";
+
+/// 프론트엔드 SampleFilterSearch와 같은 필터 (camelCase)
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SampleFilter {
+    pub q: Option<String>,
+    pub genres: Vec<String>,
+    /// 악기 태그 (하나라도 포함)
+    pub instruments: Vec<String>,
+    pub bpm_min: Option<f64>,
+    pub bpm_max: Option<f64>,
+    /// formatKey 표기 ("C#m", "F")
+    pub keys: Vec<String>,
+    /// "oneshot" | "loop" ("all"이면 무시)
+    #[serde(rename = "type")]
+    pub sample_type: Option<String>,
+    /// 태그 하나라도 포함
+    pub include: Vec<String>,
+    /// 태그 하나도 포함하지 않음
+    pub exclude: Vec<String>,
+    /// "filename" | "bpm" | "duration" | "recent" | "shuffle"
+    pub sort_by: Option<String>,
+    pub sort_dir: Option<String>,
+    /// shuffle 정렬 시드 (같은 시드면 페이지 간 순서 유지)
+    pub shuffle_seed: Option<i64>,
+    /// 범위 제한: 특정 팩 / 플레이리스트
+    pub pack_uuid: Option<String>,
+    pub playlist_id: Option<i64>,
+    /// created_at 범위 (스마트 플레이리스트 규칙용)
+    pub added_after: Option<String>,
+    pub added_before: Option<String>,
+    /// 파일명 BPM이 루프 길이와 맞지 않는 샘플만
+    pub bpm_mismatch: bool,
+    /// playlist_id가 스마트 플레이리스트면 그 규칙 (resolve_sample_filter가 채움)
+    #[serde(skip)]
+    smart_rules: Option<SmartPlaylistRules>,
+}
 
-fn make_unique_name(base: &str, used: &mut HashSet<String>) -> String {
-    if used.insert(base.to_string()) {
-        return base.to_string();
+#[derive(Debug, Serialize, Clone)]
+pub struct SamplePage {
+    pub samples: Vec<Sample>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+/// 날짜 경계 → created_at 형식 ("YYYY-MM-DD HH:MM:SS")
+/// "2024-05-01", "2024-05-01 12:00", "2024-05-01T12:00:00"을 받고, 없는 날짜/시각이면 에러
+fn normalize_date_bound(value: &str) -> Result<String, String> {
+    let invalid = || format!("날짜 형식이 올바르지 않습니다: {}", value);
+    let re = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})(?:[ T](\d{2}):(\d{2})(?::(\d{2}))?)?$").unwrap();
+    let caps = re.captures(value.trim()).ok_or_else(invalid)?;
+    let num = |i: usize| caps.get(i).map_or(0, |m| m.as_str().parse::<u32>().unwrap_or(0));
+    let (year, month, day, hour, minute, second) = (num(1), num(2), num(3), num(4), num(5), num(6));
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(invalid()),
+    };
+    if day == 0 || day > days_in_month || hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
     }
-    let stem = Path::new(base)
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or(base);
-    let ext = Path::new(base).extension().and_then(|s| s.to_str());
-    for i in 2.. {
-        let candidate = match ext {
-            Some(e) => format!("{} ({}).{}", stem, i, e),
-            None => format!("{} ({})", stem, i),
-        };
-        if used.insert(candidate.clone()) {
-            return candidate;
+    Ok(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    ))
+}
+
+/// 추가 날짜 범위 정규화 (시작이 끝보다 늦으면 에러)
+fn normalize_date_range(
+    after: Option<&str>,
+    before: Option<&str>,
+) -> Result<(Option<String>, Option<String>), String> {
+    let after = after.map(normalize_date_bound).transpose()?;
+    let before = before.map(normalize_date_bound).transpose()?;
+    if let (Some(a), Some(b)) = (&after, &before) {
+        if a > b {
+            return Err("날짜 범위가 올바르지 않습니다".to_string());
         }
     }
-    unreachable!()
+    Ok((after, before))
 }
 
-/// 선택된 샘플을 ZIP 파일로 내보내기
-#[tauri::command]
-fn export_samples(
-    sample_ids: Vec<i64>,
-    dest_path: String,
-    app: tauri::AppHandle,
-    state: State<AppState>,
-) -> Result<usize, String> {
-    // 1. DB에서 샘플 정보 조회
-    let samples: Vec<Sample> = {
-        let db = state.db.lock().unwrap();
-        let placeholders: String = sample_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
-        let query = format!(
-            "SELECT s.id, s.local_path, s.filename, s.audio_key, s.bpm, s.chord_type,
-                    s.duration, COALESCE(s.genre, p.genre) as genre,
-                    s.sample_type, s.tags,
-                    s.pack_uuid, p.name as pack_name, p.genre as pack_genre,
-                    s.created_at
-             FROM samples s
-             LEFT JOIN packs p ON s.pack_uuid = p.uuid
-             WHERE s.id IN ({})",
-            placeholders
-        );
-        let mut stmt = db.prepare(&query).map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map(rusqlite::params_from_iter(sample_ids.iter()), |row| {
-                Ok(Sample {
-                    id: row.get(0)?,
-                    local_path: row.get(1)?,
-                    filename: row.get(2)?,
-                    audio_key: row.get(3)?,
-                    bpm: row.get(4)?,
-                    chord_type: row.get(5)?,
-                    duration: row.get(6)?,
-                    genre: row.get(7)?,
-                    sample_type: row.get(8)?,
-                    tags: row.get(9)?,
-                    pack_uuid: row.get(10)?,
-                    pack_name: row.get(11)?,
-                    pack_genre: row.get(12)?,
-                    created_at: row.get(13)?,
-                })
-            })
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect();
-        rows
-    }; // DB lock released
-
-    if samples.is_empty() {
-        return Err("내보낼 샘플이 없습니다".to_string());
+/// 프론트엔드에서 받은 필터 검증 + 정규화
+/// - 날짜 범위를 created_at 형식으로 바꾸고, 잘못된 값이면 에러
+/// - playlist_id가 스마트 플레이리스트면 저장된 규칙을 불러와 그 결과로 범위를 제한
+fn resolve_sample_filter(db: &Connection, filter: &mut SampleFilter) -> Result<(), String> {
+    (filter.added_after, filter.added_before) =
+        normalize_date_range(filter.added_after.as_deref(), filter.added_before.as_deref())?;
+    if let Some(pid) = filter.playlist_id {
+        let rules_json: Option<String> = db
+            .query_row("SELECT rules FROM playlists WHERE id = ?1", params![pid], |row| row.get(0))
+            .map_err(|e| format!("플레이리스트를 찾을 수 없습니다: {}", e))?;
+        filter.smart_rules = parse_playlist_rules(rules_json.as_deref())?;
     }
+    Ok(())
+}
 
-    // 2. ZIP 파일 생성
-    let file = std::fs::File::create(&dest_path)
-        .map_err(|e| format!("ZIP 파일 생성 실패: {}", e))?;
-    let mut zip = zip::ZipWriter::new(file);
-    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
-
-    let total = samples.len();
-    let mut used_names: HashSet<String> = HashSet::new();
-    let mut exported = 0usize;
+/// FTS5 MATCH용 구문: 단어마다 따옴표로 감싸 AND 결합
+fn fts_phrase(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
 
-    for (i, sample) in samples.iter().enumerate() {
-        // 고유한 오디오 파일명 생성
-        let audio_name = make_unique_name(&sample.filename, &mut used_names);
+/// ?, ?, ? 자리표시자
+fn sql_placeholders(n: usize) -> String {
+    vec!["?"; n].join(", ")
+}
 
-        // 메타데이터 파일명 생성 (오디오 파일 확장자 제거 + _metadata.json)
-        let audio_stem = Path::new(&audio_name)
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown");
-        let meta_filename = format!("{}_metadata.json", audio_stem);
-        let meta_name = make_unique_name(&meta_filename, &mut used_names);
+/// 태그 중 하나라도 일치하는지 검사하는 조건 (sample_tags 기준, 대소문자 무시)
+fn tag_any_sql(tags: &[String], args: &mut Vec<rusqlite::types::Value>) -> String {
+    args.extend(tags.iter().cloned().map(rusqlite::types::Value::Text));
+    format!(
+        "EXISTS (SELECT 1 FROM sample_tags st JOIN tags t ON t.id = st.tag_id
+                 WHERE st.sample_id = s.id AND t.name COLLATE NOCASE IN ({}))",
+        sql_placeholders(tags.len())
+    )
+}
 
-        // 오디오 파일 추가
-        let audio_path = Path::new(&sample.local_path);
-        if audio_path.exists() {
-            let audio_data = std::fs::read(audio_path)
-                .map_err(|e| format!("오디오 파일 읽기 실패 ({}): {}", sample.filename, e))?;
-            zip.start_file(&audio_name, options)
-                .map_err(|e| e.to_string())?;
-            zip.write_all(&audio_data).map_err(|e| e.to_string())?;
+/// 필터 → WHERE 절 (samples s LEFT JOIN packs p 기준)과 바인딩 값
+/// search_samples 외에 집계 등에서도 같은 조건을 쓰도록 분리
+fn build_filter_sql(filter: &SampleFilter) -> (String, Vec<rusqlite::types::Value>) {
+    use rusqlite::types::Value;
+
+    let mut conds: Vec<String> = Vec::new();
+    let mut args: Vec<Value> = Vec::new();
+
+    if let Some(q) = filter.q.as_deref().map(str::trim) {
+        if q.chars().count() >= SEARCH_MIN_QUERY_CHARS {
+            let (long, short): (Vec<&str>, Vec<&str>) = q
+                .split_whitespace()
+                .partition(|t| t.chars().count() >= SEARCH_TRIGRAM_CHARS);
+            if !long.is_empty() {
+                conds.push("s.id IN (SELECT rowid FROM samples_fts WHERE samples_fts MATCH ?)".into());
+                let expr: Vec<String> = long.iter().map(|t| fts_phrase(t)).collect();
+                args.push(Value::Text(expr.join(" AND ")));
+            }
+            // 트라이그램보다 짧은 단어는 FTS 인덱스와 같은 열(파일명/팩 이름/태그/장르)에서 부분 일치
+            for term in short {
+                conds.push(
+                    "(instr(LOWER(s.filename), ?) > 0 OR instr(LOWER(COALESCE(p.name, '')), ?) > 0
+                      OR instr(LOWER(COALESCE(s.tags, '')), ?) > 0
+                      OR instr(LOWER(COALESCE(s.genre, p.genre, '')), ?) > 0)"
+                        .into(),
+                );
+                for _ in 0..4 {
+                    args.push(Value::Text(term.to_lowercase()));
+                }
+            }
         }
+    }
 
-        // 메타데이터 JSON 생성
-        let tags_array: Option<Vec<String>> = sample
-            .tags
-            .as_ref()
-            .map(|t| t.split(',').map(|s| s.trim().to_string()).collect());
-
-        let metadata = serde_json::json!({
-            "filename": sample.filename,
-            "audio_key": sample.audio_key,
-            "bpm": sample.bpm,
-            "chord_type": sample.chord_type,
-            "duration_ms": sample.duration,
-            "genre": sample.genre,
-            "sample_type": sample.sample_type,
-            "tags": tags_array,
-            "pack_name": sample.pack_name,
-            "pack_uuid": sample.pack_uuid,
-            "pack_genre": sample.pack_genre,
-        });
+    if !filter.genres.is_empty() {
+        conds.push(format!(
+            "COALESCE(s.genre, p.genre) IN ({})",
+            sql_placeholders(filter.genres.len())
+        ));
+        args.extend(filter.genres.iter().cloned().map(Value::Text));
+    }
 
-        let json_bytes = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
-        zip.start_file(&meta_name, options)
-            .map_err(|e| e.to_string())?;
-        zip.write_all(json_bytes.as_bytes())
-            .map_err(|e| e.to_string())?;
+    // BPM 범위는 양 끝 포함 (소수점 경계도 그대로 비교)
+    if let Some(min) = filter.bpm_min {
+        conds.push("s.bpm >= ?".into());
+        args.push(Value::Real(min));
+    }
+    if let Some(max) = filter.bpm_max {
+        conds.push("s.bpm <= ?".into());
+        args.push(Value::Real(max));
+    }
 
-        exported += 1;
+    if !filter.keys.is_empty() {
+        conds.push(format!("{} IN ({})", KEY_LABEL_SQL, sql_placeholders(filter.keys.len())));
+        args.extend(filter.keys.iter().cloned().map(Value::Text));
+    }
+
+    if let Some(t) = filter.sample_type.as_deref().filter(|t| *t != "all") {
+        conds.push("s.sample_type = ?".into());
+        args.push(Value::Text(t.to_string()));
+    }
+
+    // 태그 집합 조건: 프론트엔드와 같이 대소문자 구분 없이 비교
+    if !filter.instruments.is_empty() {
+        conds.push(tag_any_sql(&filter.instruments, &mut args));
+    }
+    if !filter.include.is_empty() {
+        conds.push(tag_any_sql(&filter.include, &mut args));
+    }
+    if !filter.exclude.is_empty() {
+        conds.push(format!("NOT {}", tag_any_sql(&filter.exclude, &mut args)));
+    }
+
+    if let Some(uuid) = &filter.pack_uuid {
+        conds.push("s.pack_uuid = ?".into());
+        args.push(Value::Text(uuid.clone()));
+    }
+    if let Some(rules) = &filter.smart_rules {
+        // 스마트 플레이리스트: 규칙의 정렬/개수 제한까지 적용한 샘플 집합
+        let inner = rules.to_filter();
+        let (inner_where, inner_args) = build_filter_sql(&inner);
+        conds.push(format!(
+            "s.id IN (SELECT s.id FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                      WHERE {} ORDER BY {} LIMIT {})",
+            inner_where,
+            build_order_sql(&inner),
+            rules.limit.map_or(-1, |l| l as i64)
+        ));
+        args.extend(inner_args);
+    } else if let Some(pid) = filter.playlist_id {
+        conds.push("s.id IN (SELECT sample_id FROM playlist_samples WHERE playlist_id = ?)".into());
+        args.push(Value::Integer(pid));
+    }
 
-        // 진행 상황 이벤트 발행
-        if i % 5 == 0 || i + 1 == total {
-            let _ = app.emit(
-                "export-progress",
-                ExportProgress {
-                    current: i + 1,
-                    total,
-                    current_file: sample.filename.clone(),
-                },
-            );
-        }
+    if let Some(after) = &filter.added_after {
+        conds.push("s.created_at >= ?".into());
+        args.push(Value::Text(after.clone()));
+    }
+    if let Some(before) = &filter.added_before {
+        conds.push("s.created_at < ?".into());
+        args.push(Value::Text(before.clone()));
+    }
+    if filter.bpm_mismatch {
+        conds.push("s.bpm_mismatch = 1".into());
     }
 
-    zip.finish().map_err(|e| e.to_string())?;
-    Ok(exported)
+    let where_sql = if conds.is_empty() {
+        "1".to_string()
+    } else {
+        conds.join(" AND ")
+    };
+    (where_sql, args)
 }
 
-// ── Playlist commands ────────────────────────────────────────────────
+/// 정렬 → ORDER BY 절 (BPM/길이는 값 없는 샘플을 항상 뒤로)
+fn build_order_sql(filter: &SampleFilter) -> String {
+    let desc = filter.sort_dir.as_deref() == Some("desc");
+    let dir = if desc { "DESC" } else { "ASC" };
+    match filter.sort_by.as_deref() {
+        Some("bpm") => format!("s.bpm IS NULL, s.bpm {}, s.filename COLLATE NOCASE", dir),
+        Some("duration") => {
+            format!("s.duration IS NULL, s.duration {}, s.filename COLLATE NOCASE", dir)
+        }
+        // recent: 기본(asc)이 최신순
+        Some("recent") => format!(
+            "s.created_at {}, s.id {}",
+            if desc { "ASC" } else { "DESC" },
+            if desc { "ASC" } else { "DESC" }
+        ),
+        // 시드 기반 의사 난수 순서 (페이지를 넘겨도 순서가 바뀌지 않음)
+        // 곱하기 전에 법으로 줄이고 승수를 2^31 미만으로 둬서 곱이 i64를 넘지 않음
+        Some("shuffle") => format!(
+            "(((s.id + {}) % 4294967291) * 1103515245) % 4294967291, s.id",
+            filter.shuffle_seed.unwrap_or(0).rem_euclid(4294967291)
+        ),
+        _ => format!("s.filename COLLATE NOCASE {}", dir),
+    }
+}
 
 #[tauri::command]
-fn get_playlists(state: State<AppState>) -> Result<Vec<Playlist>, String> {
+fn search_samples(
+    mut filter: SampleFilter,
+    page: Option<usize>,
+    page_size: Option<usize>,
+    state: State<AppState>,
+) -> Result<SamplePage, String> {
     let db = state.db.lock().unwrap();
-    let mut stmt = db
-        .prepare(
-            "SELECT p.id, p.name, p.color, p.created_at, COUNT(ps.sample_id) as sample_count
-             FROM playlists p
-             LEFT JOIN playlist_samples ps ON ps.playlist_id = p.id
-             GROUP BY p.id
-             ORDER BY p.created_at DESC",
+    resolve_sample_filter(&db, &mut filter)?;
+    let root = get_library_root(&db);
+    let page = page.unwrap_or(0);
+    let page_size = page_size
+        .unwrap_or(SEARCH_DEFAULT_PAGE_SIZE)
+        .clamp(1, SEARCH_MAX_PAGE_SIZE);
+
+    let (where_sql, args) = build_filter_sql(&filter);
+
+    let total: i64 = db
+        .query_row(
+            &format!(
+                "SELECT COUNT(*) FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid WHERE {}",
+                where_sql
+            ),
+            rusqlite::params_from_iter(args.iter()),
+            |row| row.get(0),
         )
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| format!("검색 실패: {}", e))?;
 
-    let playlists = stmt
-        .query_map([], |row| {
-            Ok(Playlist {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                created_at: row.get(3)?,
-                sample_count: row.get::<_, i64>(4)? as usize,
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {}
+             FROM samples s
+             LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE {}
+             ORDER BY {}
+             LIMIT {} OFFSET {}",
+            SAMPLE_COLUMNS,
+            where_sql,
+            build_order_sql(&filter),
+            page_size,
+            page.saturating_mul(page_size)
+        ))
+        .map_err(|e| format!("검색 실패: {}", e))?;
+
+    let samples = stmt
+        .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+            sample_from_row(row, &root)
+        })
+        .map_err(|e| format!("검색 실패: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(SamplePage {
+        samples,
+        total: total as usize,
+        page,
+        page_size,
+    })
+}
+
+// ── Facets ──────────────────────────────────────────────────────────
+
+/// 프론트엔드 INSTRUMENT_KEYWORDS와 같은 악기 태그 목록 (소문자)
+const INSTRUMENT_TAGS: &[&str] = &[
+    "drums", "snares", "hats", "kicks", "percussion", "cymbals", "toms",
+    "tambourine", "shaker", "claps", "congas", "bongos", "rim", "hi-hats",
+    "synth", "pads", "leads", "keys", "piano", "organ", "rhodes", "plucks",
+    "bass", "sub", "808",
+    "guitar", "electric guitar", "acoustic guitar",
+    "vocals", "voice", "adlib", "vocal chops",
+    "strings", "violin", "cello", "viola",
+    "brass", "trumpet", "saxophone", "horn", "flute", "woodwinds",
+    "bells", "mallets", "marimba", "xylophone", "vibraphone",
+    "fx", "risers", "sweeps", "impacts", "textures", "foley", "noise",
+    "grooves", "fills", "tops", "loops",
+];
+
+const BPM_BUCKET_SIZE: i32 = 10;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BpmBucketCount {
+    /// 구간 min ≤ bpm ≤ max — bpm_min/bpm_max 필터와 같은 양 끝 포함 규칙
+    /// (경계값 BPM은 이웃한 두 구간에 모두 셈, count = 이 구간으로 필터했을 때의 total)
+    pub min: i32,
+    pub max: i32,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SampleFacets {
+    /// 전체 필터 적용 시 결과 수 (search_samples의 total과 같음)
+    pub total: usize,
+    pub genres: Vec<FacetCount>,
+    pub instruments: Vec<FacetCount>,
+    pub tags: Vec<FacetCount>,
+    pub keys: Vec<FacetCount>,
+    pub sample_types: Vec<FacetCount>,
+    pub bpm_buckets: Vec<BpmBucketCount>,
+}
+
+/// value 식 기준 그룹 카운트 (빈 값 제외, 많은 순)
+fn facet_counts(
+    db: &Connection,
+    filter: &SampleFilter,
+    value_sql: &str,
+) -> Result<Vec<FacetCount>, String> {
+    let (where_sql, args) = build_filter_sql(filter);
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {v} AS value, COUNT(*) AS n
+             FROM samples s
+             LEFT JOIN packs p ON s.pack_uuid = p.uuid
+             WHERE ({w}) AND {v} IS NOT NULL AND {v} <> ''
+             GROUP BY value
+             ORDER BY n DESC, value",
+            v = value_sql,
+            w = where_sql
+        ))
+        .map_err(|e| format!("집계 실패: {}", e))?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+            Ok(FacetCount {
+                value: row.get(0)?,
+                count: row.get::<_, i64>(1)? as usize,
             })
         })
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("집계 실패: {}", e))?
         .filter_map(|r| r.ok())
         .collect();
+    Ok(rows)
+}
 
-    Ok(playlists)
+/// 태그별 샘플 수 집계 (sample_tags 기준)
+/// only_instruments면 INSTRUMENT_TAGS에 있는 태그만
+fn tag_facet_counts(
+    db: &Connection,
+    filter: &SampleFilter,
+    only_instruments: bool,
+) -> Result<Vec<FacetCount>, String> {
+    let (where_sql, mut args) = build_filter_sql(filter);
+    let instrument_cond = if only_instruments {
+        args.extend(
+            INSTRUMENT_TAGS
+                .iter()
+                .map(|t| rusqlite::types::Value::Text(t.to_string())),
+        );
+        format!("AND LOWER(t.name) IN ({})", sql_placeholders(INSTRUMENT_TAGS.len()))
+    } else {
+        String::new()
+    };
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT t.name, COUNT(*) AS n
+             FROM sample_tags st
+             JOIN tags t ON t.id = st.tag_id
+             WHERE st.sample_id IN (
+                 SELECT s.id FROM samples s
+                 LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                 WHERE {}
+             ) {}
+             GROUP BY t.id
+             ORDER BY n DESC, t.name",
+            where_sql, instrument_cond
+        ))
+        .map_err(|e| format!("집계 실패: {}", e))?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+            Ok(FacetCount {
+                value: row.get(0)?,
+                count: row.get::<_, i64>(1)? as usize,
+            })
+        })
+        .map_err(|e| format!("집계 실패: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(rows)
 }
 
+/// 현재 필터 기준 facet별 개수
+/// 각 facet은 자기 자신의 선택만 뺀 나머지 필터로 집계 (다중 선택 시 다른 값이 0이 되지 않도록)
+/// → 값 v의 개수 = 그 facet을 [v]로 바꿔 search_samples를 호출했을 때의 total
 #[tauri::command]
-fn create_playlist(name: String, color: Option<String>, state: State<AppState>) -> Result<Playlist, String> {
+fn get_facets(mut filter: SampleFilter, state: State<AppState>) -> Result<SampleFacets, String> {
     let db = state.db.lock().unwrap();
-    db.execute(
-        "INSERT INTO playlists (name, color) VALUES (?1, ?2)",
-        params![name, color],
-    )
-    .map_err(|e| format!("플레이리스트 생성 실패: {}", e))?;
+    resolve_sample_filter(&db, &mut filter)?;
 
-    let id = db.last_insert_rowid();
-    let playlist = db
+    let (where_sql, args) = build_filter_sql(&filter);
+    let total: i64 = db
         .query_row(
-            "SELECT id, name, color, created_at FROM playlists WHERE id = ?1",
-            params![id],
-            |row| {
-                Ok(Playlist {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                    created_at: row.get(3)?,
-                    sample_count: 0,
-                })
-            },
+            &format!(
+                "SELECT COUNT(*) FROM samples s LEFT JOIN packs p ON s.pack_uuid = p.uuid WHERE {}",
+                where_sql
+            ),
+            rusqlite::params_from_iter(args.iter()),
+            |row| row.get(0),
         )
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| format!("집계 실패: {}", e))?;
 
-    Ok(playlist)
+    let without = |clear: fn(&mut SampleFilter)| {
+        let mut f = filter.clone();
+        clear(&mut f);
+        f
+    };
+
+    let genres = facet_counts(&db, &without(|f| f.genres.clear()), "COALESCE(s.genre, p.genre)")?;
+    let keys = facet_counts(&db, &without(|f| f.keys.clear()), KEY_LABEL_SQL)?;
+    let sample_types = facet_counts(&db, &without(|f| f.sample_type = None), "s.sample_type")?;
+    let instruments = tag_facet_counts(&db, &without(|f| f.instruments.clear()), true)?;
+    let tags = tag_facet_counts(&db, &without(|f| f.include.clear()), false)?;
+
+    let bpm_filter = without(|f| {
+        f.bpm_min = None;
+        f.bpm_max = None;
+    });
+    let (bpm_where, bpm_args) = build_filter_sql(&bpm_filter);
+    // 필터가 양 끝을 포함하므로 구간 경계에 정확히 걸친 BPM은 아래 구간에도 한 번 더 셈
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT bucket, COUNT(*) FROM (
+                 SELECT CAST(s.bpm / {size} AS INTEGER) * {size} AS bucket
+                 FROM samples s
+                 LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                 WHERE s.bpm IS NOT NULL AND ({w})
+                 UNION ALL
+                 SELECT CAST(s.bpm AS INTEGER) - {size}
+                 FROM samples s
+                 LEFT JOIN packs p ON s.pack_uuid = p.uuid
+                 WHERE s.bpm >= {size} AND s.bpm = CAST(s.bpm AS INTEGER)
+                   AND CAST(s.bpm AS INTEGER) % {size} = 0 AND ({w})
+             )
+             GROUP BY bucket
+             ORDER BY bucket",
+            size = BPM_BUCKET_SIZE,
+            w = bpm_where
+        ))
+        .map_err(|e| format!("집계 실패: {}", e))?;
+    let bpm_args: Vec<&rusqlite::types::Value> = bpm_args.iter().chain(bpm_args.iter()).collect();
+    let bpm_buckets = stmt
+        .query_map(rusqlite::params_from_iter(bpm_args), |row| {
+            let min: i32 = row.get(0)?;
+            Ok(BpmBucketCount {
+                min,
+                max: min + BPM_BUCKET_SIZE,
+                count: row.get::<_, i64>(1)? as usize,
+            })
+        })
+        .map_err(|e| format!("집계 실패: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(SampleFacets {
+        total: total as usize,
+        genres,
+        instruments,
+        tags,
+        keys,
+        sample_types,
+        bpm_buckets,
+    })
 }
 
-#[tauri::command]
-fn rename_playlist(playlist_id: i64, name: String, state: State<AppState>) -> Result<(), String> {
-    let db = state.db.lock().unwrap();
-    db.execute(
-        "UPDATE playlists SET name = ?1 WHERE id = ?2",
-        params![name, playlist_id],
-    )
-    .map_err(|e| format!("플레이리스트 이름 변경 실패: {}", e))?;
-    Ok(())
+// ── Tags ────────────────────────────────────────────────────────────
+
+/// 쉼표 구분 태그 문자열 → json_each로 펼칠 JSON 배열 식 (트리거 안에서는 CTE를 쓸 수 없음)
+/// 줄바꿈/탭은 공백으로 바꾸고, 그래도 JSON이 깨지면 빈 배열로 처리해 샘플 쓰기가 실패하지 않게 함
+fn tag_json_array_sql(col: &str) -> String {
+    let arr = format!(
+        r#"('["' || REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(COALESCE({}, ''),
+            '\', '\\'), '"', '\"'), char(10), ' '), char(13), ' '), char(9), ' '), ',', '","') || '"]')"#,
+        col
+    );
+    format!("(CASE WHEN json_valid({a}) THEN {a} ELSE '[]' END)", a = arr)
 }
 
-#[tauri::command]
-fn update_playlist_color(playlist_id: i64, color: Option<String>, state: State<AppState>) -> Result<(), String> {
-    let db = state.db.lock().unwrap();
-    db.execute(
-        "UPDATE playlists SET color = ?1 WHERE id = ?2",
-        params![color, playlist_id],
-    )
-    .map_err(|e| format!("플레이리스트 색상 변경 실패: {}", e))?;
+/// samples.tags를 기준으로 tags/sample_tags를 유지하는 트리거
+/// (임포트·동기화·편집 등 모든 쓰기 경로가 문자열만 갱신해도 정규화 테이블이 따라옴)
+fn init_tag_tables(db: &Connection) -> Result<(), String> {
+    let insert_tags = |row: &str| {
+        let arr = tag_json_array_sql(&format!("{}.tags", row));
+        format!(
+            "INSERT OR IGNORE INTO tags (name)
+                 SELECT TRIM(value) FROM json_each({arr}) WHERE TRIM(value) <> '';
+             INSERT OR IGNORE INTO sample_tags (sample_id, tag_id)
+                 SELECT {row}.id, t.id FROM json_each({arr}) j JOIN tags t ON t.name = TRIM(j.value);",
+            arr = arr,
+            row = row
+        )
+    };
+    db.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE
+        );
+        CREATE TABLE IF NOT EXISTS sample_tags (
+            sample_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (sample_id, tag_id),
+            FOREIGN KEY (sample_id) REFERENCES samples(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_sample_tags_tag ON sample_tags (tag_id);
+        CREATE TABLE IF NOT EXISTS tag_classifier (
+            tag TEXT NOT NULL,
+            token TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            PRIMARY KEY (tag, token)
+        );
+        CREATE TRIGGER IF NOT EXISTS sample_tags_insert AFTER INSERT ON samples
+            WHEN new.tags IS NOT NULL BEGIN
+            {ins}
+        END;
+        CREATE TRIGGER IF NOT EXISTS sample_tags_update AFTER UPDATE OF tags ON samples
+            WHEN new.tags IS NOT old.tags BEGIN
+            DELETE FROM sample_tags WHERE sample_id = new.id;
+            {ins}
+        END;
+        CREATE TRIGGER IF NOT EXISTS sample_tags_delete AFTER DELETE ON samples BEGIN
+            DELETE FROM sample_tags WHERE sample_id = old.id;
+        END;",
+        ins = insert_tags("new")
+    ))
+    .map_err(|e| format!("태그 테이블 초기화 실패: {}", e))?;
+
+    // Migration: 태그별 출처/신뢰도 (분류기 제안 등, NULL이면 샘플의 tags 출처를 따름)
+    let _ = db.execute("ALTER TABLE sample_tags ADD COLUMN source TEXT", []);
+    let _ = db.execute("ALTER TABLE sample_tags ADD COLUMN confidence REAL", []);
+
+    // Migration: 기존 samples.tags → sample_tags (처음 한 번)
+    let populated: bool = db
+        .query_row("SELECT EXISTS (SELECT 1 FROM sample_tags)", [], |row| row.get(0))
+        .unwrap_or(true);
+    if !populated {
+        let arr = tag_json_array_sql("s.tags");
+        db.execute_batch(&format!(
+            "INSERT OR IGNORE INTO tags (name)
+                 SELECT TRIM(j.value) FROM samples s, json_each({arr}) j
+                 WHERE s.tags IS NOT NULL AND TRIM(j.value) <> '';
+             INSERT OR IGNORE INTO sample_tags (sample_id, tag_id)
+                 SELECT s.id, t.id FROM samples s, json_each({arr}) j
+                 JOIN tags t ON t.name = TRIM(j.value)
+                 WHERE s.tags IS NOT NULL;",
+            arr = arr
+        ))
+        .map_err(|e| format!("태그 마이그레이션 실패: {}", e))?;
+    }
     Ok(())
 }
 
-#[tauri::command]
-fn delete_playlist(playlist_id: i64, state: State<AppState>) -> Result<(), String> {
-    let db = state.db.lock().unwrap();
-    db.execute("DELETE FROM playlist_samples WHERE playlist_id = ?1", params![playlist_id])
-        .map_err(|e| e.to_string())?;
-    db.execute("DELETE FROM playlists WHERE id = ?1", params![playlist_id])
-        .map_err(|e| format!("플레이리스트 삭제 실패: {}", e))?;
-    Ok(())
+#[derive(Debug, Serialize, Clone)]
+pub struct TagInfo {
+    pub id: i64,
+    pub name: String,
+    pub sample_count: usize,
 }
 
-#[tauri::command]
-fn add_to_playlist(playlist_id: i64, sample_ids: Vec<i64>, state: State<AppState>) -> Result<(), String> {
-    let db = state.db.lock().unwrap();
-    let mut stmt = db
-        .prepare("INSERT OR IGNORE INTO playlist_samples (playlist_id, sample_id) VALUES (?1, ?2)")
-        .map_err(|e| e.to_string())?;
-    for sid in &sample_ids {
-        stmt.execute(params![playlist_id, sid]).map_err(|e| e.to_string())?;
+/// 쉼표 구분 태그 문자열 → 목록 (공백 제거, 빈 항목 제외)
+fn split_tags(tags: Option<&str>) -> Vec<String> {
+    tags.map(|t| {
+        t.split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// 태그 이름 검증 (쉼표는 구분자라 사용 불가)
+fn normalize_tag_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("태그 이름이 비어 있습니다".to_string());
     }
-    Ok(())
+    if name.contains(',') {
+        return Err("태그 이름에 쉼표(,)를 쓸 수 없습니다".to_string());
+    }
+    Ok(name.to_string())
 }
 
-#[tauri::command]
-fn remove_from_playlist(playlist_id: i64, sample_ids: Vec<i64>, state: State<AppState>) -> Result<(), String> {
-    let db = state.db.lock().unwrap();
-    for sid in &sample_ids {
-        db.execute(
-            "DELETE FROM playlist_samples WHERE playlist_id = ?1 AND sample_id = ?2",
-            params![playlist_id, sid],
+/// 주어진 태그 중 하나라도 가진 샘플 id
+fn sample_ids_with_tags(conn: &Connection, names: &[String]) -> Result<Vec<i64>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT DISTINCT st.sample_id FROM sample_tags st
+             JOIN tags t ON t.id = st.tag_id
+             WHERE t.name IN ({})",
+            sql_placeholders(names.len())
+        ))
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map(rusqlite::params_from_iter(names.iter()), |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(ids)
+}
+
+/// 샘플들의 태그 목록을 고쳐 samples.tags에 다시 저장 (sample_tags는 트리거가 갱신)
+/// 사용자 편집이므로 출처를 user로 기록. 실제로 바뀐 샘플 수 반환
+fn rewrite_sample_tags(
+    conn: &Connection,
+    sample_ids: &[i64],
+    edit: impl Fn(&mut Vec<String>),
+) -> Result<usize, String> {
+    let mut select = conn
+        .prepare_cached("SELECT tags FROM samples WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+    let mut update = conn
+        .prepare_cached(
+            "UPDATE samples SET tags = ?1, tags_source = ?2, tags_confidence = ?3 WHERE id = ?4",
         )
         .map_err(|e| e.to_string())?;
+
+    let mut changed = 0;
+    for &id in sample_ids {
+        let Ok(current) = select.query_row(params![id], |row| row.get::<_, Option<String>>(0))
+        else {
+            continue;
+        };
+        let before = split_tags(current.as_deref());
+        let mut tags = before.clone();
+        edit(&mut tags);
+        // 순서를 유지하며 중복 제거
+        let mut seen = HashSet::new();
+        tags.retain(|t| !t.is_empty() && seen.insert(t.clone()));
+        if tags == before {
+            continue;
+        }
+        let joined = if tags.is_empty() { None } else { Some(tags.join(",")) };
+        update
+            .execute(params![joined, SOURCE_USER, CONFIDENCE_EXACT, id])
+            .map_err(|e| format!("태그 저장 실패: {}", e))?;
+        changed += 1;
     }
+    Ok(changed)
+}
+
+/// 어떤 샘플도 쓰지 않는 태그 삭제
+fn prune_unused_tags(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM tags WHERE id NOT IN (SELECT DISTINCT tag_id FROM sample_tags)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// 사용 중인 태그와 샘플 수 (많은 순)
 #[tauri::command]
-fn get_playlist_samples(playlist_id: i64, state: State<AppState>) -> Result<Vec<Sample>, String> {
+fn list_tags(state: State<AppState>) -> Result<Vec<TagInfo>, String> {
     let db = state.db.lock().unwrap();
     let mut stmt = db
         .prepare(
-            "SELECT s.id, s.local_path, s.filename, s.audio_key, s.bpm, s.chord_type,
-                    s.duration, COALESCE(s.genre, p.genre) as genre,
-                    s.sample_type, s.tags,
-                    s.pack_uuid, p.name as pack_name, p.genre as pack_genre,
-                    s.created_at
-             FROM playlist_samples ps
-             JOIN samples s ON s.id = ps.sample_id
-             LEFT JOIN packs p ON s.pack_uuid = p.uuid
-             WHERE ps.playlist_id = ?1
-             ORDER BY ps.added_at DESC",
+            "SELECT t.id, t.name, COUNT(*) AS n
+             FROM tags t
+             JOIN sample_tags st ON st.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY n DESC, t.name COLLATE NOCASE",
         )
         .map_err(|e| e.to_string())?;
-
-    let samples = stmt
-        .query_map(params![playlist_id], |row| {
-            Ok(Sample {
+    let tags = stmt
+        .query_map([], |row| {
+            Ok(TagInfo {
                 id: row.get(0)?,
-                local_path: row.get(1)?,
-                filename: row.get(2)?,
-                audio_key: row.get(3)?,
-                bpm: row.get(4)?,
-                chord_type: row.get(5)?,
-                duration: row.get(6)?,
-                genre: row.get(7)?,
-                sample_type: row.get(8)?,
-                tags: row.get(9)?,
-                pack_uuid: row.get(10)?,
-                pack_name: row.get(11)?,
-                pack_genre: row.get(12)?,
-                created_at: row.get(13)?,
+                name: row.get(1)?,
+                sample_count: row.get::<_, i64>(2)? as usize,
             })
         })
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
+    Ok(tags)
+}
 
-    Ok(samples)
+/// 여러 태그를 하나로 합침 (target이 이미 있으면 중복 없이). 바뀐 샘플 수 반환
+#[tauri::command]
+fn merge_tags(
+    sources: Vec<String>,
+    target: String,
+    app: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<usize, String> {
+    let target = normalize_tag_name(&target)?;
+    let sources: Vec<String> = sources
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty() && *s != target)
+        .collect();
+    if sources.is_empty() {
+        return Ok(0);
+    }
+
+    let mut db = state.db.lock().unwrap();
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    let ids = sample_ids_with_tags(&tx, &sources)?;
+    let changed = rewrite_sample_tags(&tx, &ids, |tags| {
+        for t in tags.iter_mut() {
+            if sources.contains(t) {
+                *t = target.clone();
+            }
+        }
+    })?;
+    prune_unused_tags(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    // 태그가 바뀌면 분류기 학습 데이터도 바뀜 — 응답을 막지 않도록 백그라운드에서 재학습
+    if changed > 0 {
+        schedule_classifier_retrain(&app);
+    }
+    Ok(changed)
+}
+
+/// 태그 이름 변경 (새 이름이 이미 있으면 병합과 같음). 바뀐 샘플 수 반환
+#[tauri::command]
+fn rename_tag(
+    name: String,
+    new_name: String,
+    app: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<usize, String> {
+    merge_tags(vec![name], new_name, app, state)
+}
+
+/// 모든 샘플에서 태그 제거. 바뀐 샘플 수 반환
+#[tauri::command]
+fn delete_tag(name: String, app: tauri::AppHandle, state: State<AppState>) -> Result<usize, String> {
+    let mut db = state.db.lock().unwrap();
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    let names = vec![name];
+    let ids = sample_ids_with_tags(&tx, &names)?;
+    let changed = rewrite_sample_tags(&tx, &ids, |tags| tags.retain(|t| *t != names[0]))?;
+    prune_unused_tags(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    // 태그가 바뀌면 분류기 학습 데이터도 바뀜 — 응답을 막지 않도록 백그라운드에서 재학습
+    if changed > 0 {
+        schedule_classifier_retrain(&app);
+    }
+    Ok(changed)
+}
+
+/// 여러 샘플에 태그 일괄 추가/제거. 바뀐 샘플 수 반환
+#[tauri::command]
+fn apply_tags(
+    sample_ids: Vec<i64>,
+    add: Vec<String>,
+    remove: Vec<String>,
+    app: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<usize, String> {
+    let add = add
+        .iter()
+        .map(|t| normalize_tag_name(t))
+        .collect::<Result<Vec<_>, _>>()?;
+    let remove: Vec<String> = remove.iter().map(|t| t.trim().to_string()).collect();
+
+    let mut db = state.db.lock().unwrap();
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    let changed = rewrite_sample_tags(&tx, &sample_ids, |tags| {
+        tags.retain(|t| !remove.contains(t));
+        tags.extend(add.iter().cloned());
+    })?;
+    prune_unused_tags(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    // 태그가 바뀌면 분류기 학습 데이터도 바뀜 — 응답을 막지 않도록 백그라운드에서 재학습
+    if changed > 0 {
+        schedule_classifier_retrain(&app);
+    }
+    Ok(changed)
 }
 
 // ── Drag icon path ──────────────────────────────────────────────────
@@ -2558,7 +9692,7 @@ pub fn run() {
     let slice_path = get_slice_path().expect("Failed to get Slice path");
     std::fs::create_dir_all(&slice_path).expect("Failed to create Slice directory");
 
-    let db_path = slice_path.join("slice.db");
+    let db_path = get_db_path().expect("Failed to get database path");
     let db = Connection::open(&db_path).expect("Failed to open database");
     init_db(&db).expect("Failed to initialize database");
 
@@ -2569,17 +9703,46 @@ pub fn run() {
         .plugin(tauri_plugin_process::init())
         .manage(AppState {
             db: Mutex::new(db),
+            busy: AtomicUsize::new(0),
+            watcher: Mutex::new(None),
+            jobs: Mutex::new(std::collections::BTreeMap::new()),
+            next_job_id: AtomicU64::new(0),
+            classifier_retrain: AtomicU64::new(0),
+        })
+        .setup(|app| {
+            // 사용자가 끈 경우나 Splice가 없으면 감시 없이 시작 (설정에서 다시 켤 수 있음)
+            let state = app.state::<AppState>();
+            let enabled = get_setting(&state.db.lock().unwrap(), SETTING_LIBRARY_WATCHER)
+                .is_none_or(|v| v != "0");
+            if enabled {
+                let _ = start_library_watcher(app.handle());
+            }
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             check_library_status,
             import_from_splice,
+            set_library_watcher,
+            get_library_location,
+            set_library_location,
+            relocate_library,
+            list_splice_dbs,
+            set_splice_db_selection,
+            set_splice_db_override,
             scan_external_folder,
             check_pack_name_conflicts,
             import_external_folder,
+            list_jobs,
+            cancel_job,
             scan_library,
             get_all_samples,
             get_pack_samples,
             get_waveform,
+            scan_library_health,
+            find_relink_candidates,
+            apply_relinks,
+            detect_sample_keys,
+            reanalyze_samples,
             export_samples,
             update_sample,
             update_pack,
@@ -2594,8 +9757,165 @@ pub fn run() {
             delete_playlist,
             add_to_playlist,
             remove_from_playlist,
+            update_playlist_rules,
             get_playlist_samples,
+            search_samples,
+            get_facets,
+            list_tags,
+            rename_tag,
+            merge_tags,
+            delete_tag,
+            apply_tags,
+            get_sample_tags,
+            get_sample_markers,
+            write_tags_to_files,
+            retrain_tag_classifier,
+            get_metadata_rules,
+            save_metadata_rules,
+            reset_metadata_rules,
+            export_metadata_rules,
+            import_metadata_rules,
+            preview_metadata_rules,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16비트 모노 PCM WAV (extra 청크는 fmt와 data 사이에 들어감)
+    fn wav_fixture(name: &str, extra: &[(&[u8; 4], Vec<u8>)]) -> PathBuf {
+        let mut body = b"WAVE".to_vec();
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&1u16.to_le_bytes());
+        fmt.extend_from_slice(&1u16.to_le_bytes());
+        fmt.extend_from_slice(&44100u32.to_le_bytes());
+        fmt.extend_from_slice(&88200u32.to_le_bytes());
+        fmt.extend_from_slice(&2u16.to_le_bytes());
+        fmt.extend_from_slice(&16u16.to_le_bytes());
+        write_riff_chunk(&mut body, b"fmt ", &fmt).unwrap();
+        for (id, data) in extra {
+            write_riff_chunk(&mut body, *id, data).unwrap();
+        }
+        write_riff_chunk(&mut body, b"data", &[0u8; 400]).unwrap();
+        let mut file = b"RIFF".to_vec();
+        file.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        file.extend_from_slice(&body);
+
+        let dir = std::env::temp_dir().join(format!("slice-wav-tags-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, file).unwrap();
+        path
+    }
+
+    /// 최상위 청크 (id, 본문) 목록
+    fn wav_chunks(path: &Path) -> Vec<([u8; 4], Vec<u8>)> {
+        let bytes = std::fs::read(path).unwrap();
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(le_u32(&bytes, 4).unwrap() as usize, bytes.len() - 8);
+        let mut chunks = Vec::new();
+        let mut at = 12;
+        while at + 8 <= bytes.len() {
+            let size = le_u32(&bytes, at + 4).unwrap() as usize;
+            let id = [bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]];
+            chunks.push((id, bytes[at + 8..at + 8 + size].to_vec()));
+            at += 8 + size + (size & 1);
+        }
+        chunks
+    }
+
+    fn tag_target(path: &Path, tags: FileTags) -> TagWriteTarget {
+        TagWriteTarget {
+            id: 1,
+            filename: path.file_name().unwrap().to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            tags,
+            one_shot: false,
+            bars: None,
+        }
+    }
+
+    #[test]
+    fn wav_genre_only_edit_adds_no_acid_chunk() {
+        let path = wav_fixture("genre-only.wav", &[]);
+        let target = tag_target(
+            &path,
+            FileTags {
+                genre: Some("House".to_string()),
+                ..Default::default()
+            },
+        );
+        let changes = write_wav_tags(&path, &target, false).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "genre");
+
+        let chunks = wav_chunks(&path);
+        assert!(chunks.iter().all(|(id, _)| id != b"acid"));
+        let lists: Vec<_> = chunks.iter().filter(|(id, _)| id == b"LIST").collect();
+        assert_eq!(lists.len(), 1);
+        assert!(lists[0].1.starts_with(b"INFO"));
+        assert!(lists[0].1.windows(6).any(|w| w == b"House\0"));
+        assert_eq!(chunks.last().unwrap().0, *b"data");
+    }
+
+    #[test]
+    fn wav_genre_only_edit_keeps_existing_acid_chunk() {
+        let mut acid = vec![0u8; 24];
+        acid[0..4].copy_from_slice(&1u32.to_le_bytes());
+        acid[20..24].copy_from_slice(&128.0f32.to_le_bytes());
+        let path = wav_fixture("keep-acid.wav", &[(b"acid", acid.clone())]);
+        let target = tag_target(
+            &path,
+            FileTags {
+                genre: Some("Techno".to_string()),
+                ..Default::default()
+            },
+        );
+        write_wav_tags(&path, &target, false).unwrap();
+        let chunks = wav_chunks(&path);
+        let acids: Vec<_> = chunks.iter().filter(|(id, _)| id == b"acid").collect();
+        assert_eq!(acids.len(), 1);
+        assert_eq!(acids[0].1, acid);
+    }
+
+    #[test]
+    fn wav_bpm_edit_writes_acid_tempo() {
+        let path = wav_fixture("bpm.wav", &[]);
+        let target = tag_target(
+            &path,
+            FileTags {
+                bpm: Some(124.0),
+                ..Default::default()
+            },
+        );
+        write_wav_tags(&path, &target, false).unwrap();
+        let chunks = wav_chunks(&path);
+        let acid = &chunks.iter().find(|(id, _)| id == b"acid").unwrap().1;
+        let tempo = f32::from_le_bytes([acid[20], acid[21], acid[22], acid[23]]);
+        assert_eq!(tempo, 124.0);
+        assert!(chunks.iter().all(|(id, _)| id != b"LIST"));
+    }
+
+    #[test]
+    fn wav_oversized_info_is_not_duplicated() {
+        let mut info = b"INFO".to_vec();
+        write_riff_chunk(&mut info, b"ICMT", &vec![b'x'; EMBEDDED_CHUNK_MAX_BYTES as usize + 16]).unwrap();
+        let path = wav_fixture("oversized-info.wav", &[(b"LIST", info)]);
+        let before = std::fs::read(&path).unwrap();
+        let target = tag_target(
+            &path,
+            FileTags {
+                genre: Some("House".to_string()),
+                ..Default::default()
+            },
+        );
+        let changes = write_wav_tags(&path, &target, false).unwrap();
+        assert!(changes.is_empty());
+        let chunks = wav_chunks(&path);
+        assert_eq!(chunks.iter().filter(|(id, _)| id == b"LIST").count(), 1);
+        assert_eq!(std::fs::read(&path).unwrap(), before);
+    }
+}
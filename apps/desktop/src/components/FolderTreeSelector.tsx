@@ -31,12 +31,6 @@ function isChildOfAny(path: string, paths: Set<string>): boolean {
   return false;
 }
 
-// 경로에서 폴더명 추출
-function folderNameFromPath(path: string): string {
-  const parts = path.split("/");
-  return parts[parts.length - 1] || "Unknown";
-}
-
 interface TreeNodeProps {
   node: FolderNode;
   depth: number;
@@ -303,11 +297,11 @@ export default function FolderTreeSelector({ tree, onConfirm, onCancel }: Folder
   // "가져오기" 클릭 → 충돌 확인 → 충돌 있으면 충돌 UI, 없으면 바로 임포트
   const handleImportClick = useCallback(async () => {
     const paths = Array.from(selected);
-    const names = paths.map(folderNameFromPath);
 
     setChecking(true);
     try {
-      const result = await invoke<PackConflict[]>("check_pack_name_conflicts", { packNames: names });
+      // 임포트와 같은 방식으로 팩을 나눠서 확인 (내보내기 번들은 원래 팩 이름)
+      const result = await invoke<PackConflict[]>("check_pack_name_conflicts", { selectedPaths: paths });
       if (result.length > 0) {
         setConflicts(result);
         setStep("conflict");
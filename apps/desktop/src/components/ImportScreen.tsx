@@ -5,7 +5,7 @@ import { open } from "@tauri-apps/plugin-dialog";
 import type { LibraryStatus, ImportProgress, ImportResult, FolderNode } from "@/types";
 import { Button } from "@/components/ui/button";
 import { Dialog, DialogContent } from "@/components/ui/dialog";
-import { FolderOpen } from "lucide-react";
+import { FileArchive, FolderOpen } from "lucide-react";
 import { useI18n } from "@/contexts/I18nContext";
 import FolderTreeSelector from "@/components/FolderTreeSelector";
 
@@ -48,8 +48,11 @@ export default function ImportScreen({ status, onComplete }: ImportScreenProps)
     }
   };
 
-  const handleExternalImport = async () => {
-    const selected = await open({ directory: true, title: t("import.folderDialogTitle") });
+  // 폴더 또는 ZIP 파일 선택 → 스캔 → 트리 표시
+  const handleExternalImport = async (zip: boolean) => {
+    const selected = zip
+      ? await open({ title: t("import.zipDialogTitle"), filters: [{ name: "ZIP", extensions: ["zip"] }] })
+      : await open({ directory: true, title: t("import.folderDialogTitle") });
     if (!selected) return;
 
     setError(null);
@@ -105,6 +108,11 @@ export default function ImportScreen({ status, onComplete }: ImportScreenProps)
                   {t("import.skipped", { count: result.files_skipped })}
                 </span>
               )}
+              {result.failed.length > 0 && (
+                <span className="block text-red-400" title={result.failed.join("\n")}>
+                  {t("import.failed", { count: result.failed.length })}
+                </span>
+              )}
             </p>
           </div>
           <Button onClick={onComplete} className="px-8">
@@ -220,10 +228,14 @@ export default function ImportScreen({ status, onComplete }: ImportScreenProps)
               <div className="h-px flex-1 bg-border" />
             </div>
 
-            <Button onClick={handleExternalImport} variant="outline" size="lg" className="w-full px-8 gap-2">
+            <Button onClick={() => handleExternalImport(false)} variant="outline" size="lg" className="w-full px-8 gap-2">
               <FolderOpen size={16} />
               {t("import.fromFolder")}
             </Button>
+            <Button onClick={() => handleExternalImport(true)} variant="outline" size="lg" className="w-full px-8 gap-2">
+              <FileArchive size={16} />
+              {t("import.fromZip")}
+            </Button>
           </div>
 
           {error && (
@@ -17,7 +17,7 @@ import {
   ContextMenuTrigger,
 } from "@/components/ui/context-menu";
 import PackEditDialog from "@/components/PackEditDialog";
-import { ChevronDown, FolderOpen, Search, Plus, Trash2, Pencil, X, Grid3x3, List, ArrowUpDown } from "lucide-react";
+import { ChevronDown, FileArchive, FolderOpen, Search, Plus, Trash2, Pencil, X, Grid3x3, List, ArrowUpDown } from "lucide-react";
 import { useI18n } from "@/contexts/I18nContext";
 import type { Pack } from "@/types";
 
@@ -29,7 +29,8 @@ interface PacksViewProps {
   onEditPack?: (updated: Pack) => void;
   filter: string;
   onFilterChange: (value: string) => void;
-  onImportExternal?: () => void;
+  /** zip: true면 ZIP 파일 선택 */
+  onImportExternal?: (zip: boolean) => void;
 }
 
 type ViewMode = "grid" | "list";
@@ -163,10 +164,16 @@ export default function PacksView({ packs, loading, onSelectPack, onDeletePack,
           </p>
         </div>
         {onImportExternal && (
-          <Button variant="outline" size="sm" className="h-8 gap-1.5 text-xs" onClick={onImportExternal}>
-            <Plus size={14} />
-            {t("packs.addExternal")}
-          </Button>
+          <div className="flex items-center gap-1.5">
+            <Button variant="outline" size="sm" className="h-8 gap-1.5 text-xs" onClick={() => onImportExternal(false)}>
+              <Plus size={14} />
+              {t("packs.addExternal")}
+            </Button>
+            <Button variant="outline" size="sm" className="h-8 gap-1.5 text-xs" onClick={() => onImportExternal(true)}>
+              <FileArchive size={14} />
+              {t("packs.addZip")}
+            </Button>
+          </div>
         )}
       </div>
 
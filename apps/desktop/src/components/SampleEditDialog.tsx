@@ -62,7 +62,7 @@ export default function SampleEditDialog({ sample, open, onOpenChange, onSaved }
         genre: genre.trim() || null,
         audio_key: audioKey || null,
         chord_type: chordType || null,
-        bpm: bpm ? parseInt(bpm, 10) : null,
+        bpm: bpm ? parseFloat(bpm) : null,
         sample_type: sampleType || null,
       };
       const updated = await invoke<Sample>("update_sample", { update });
@@ -165,6 +165,7 @@ export default function SampleEditDialog({ sample, open, onOpenChange, onSaved }
                 type="number"
                 min={0}
                 max={999}
+                step="any"
                 value={bpm}
                 onChange={(e) => setBpm(e.target.value)}
                 placeholder="예: 120"
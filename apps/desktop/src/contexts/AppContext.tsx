@@ -59,7 +59,7 @@ interface AppContextType {
   // 외부 임포트
   externalImporting: boolean;
   externalProgress: ImportProgress | null;
-  importExternalFolder: () => Promise<void>;
+  importExternalFolder: (zip?: boolean) => Promise<void>;
   folderTree: FolderNode | null;
   cancelFolderSelect: () => void;
   confirmFolderSelect: (selectedPaths: string[], replaceMap: Record<string, string>) => Promise<void>;
@@ -799,9 +799,11 @@ export function AppProvider({ children }: { children: ReactNode }) {
     };
   }, [externalImporting]);
 
-  // 1단계: 폴더 선택 → 스캔 → 트리 표시
-  const importExternalFolder = useCallback(async () => {
-    const selected = await open({ directory: true, title: t("import.folderDialogTitle") });
+  // 1단계: 폴더(또는 ZIP 파일) 선택 → 스캔 → 트리 표시
+  const importExternalFolder = useCallback(async (zip = false) => {
+    const selected = zip
+      ? await open({ title: t("import.zipDialogTitle"), filters: [{ name: "ZIP", extensions: ["zip"] }] })
+      : await open({ directory: true, title: t("import.folderDialogTitle") });
     if (!selected) return;
 
     try {
@@ -41,6 +41,7 @@ const en: Record<TranslationKey, string> = {
   "settings.spliceSync": "Splice Sync",
   "settings.importResult": "Imported {files} files from {packs} packs.",
   "settings.skipped": "({count} skipped)",
+  "settings.failed": "({count} could not be placed)",
   "settings.exportLib": "Export Library",
   "settings.exportDesc": "Export all samples as ZIP file (includes metadata)",
   "settings.exportDialogTitle": "Export Full Library",
@@ -60,6 +61,7 @@ const en: Record<TranslationKey, string> = {
   "import.complete": "Import Complete",
   "import.resultMsg": "Copied {files} files from {packs} packs.",
   "import.skipped": "({count} already existed, skipped)",
+  "import.failed": "{count} files could not be placed in the library and were not imported",
   "import.openLibrary": "Open Library",
   "import.copying": "Copying files…",
   "import.packProgress": "Pack progress",
@@ -75,6 +77,8 @@ const en: Record<TranslationKey, string> = {
   "import.or": "or",
   "import.fromFolder": "Import from External Folder",
   "import.folderDialogTitle": "Select Sample Pack Folder",
+  "import.fromZip": "Import from ZIP File",
+  "import.zipDialogTitle": "Select Sample Pack ZIP File",
 
   // App (external import overlay)
   "app.externalImporting": "Importing external packs…",
@@ -151,6 +155,7 @@ const en: Record<TranslationKey, string> = {
   "packs.packsAndSamples": "{packs} packs · {samples} samples",
   "packs.filteredCount": "{filtered} / {total} packs",
   "packs.addExternal": "Add External Pack",
+  "packs.addZip": "Add ZIP Pack",
   "packs.gridView": "Grid View",
   "packs.listView": "List View",
   "packs.searchPacks": "Search packs...",
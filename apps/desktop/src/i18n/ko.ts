@@ -39,6 +39,7 @@ const ko = {
   "settings.spliceSync": "Splice 동기화",
   "settings.importResult": "{packs}개 팩에서 {files}개 파일을 가져왔습니다.",
   "settings.skipped": "({count}개 건너뜀)",
+  "settings.failed": "({count}개 배치 실패)",
   "settings.exportLib": "라이브러리 내보내기",
   "settings.exportDesc": "전체 샘플을 ZIP 파일로 내보냅니다 (메타데이터 포함)",
   "settings.exportDialogTitle": "전체 라이브러리 내보내기",
@@ -58,6 +59,7 @@ const ko = {
   "import.complete": "Import 완료",
   "import.resultMsg": "{packs}개 팩에서 {files}개 파일을 복사했습니다.",
   "import.skipped": "({count}개 이미 존재하여 건너뜀)",
+  "import.failed": "{count}개 파일을 라이브러리에 배치하지 못해 가져오지 않았습니다",
   "import.openLibrary": "라이브러리 열기",
   "import.copying": "파일 복사 중…",
   "import.packProgress": "팩 진행",
@@ -73,6 +75,8 @@ const ko = {
   "import.or": "또는",
   "import.fromFolder": "외부 폴더에서 가져오기",
   "import.folderDialogTitle": "샘플팩 폴더 선택",
+  "import.fromZip": "ZIP 파일에서 가져오기",
+  "import.zipDialogTitle": "샘플팩 ZIP 파일 선택",
 
   // App (external import overlay)
   "app.externalImporting": "외부 팩 임포트 중…",
@@ -149,6 +153,7 @@ const ko = {
   "packs.packsAndSamples": "{packs}개 팩 · {samples}개 샘플",
   "packs.filteredCount": "{filtered} / {total}개 팩",
   "packs.addExternal": "외부 팩 추가",
+  "packs.addZip": "ZIP 팩 추가",
   "packs.gridView": "그리드 뷰",
   "packs.listView": "리스트 뷰",
   "packs.searchPacks": "팩 검색...",
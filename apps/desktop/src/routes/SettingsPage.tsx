@@ -136,8 +136,13 @@ export default function SettingsPage() {
     }
   }, []);
 
+  // 오디오 바이트 기준 진행률 (바이트 정보가 없으면 파일 개수 기준)
   const exportPct = exportProgress
-    ? Math.round((exportProgress.current / exportProgress.total) * 100)
+    ? Math.round(
+        (exportProgress.bytes_total > 0
+          ? exportProgress.bytes_done / exportProgress.bytes_total
+          : exportProgress.current / exportProgress.total) * 100,
+      )
     : 0;
 
   // ── Delete All state ─────────────────────────────────────────────
@@ -428,6 +433,11 @@ export default function SettingsPage() {
                       {" "}{t("settings.skipped", { count: spliceResult.files_skipped })}
                     </span>
                   )}
+                  {spliceResult.failed.length > 0 && (
+                    <span className="text-red-400" title={spliceResult.failed.join("\n")}>
+                      {" "}{t("settings.failed", { count: spliceResult.failed.length })}
+                    </span>
+                  )}
                 </div>
               )}
 
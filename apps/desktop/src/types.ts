@@ -1,3 +1,6 @@
+// 임포트 시 파일을 라이브러리에 두는 방식
+export type StorageMode = "copy" | "hardlink" | "symlink" | "reference";
+
 export interface Pack {
   uuid: string;
   name: string;
@@ -5,6 +8,7 @@ export interface Pack {
   cover_url: string | null;
   sample_count: number;
   created_at: string | null;
+  storage_mode: StorageMode;
 }
 
 export interface Sample {
@@ -22,6 +26,36 @@ export interface Sample {
   pack_name: string | null;
   pack_genre: string | null;
   created_at: string | null;
+  provenance: SampleProvenance;
+  /** 루프 길이에 맞는 마디 수 (4/4) */
+  bars: number | null;
+  /** 파일명 BPM이 루프 길이와 맞지 않음 */
+  bpm_mismatch: boolean;
+}
+
+export type MetadataSource =
+  | "splice"
+  | "filename"
+  | "path"
+  | "pack"
+  | "audio"
+  | "user"
+  | "classifier"
+  | "embedded"
+  | "sidecar" // Slice 내보내기 번들의 _metadata.json
+  | "unknown";
+
+export interface FieldProvenance {
+  source: MetadataSource;
+  confidence: number | null; // 0.0–1.0
+}
+
+export interface SampleProvenance {
+  bpm: FieldProvenance | null;
+  audio_key: FieldProvenance | null;
+  sample_type: FieldProvenance | null;
+  genre: FieldProvenance | null;
+  tags: FieldProvenance | null;
 }
 
 export interface Playlist {
@@ -30,6 +64,28 @@ export interface Playlist {
   color: string | null;
   sample_count: number;
   created_at: string | null;
+  /** 스마트 플레이리스트 규칙 (null이면 일반 플레이리스트) */
+  rules: SmartPlaylistRules | null;
+}
+
+/** playlists.rules에 저장되는 스마트 플레이리스트 규칙 (조건은 모두 AND) */
+export interface SmartPlaylistRules {
+  version?: number;
+  q?: string;
+  includeTags?: string[];
+  excludeTags?: string[];
+  instruments?: string[];
+  bpmMin?: number;
+  bpmMax?: number;
+  keys?: string[];
+  packUuid?: string;
+  genres?: string[];
+  type?: SampleType;
+  addedAfter?: string;
+  addedBefore?: string;
+  sortBy?: SortBy;
+  sortDir?: SortDir;
+  limit?: number;
 }
 
 export interface WaveformData {
@@ -43,6 +99,15 @@ export interface LibraryData {
   total_samples: number;
 }
 
+export interface SpliceDbInfo {
+  path: string;
+  account: string; // "<계정>/<해시>"
+  pack_count: number;
+  sample_count: number;
+  selected: boolean;
+  is_override: boolean;
+}
+
 export interface LibraryStatus {
   has_data: boolean;
   pack_count: number;
@@ -57,18 +122,132 @@ export interface ImportProgress {
   current_pack: number;
   total_packs: number;
   current_pack_name: string;
+  job_id: number | null; // 자동 동기화는 null
 }
 
 export interface ImportResult {
   files_copied: number;
   files_skipped: number;
   total_packs: number;
+  added: number;   // 새로 추가된 샘플 수
+  updated: number; // 메타데이터/경로가 갱신된 샘플 수
+  removed: number; // 원본에서 사라져 제거된 샘플 수
+  failed: string[]; // 배치하지 못해 건너뛴 파일의 오류 메시지
+}
+
+// `library-changed` 이벤트 페이로드 (자동 동기화 결과)
+export interface LibraryChanged {
+  added_sample_ids: number[];
+  added: number;
+  updated: number;
+  removed: number;
 }
 
 export interface ExportProgress {
   current: number;
   total: number;
   current_file: string;
+  job_id: number | null;
+  bytes_done: number;  // 지금까지 ZIP에 쓴 오디오 바이트
+  bytes_total: number;
+}
+
+export type JobKind = "splice_import" | "external_import" | "export";
+export type JobStatus = "running" | "completed" | "cancelled" | "failed";
+
+export interface JobInfo {
+  id: number;
+  kind: JobKind;
+  status: JobStatus;
+  current: number;
+  total: number;
+  current_file: string;
+  result: ImportResult | number | null;
+  error: string | null;
+  started_at: number; // unix seconds
+  finished_at: number | null;
+}
+
+export interface RelocateProgress {
+  current: number;
+  total: number;
+  current_file: string;
+}
+
+export interface ScanProgress {
+  current: number;
+  total: number;
+  current_file: string;
+}
+
+export interface HealthIssue {
+  sample_id: number;
+  filename: string;
+  local_path: string;
+  pack_name: string | null;
+  issue: "missing" | "empty" | "undecodable";
+  detail: string | null;
+}
+
+export interface HealthReport {
+  scanned: number;
+  issues: HealthIssue[];
+}
+
+export interface KeyDetection {
+  sample_id: number;
+  audio_key: string | null; // "C#" 등 음 이름
+  chord_type: string | null; // "major" | "minor"
+  confidence: number; // 0.0–1.0
+  applied: boolean;
+}
+
+export type Analyzer = "bpm" | "key" | "sample_type" | "genre" | "tags";
+
+export interface FieldChange {
+  field: Analyzer;
+  before: string | null;
+  after: string | null;
+  source: MetadataSource;
+  confidence: number;
+  applied: boolean; // false면 사용자 편집/Splice 값이라 보존됨
+}
+
+export interface ReanalyzeDiff {
+  sample_id: number;
+  filename: string;
+  changes: FieldChange[];
+}
+
+/** write_tags_to_files — 파일에 기록할(된) 태그 필드 */
+export interface TagWriteChange {
+  field: "bpm" | "key" | "genre" | "tags";
+  before: string | null; // 지금 파일에 있는 값
+  after: string;
+}
+
+export interface TagWriteReport {
+  sample_id: number;
+  filename: string;
+  local_path: string;
+  status: "unchanged" | "pending" | "written" | "unsupported" | "failed"; // pending: dry run
+  changes: TagWriteChange[];
+  error: string | null;
+}
+
+export interface RelinkCandidate {
+  path: string;
+  score: number; // 0.0–1.0
+  name_match: boolean;
+  size_match: boolean;
+  fingerprint_score: number | null;
+}
+
+export interface RelinkProposal {
+  sample_id: number;
+  filename: string;
+  old_path: string;
+  candidates: RelinkCandidate[]; // 점수 내림차순
 }
 
 export interface PackConflict {
@@ -105,6 +284,95 @@ export interface SampleFilterSearch {
   sortDir?: SortDir;
 }
 
+/** search_samples 필터: SampleFilterSearch + 정렬 시드/범위 */
+export interface SampleSearchFilter extends SampleFilterSearch {
+  shuffleSeed?: number;
+  packUuid?: string;
+  playlistId?: number;
+  addedAfter?: string;
+  addedBefore?: string;
+  bpmMismatch?: boolean;
+}
+
+export interface SamplePage {
+  samples: Sample[];
+  total: number;
+  page: number;
+  page_size: number;
+}
+
+export interface FacetCount {
+  value: string;
+  count: number;
+}
+
+/** min ≤ bpm ≤ max (bpmMin/bpmMax 필터와 같이 양 끝 포함, 경계값은 이웃 구간에도 포함) */
+export interface BpmBucketCount {
+  min: number;
+  max: number;
+  count: number;
+}
+
+/** get_facets: 각 facet은 자기 선택만 뺀 나머지 필터 기준 개수 */
+export interface SampleFacets {
+  total: number;
+  genres: FacetCount[];
+  instruments: FacetCount[];
+  tags: FacetCount[];
+  keys: FacetCount[];
+  sample_types: FacetCount[];
+  bpm_buckets: BpmBucketCount[];
+}
+
+/** 샘플의 태그별 출처/신뢰도 (classifier: 사용자 교정으로 학습한 제안) */
+export interface SampleTagInfo {
+  name: string;
+  source: MetadataSource | null;
+  confidence: number | null;
+}
+
+export interface TagInfo {
+  id: number;
+  name: string;
+  sample_count: number;
+}
+
+export type RuleTarget = "tag" | "genre";
+/** keyword: 포함, regex: 정규식, segment: 폴더 이름 일치, tag: 기존 태그 → 상위 태그 */
+export type RuleMatcher = "keyword" | "regex" | "segment" | "tag";
+
+/** 태그/장르 자동 부여 규칙 (목록 순서 = 우선순위) */
+export interface MetadataRule {
+  id?: number | null;
+  target: RuleTarget;
+  matcher: RuleMatcher;
+  pattern: string;
+  value: string;
+  enabled: boolean;
+}
+
+/** 파일에 내장된 루프 구간 (WAV smpl / AIFF INST) */
+export interface LoopPoint {
+  start_ms: number;
+  end_ms: number;
+  loop_type: "forward" | "pingpong" | "backward";
+  /** 0 = 무한 반복 */
+  play_count: number;
+}
+
+/** 파일에 내장된 큐 마커 (WAV cue / AIFF MARK) */
+export interface CueMarker {
+  position_ms: number;
+  label: string | null;
+}
+
+export interface SampleMarkers {
+  /** MIDI 루트 노트 */
+  root_note: number | null;
+  loops: LoopPoint[];
+  cues: CueMarker[];
+}
+
 export interface PacksSearch {
   q?: string;
 }